@@ -0,0 +1,371 @@
+use std::collections::HashMap;
+
+use bevy::{
+    app::{App, Plugin, PostUpdate},
+    asset::Handle,
+    ecs::{
+        component::Component,
+        schedule::{common_conditions::in_state, IntoSystemConfigs, SystemSet},
+        system::Query,
+    },
+    math::Vec2,
+    sprite::Sprite,
+};
+use bevy_rapier2d::dynamics::Velocity;
+use bevy_trickfilm::{animation::AnimationPlayer2D, asset::AnimationClip2D};
+use serde::Deserialize;
+
+use crate::{status::StatusEffects, GameState};
+
+pub struct CharacterAnimationPlugin;
+
+impl Plugin for CharacterAnimationPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            PostUpdate,
+            play_animations
+                .in_set(AnimationSystem)
+                .run_if(in_state(GameState::Playing)),
+        );
+    }
+}
+
+/// Plays the clips picked by [`AnimationState`]s. Systems updating the states
+/// should run before it.
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct AnimationSystem;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Deserialize)]
+pub enum AnimationAction {
+    Idle,
+    Walk,
+    Sprint,
+    Dodge,
+    Attack,
+    Cast,
+    Hit,
+    Die,
+}
+
+impl AnimationAction {
+    /// One-shot actions play once and return to locomotion, except dying.
+    pub fn is_one_shot(self) -> bool {
+        matches!(self, Self::Attack | Self::Cast | Self::Hit | Self::Die)
+    }
+
+    /// A one-shot can't interrupt one of higher priority.
+    fn priority(self) -> u8 {
+        match self {
+            Self::Die => 3,
+            Self::Hit => 2,
+            Self::Attack | Self::Cast => 1,
+            _ => 0,
+        }
+    }
+
+    /// Played instead when a set has no clip for this action.
+    fn fallback(self) -> Option<Self> {
+        match self {
+            Self::Walk => Some(Self::Idle),
+            Self::Sprint => Some(Self::Walk),
+            Self::Dodge => Some(Self::Sprint),
+            Self::Cast => Some(Self::Attack),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Deserialize)]
+pub enum Facing {
+    Down,
+    DownLeft,
+    Left,
+    UpLeft,
+    Up,
+    UpRight,
+    Right,
+    DownRight,
+}
+
+impl Facing {
+    pub fn direction(self) -> Vec2 {
+        match self {
+            Self::Down => Vec2::NEG_Y,
+            Self::DownLeft => Vec2::new(-1.0, -1.0).normalize(),
+            Self::Left => Vec2::NEG_X,
+            Self::UpLeft => Vec2::new(-1.0, 1.0).normalize(),
+            Self::Up => Vec2::Y,
+            Self::UpRight => Vec2::new(1.0, 1.0).normalize(),
+            Self::Right => Vec2::X,
+            Self::DownRight => Vec2::new(1.0, -1.0).normalize(),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct AnimationClip {
+    pub clip: Handle<AnimationClip2D>,
+    /// Mirrors the sprite, e.g. to face right with a left-facing clip.
+    pub flip_x: bool,
+}
+
+/// The clips an entity can play, by action and facing. Sets may cover four or
+/// eight facings, the one closest to the entity's direction is played.
+#[derive(Component, Clone, Debug, Default)]
+pub struct AnimationSet {
+    clips: HashMap<(AnimationAction, Facing), AnimationClip>,
+    /// Movement speed at which an action's clip plays at normal speed.
+    reference_speeds: HashMap<AnimationAction, f32>,
+}
+
+impl AnimationSet {
+    pub fn insert(
+        &mut self,
+        action: AnimationAction,
+        facing: Facing,
+        clip: Handle<AnimationClip2D>,
+        flip_x: bool,
+    ) -> &mut Self {
+        self.clips
+            .insert((action, facing), AnimationClip { clip, flip_x });
+        self
+    }
+
+    /// Ties the playback speed of `action` to how fast the entity moves.
+    pub fn with_reference_speed(mut self, action: AnimationAction, speed: f32) -> Self {
+        self.reference_speeds.insert(action, speed);
+        self
+    }
+
+    /// The action played for `action`, following fallbacks for missing clips.
+    pub fn resolve(&self, action: AnimationAction) -> Option<AnimationAction> {
+        let mut action = Some(action);
+        while let Some(current) = action {
+            if self
+                .clips
+                .keys()
+                .any(|(clip_action, _)| *clip_action == current)
+            {
+                return Some(current);
+            }
+            action = current.fallback();
+        }
+        None
+    }
+
+    /// The clip of `action` facing closest to `direction`.
+    pub fn get(
+        &self,
+        action: AnimationAction,
+        direction: Vec2,
+    ) -> Option<(Facing, &AnimationClip)> {
+        let action = self.resolve(action)?;
+        self.clips
+            .iter()
+            .filter(|((clip_action, _), _)| *clip_action == action)
+            .max_by(|((_, a), _), ((_, b), _)| {
+                a.direction()
+                    .dot(direction)
+                    .total_cmp(&b.direction().dot(direction))
+            })
+            .map(|((_, facing), clip)| (*facing, clip))
+    }
+
+    pub fn playback_speed(&self, action: AnimationAction, movement_speed: f32) -> f32 {
+        self.reference_speeds
+            .get(&action)
+            .map_or(1.0, |reference| movement_speed / reference)
+    }
+}
+
+/// What an entity is doing, from which its [`AnimationSet`] clip is picked.
+#[derive(Component, Clone, Debug)]
+pub struct AnimationState {
+    /// Looping action played when no one-shot is, set by the owner.
+    pub locomotion: AnimationAction,
+    /// Where the entity faces. Left alone when it stops moving.
+    pub direction: Vec2,
+    one_shot: Option<AnimationAction>,
+    /// The action and facing whose clip was last started.
+    playing: Option<(AnimationAction, Facing)>,
+}
+
+impl Default for AnimationState {
+    fn default() -> Self {
+        Self::facing(Vec2::NEG_Y)
+    }
+}
+
+impl AnimationState {
+    /// Idle, facing `direction`.
+    pub fn facing(direction: Vec2) -> Self {
+        Self {
+            locomotion: AnimationAction::Idle,
+            direction,
+            one_shot: None,
+            playing: None,
+        }
+    }
+
+    /// Plays `action` once before returning to locomotion, returning whether it
+    /// will play. Ignored without a clip for it or while a more important
+    /// one-shot plays, e.g. attacking while dying.
+    pub fn play_once(&mut self, set: &AnimationSet, action: AnimationAction) -> bool {
+        if set.resolve(action).is_none()
+            || self
+                .one_shot
+                .is_some_and(|current| current.priority() > action.priority())
+        {
+            return false;
+        }
+        self.one_shot = Some(action);
+        // restarts the clip even if it is already playing
+        self.playing = None;
+        true
+    }
+
+    pub fn is_playing_once(&self, action: AnimationAction) -> bool {
+        self.one_shot == Some(action)
+    }
+
+    /// The action currently shown.
+    pub fn action(&self) -> AnimationAction {
+        self.one_shot.unwrap_or(self.locomotion)
+    }
+
+    /// Stops any one-shot, e.g. when coming back to life.
+    pub fn reset(&mut self) {
+        self.one_shot = None;
+        self.playing = None;
+    }
+
+    /// Ends the one-shot whose clip finished. Dying holds its last frame.
+    fn finish_one_shot(&mut self) {
+        if self.one_shot != Some(AnimationAction::Die) {
+            self.one_shot = None;
+        }
+    }
+}
+
+type AnimatedData<'a> = (
+    &'a AnimationSet,
+    &'a mut AnimationState,
+    &'a mut AnimationPlayer2D,
+    &'a mut Sprite,
+    Option<&'a Velocity>,
+    Option<&'a StatusEffects>,
+);
+
+/// Starts a clip only when the action or facing changes, so clips aren't
+/// restarted every frame.
+fn play_animations(mut animated_q: Query<AnimatedData>) {
+    for (set, mut state, mut animator, mut sprite, velocity, effects) in animated_q.iter_mut() {
+        if state.one_shot.is_some() && state.playing.is_some() && animator.is_finished() {
+            state.finish_one_shot();
+        }
+
+        let action = state.action();
+        // one-shots keep the facing they started with
+        let keep_facing =
+            action.is_one_shot() && state.playing.is_some_and(|(playing, _)| playing == action);
+        if !keep_facing {
+            let Some((facing, clip)) = set.get(action, state.direction) else {
+                continue;
+            };
+            if state.playing != Some((action, facing)) {
+                let player = animator.play(clip.clip.clone());
+                if !action.is_one_shot() {
+                    player.repeat();
+                }
+                sprite.flip_x = clip.flip_x;
+                state.playing = Some((action, facing));
+            }
+        }
+
+        let movement_speed = velocity.map_or(0.0, |velocity| velocity.linvel.length());
+        let speed = if action.is_one_shot() {
+            1.0
+        } else {
+            set.playback_speed(action, movement_speed)
+        } * effects.map_or(1.0, StatusEffects::speed_multiplier);
+        if animator.speed() != speed {
+            animator.set_speed(speed);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn four_way_set() -> AnimationSet {
+        let mut set = AnimationSet::default();
+        for action in [AnimationAction::Idle, AnimationAction::Attack] {
+            for facing in [Facing::Down, Facing::Left, Facing::Right, Facing::Up] {
+                set.insert(action, facing, Handle::default(), facing == Facing::Right);
+            }
+        }
+        set
+    }
+
+    #[test]
+    fn picks_the_closest_facing() {
+        let set = four_way_set();
+        let facing = |direction| set.get(AnimationAction::Idle, direction).unwrap().0;
+        assert_eq!(facing(Vec2::new(1.0, 0.4)), Facing::Right);
+        assert_eq!(facing(Vec2::new(0.4, 1.0)), Facing::Up);
+        assert_eq!(facing(Vec2::new(-0.2, -1.0)), Facing::Down);
+    }
+
+    #[test]
+    fn missing_actions_fall_back() {
+        let set = four_way_set();
+        assert_eq!(
+            set.resolve(AnimationAction::Sprint),
+            Some(AnimationAction::Idle)
+        );
+        assert_eq!(
+            set.resolve(AnimationAction::Cast),
+            Some(AnimationAction::Attack)
+        );
+        assert_eq!(set.resolve(AnimationAction::Hit), None);
+    }
+
+    #[test]
+    fn one_shots_respect_priority() {
+        let mut set = four_way_set();
+        set.insert(AnimationAction::Die, Facing::Down, Handle::default(), false);
+        let mut state = AnimationState::default();
+
+        assert!(!state.play_once(&set, AnimationAction::Hit));
+        assert!(state.play_once(&set, AnimationAction::Attack));
+        assert!(state.play_once(&set, AnimationAction::Die));
+        assert!(!state.play_once(&set, AnimationAction::Attack));
+        assert_eq!(state.action(), AnimationAction::Die);
+
+        state.finish_one_shot();
+        assert_eq!(state.action(), AnimationAction::Die);
+        state.reset();
+        assert_eq!(state.action(), AnimationAction::Idle);
+    }
+
+    #[test]
+    fn finished_one_shots_return_to_locomotion() {
+        let set = four_way_set();
+        let mut state = AnimationState {
+            locomotion: AnimationAction::Walk,
+            ..Default::default()
+        };
+        state.play_once(&set, AnimationAction::Attack);
+        assert_eq!(state.action(), AnimationAction::Attack);
+        state.finish_one_shot();
+        assert_eq!(state.action(), AnimationAction::Walk);
+    }
+
+    #[test]
+    fn playback_speed_follows_movement() {
+        let set = four_way_set().with_reference_speed(AnimationAction::Walk, 75.0);
+        assert_eq!(set.playback_speed(AnimationAction::Walk, 150.0), 2.0);
+        assert_eq!(set.playback_speed(AnimationAction::Idle, 150.0), 1.0);
+    }
+}
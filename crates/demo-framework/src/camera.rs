@@ -4,7 +4,10 @@ use bevy::{
     core_pipeline::core_2d::Camera2dBundle,
     ecs::{
         query::With,
-        schedule::{common_conditions::in_state, IntoSystemConfigs, OnEnter},
+        schedule::{
+            common_conditions::{in_state, run_once},
+            IntoSystemConfigs, OnEnter,
+        },
         system::{Commands, Query, Res, ResMut, Resource},
     },
     math::{Quat, Vec2, Vec3},
@@ -61,7 +64,9 @@ impl Plugin for CameraPlugin {
             .register_type::<LightPassParams>()
             .add_systems(
                 OnEnter(GameState::Playing),
-                setup_main_camera.after(setup_post_processing_camera),
+                setup_main_camera
+                    .after(setup_post_processing_camera)
+                    .run_if(run_once()),
             )
             .add_systems(
                 Update,
@@ -0,0 +1,413 @@
+use std::ops::BitOr;
+
+use bevy::{
+    app::{App, Plugin, Update},
+    ecs::{
+        component::Component,
+        entity::Entity,
+        event::{Event, EventReader, EventWriter},
+        query::Without,
+        schedule::{common_conditions::in_state, IntoSystemConfigs, SystemSet},
+        system::{Commands, Query, Res},
+    },
+    time::{Time, Timer},
+};
+use rand::Rng;
+
+use crate::{
+    player::{Player, Resistances, Stats},
+    skill::Element,
+    status::StatusEffects,
+    GameState,
+};
+
+/// Defense at which half of the physical damage is blocked.
+const DEFENSE_HALF_POINT: f32 = 50.0;
+/// Resistances are capped so no element can be fully ignored.
+const MAX_RESISTANCE: f32 = 75.0;
+const MIN_RESISTANCE: f32 = -100.0;
+pub const CRIT_MULTIPLIER: f32 = 1.5;
+
+pub struct DamagePlugin;
+
+impl Plugin for DamagePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<DamageEvent>()
+            .add_event::<DamageDealt>()
+            .add_event::<Died>()
+            .add_systems(
+                Update,
+                (tick_invulnerability, apply_damage)
+                    .chain()
+                    .in_set(DamageSet)
+                    .run_if(in_state(GameState::Playing)),
+            );
+    }
+}
+
+/// Applies [`DamageEvent`]s. Systems sending them should run before it and
+/// systems reading [`DamageDealt`] or [`Died`] after it.
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct DamageSet;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct DamageFlags(u8);
+
+impl DamageFlags {
+    pub const NONE: Self = Self(0);
+    /// Never rolls a critical hit.
+    pub const NO_CRIT: Self = Self(1);
+    pub const IGNORE_DEFENSE: Self = Self(1 << 1);
+    pub const IGNORE_RESISTANCES: Self = Self(1 << 2);
+    /// Doesn't chill, burn or shock, e.g. for damage over time.
+    pub const NO_STATUS: Self = Self(1 << 3);
+
+    pub const fn contains(self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+}
+
+impl BitOr for DamageFlags {
+    type Output = Self;
+
+    fn bitor(self, other: Self) -> Self {
+        Self(self.0 | other.0)
+    }
+}
+
+/// Damage about to be dealt to `target`, before mitigation.
+#[derive(Event, Clone, Copy, Debug)]
+pub struct DamageEvent {
+    pub source: Option<Entity>,
+    pub target: Entity,
+    pub element: Element,
+    pub amount: f32,
+    /// Between 0 and 1.
+    pub crit_chance: f32,
+    pub flags: DamageFlags,
+}
+
+/// Sent for every [`DamageEvent`] that got through, e.g. for hit feedback.
+#[derive(Event, Clone, Copy, Debug)]
+pub struct DamageDealt {
+    pub source: Option<Entity>,
+    pub target: Entity,
+    pub element: Element,
+    /// After mitigation.
+    pub amount: f32,
+    pub critical: bool,
+}
+
+/// Sent once when an entity's health drops to zero.
+#[derive(Event, Clone, Copy, Debug)]
+pub struct Died {
+    pub entity: Entity,
+    pub killer: Option<Entity>,
+}
+
+#[derive(Component, Clone, Copy, Debug)]
+pub struct Health {
+    pub current: f32,
+    pub max: f32,
+}
+
+impl Health {
+    pub fn new(max: f32) -> Self {
+        Self { current: max, max }
+    }
+
+    pub fn is_dead(&self) -> bool {
+        self.current <= 0.0
+    }
+
+    /// Removes `amount` of health, returning whether this killed the entity.
+    pub fn take_damage(&mut self, amount: f32) -> bool {
+        if self.is_dead() {
+            return false;
+        }
+        self.current = (self.current - amount.max(0.0)).max(0.0);
+        self.is_dead()
+    }
+
+    pub fn heal(&mut self, amount: f32) {
+        if !self.is_dead() {
+            self.current = (self.current + amount.max(0.0)).min(self.max);
+        }
+    }
+}
+
+/// Ignores all damage until the timer runs out, e.g. while dodging.
+#[derive(Component, Clone, Debug)]
+pub struct Invulnerable(pub Timer);
+
+/// Fraction of physical damage blocked by `defense`, approaching 1 as defense
+/// grows.
+pub fn defense_reduction(defense: f32) -> f32 {
+    let defense = defense.max(0.0);
+    defense / (defense + DEFENSE_HALF_POINT)
+}
+
+/// Damage multiplier for a resistance in percent. Negative resistances increase
+/// the damage taken.
+pub fn resistance_multiplier(resistance: f32) -> f32 {
+    1.0 - resistance.clamp(MIN_RESISTANCE, MAX_RESISTANCE) / 100.0
+}
+
+/// Resistance in percent against an element. Physical damage is handled by
+/// defense instead.
+pub fn resistance_to(resistances: &Resistances, element: Element) -> f32 {
+    match element {
+        Element::Physical => 0.0,
+        Element::Cold => resistances.cold,
+        Element::Fire => resistances.fire,
+        Element::Lightning => resistances.light,
+    }
+}
+
+/// Damage left once the target's defense and resistances are applied.
+pub fn mitigate(
+    amount: f32,
+    element: Element,
+    defense: f32,
+    resistances: &Resistances,
+    flags: DamageFlags,
+) -> f32 {
+    let mut amount = amount.max(0.0);
+    if element == Element::Physical && !flags.contains(DamageFlags::IGNORE_DEFENSE) {
+        amount *= 1.0 - defense_reduction(defense);
+    }
+    if !flags.contains(DamageFlags::IGNORE_RESISTANCES) {
+        amount *= resistance_multiplier(resistance_to(resistances, element));
+    }
+    amount
+}
+
+/// Rolls a critical hit, `roll` being uniform between 0 and 1. Returns the
+/// damage and whether it was critical.
+pub fn roll_crit(amount: f32, crit_chance: f32, roll: f32, flags: DamageFlags) -> (f32, bool) {
+    if !flags.contains(DamageFlags::NO_CRIT) && roll < crit_chance {
+        (amount * CRIT_MULTIPLIER, true)
+    } else {
+        (amount, false)
+    }
+}
+
+type DamageTarget<'a> = (
+    &'a mut Health,
+    Option<&'a Player>,
+    Option<&'a Stats>,
+    Option<&'a StatusEffects>,
+);
+
+fn tick_invulnerability(
+    mut commands: Commands,
+    mut invulnerable_q: Query<(Entity, &mut Invulnerable)>,
+    time: Res<Time>,
+) {
+    for (entity, mut invulnerable) in invulnerable_q.iter_mut() {
+        if invulnerable.0.tick(time.delta()).finished() {
+            commands.entity(entity).remove::<Invulnerable>();
+        }
+    }
+}
+
+/// Invulnerable targets take no damage and can't die.
+fn apply_damage(
+    mut target_q: Query<DamageTarget, Without<Invulnerable>>,
+    mut damage_events: EventReader<DamageEvent>,
+    mut dealt_events: EventWriter<DamageDealt>,
+    mut died_events: EventWriter<Died>,
+) {
+    let mut rng = rand::thread_rng();
+    let no_resistances = Resistances::default();
+
+    for event in damage_events.read() {
+        let Ok((mut health, player, stats, effects)) = target_q.get_mut(event.target) else {
+            continue;
+        };
+        let stats = player.map(|player| &player.stats).or(stats);
+        let (defense, resistances) = stats
+            .map(|stats| (stats.defense, &stats.resistances))
+            .unwrap_or((0.0, &no_resistances));
+
+        if health.is_dead() {
+            continue;
+        }
+        let (amount, critical) = roll_crit(event.amount, event.crit_chance, rng.gen(), event.flags);
+        let amount = mitigate(amount, event.element, defense, resistances, event.flags)
+            * effects.map_or(1.0, StatusEffects::damage_taken_multiplier);
+        dealt_events.send(DamageDealt {
+            source: event.source,
+            target: event.target,
+            element: event.element,
+            amount,
+            critical,
+        });
+        if health.take_damage(amount) {
+            died_events.send(Died {
+                entity: event.target,
+                killer: event.source,
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::{event::Events, system::RunSystemOnce, world::World};
+
+    use super::*;
+
+    fn resistances(cold: f32) -> Resistances {
+        Resistances {
+            cold,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn defense_halves_physical_damage_at_half_point() {
+        assert_eq!(defense_reduction(0.0), 0.0);
+        assert_eq!(defense_reduction(DEFENSE_HALF_POINT), 0.5);
+        assert!(defense_reduction(10_000.0) < 1.0);
+        assert_eq!(defense_reduction(-10.0), 0.0);
+    }
+
+    #[test]
+    fn defense_only_mitigates_physical_damage() {
+        let none = Resistances::default();
+        let physical = mitigate(
+            10.0,
+            Element::Physical,
+            DEFENSE_HALF_POINT,
+            &none,
+            DamageFlags::NONE,
+        );
+        let cold = mitigate(
+            10.0,
+            Element::Cold,
+            DEFENSE_HALF_POINT,
+            &none,
+            DamageFlags::NONE,
+        );
+        assert_eq!(physical, 5.0);
+        assert_eq!(cold, 10.0);
+    }
+
+    #[test]
+    fn resistances_mitigate_their_element() {
+        let damage = mitigate(
+            10.0,
+            Element::Cold,
+            0.0,
+            &resistances(50.0),
+            DamageFlags::NONE,
+        );
+        assert_eq!(damage, 5.0);
+        let damage = mitigate(
+            10.0,
+            Element::Fire,
+            0.0,
+            &resistances(50.0),
+            DamageFlags::NONE,
+        );
+        assert_eq!(damage, 10.0);
+    }
+
+    #[test]
+    fn resistances_are_capped() {
+        assert_eq!(resistance_multiplier(100.0), 0.25);
+        assert_eq!(resistance_multiplier(-500.0), 2.0);
+        assert_eq!(resistance_multiplier(-50.0), 1.5);
+    }
+
+    #[test]
+    fn flags_skip_mitigation() {
+        let flags = DamageFlags::IGNORE_DEFENSE | DamageFlags::IGNORE_RESISTANCES;
+        assert_eq!(
+            mitigate(10.0, Element::Physical, 50.0, &resistances(0.0), flags),
+            10.0
+        );
+        assert_eq!(
+            mitigate(10.0, Element::Cold, 0.0, &resistances(75.0), flags),
+            10.0
+        );
+    }
+
+    #[test]
+    fn negative_damage_is_ignored() {
+        assert_eq!(
+            mitigate(
+                -5.0,
+                Element::Cold,
+                0.0,
+                &resistances(-50.0),
+                DamageFlags::NONE
+            ),
+            0.0
+        );
+    }
+
+    #[test]
+    fn crits_below_chance() {
+        assert_eq!(roll_crit(10.0, 0.25, 0.1, DamageFlags::NONE), (15.0, true));
+        assert_eq!(
+            roll_crit(10.0, 0.25, 0.25, DamageFlags::NONE),
+            (10.0, false)
+        );
+        assert_eq!(roll_crit(10.0, 0.0, 0.0, DamageFlags::NONE), (10.0, false));
+        assert_eq!(
+            roll_crit(10.0, 1.0, 0.1, DamageFlags::NO_CRIT),
+            (10.0, false)
+        );
+    }
+
+    #[test]
+    fn health_dies_once() {
+        let mut health = Health::new(10.0);
+        assert!(!health.take_damage(4.0));
+        assert_eq!(health.current, 6.0);
+        assert!(health.take_damage(20.0));
+        assert_eq!(health.current, 0.0);
+        assert!(!health.take_damage(5.0));
+    }
+
+    #[test]
+    fn heal_is_capped_and_needs_life() {
+        let mut health = Health::new(10.0);
+        health.take_damage(5.0);
+        health.heal(20.0);
+        assert_eq!(health.current, 10.0);
+        health.take_damage(10.0);
+        health.heal(5.0);
+        assert!(health.is_dead());
+    }
+
+    #[test]
+    fn dealt_damage_reports_crits() {
+        let mut world = World::new();
+        world.init_resource::<Events<DamageEvent>>();
+        world.init_resource::<Events<DamageDealt>>();
+        world.init_resource::<Events<Died>>();
+        let target = world.spawn(Health::new(100.0)).id();
+        for crit_chance in [1.0, 0.0] {
+            world.send_event(DamageEvent {
+                source: None,
+                target,
+                element: Element::Physical,
+                amount: 10.0,
+                crit_chance,
+                flags: DamageFlags::NONE,
+            });
+        }
+
+        world.run_system_once(apply_damage);
+
+        let dealt: Vec<_> = world
+            .resource_mut::<Events<DamageDealt>>()
+            .drain()
+            .map(|dealt| (dealt.amount, dealt.critical))
+            .collect();
+        assert_eq!(dealt, vec![(10.0 * CRIT_MULTIPLIER, true), (10.0, false)]);
+    }
+}
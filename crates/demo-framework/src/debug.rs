@@ -1,5 +1,10 @@
 use bevy::app::{App, Plugin};
 
+#[cfg(debug_assertions)]
+mod editor;
+#[cfg(debug_assertions)]
+mod navigation;
+
 pub struct DebugPlugin;
 
 impl Plugin for DebugPlugin {
@@ -14,6 +19,8 @@ impl Plugin for DebugPlugin {
                 diagnostic::EntityCountDiagnosticsPlugin::default(),
                 bevy_inspector_egui::quick::WorldInspectorPlugin::new(),
                 bevy_rapier2d::render::RapierDebugRenderPlugin::default(),
+                editor::EditorPlugin,
+                navigation::NavigationGizmosPlugin,
             ));
         }
     }
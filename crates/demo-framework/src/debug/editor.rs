@@ -0,0 +1,327 @@
+use std::fs;
+
+use bevy::{
+    app::{App, Plugin, PreUpdate, Update},
+    asset::{io::file::FileAssetReader, Assets},
+    ecs::{
+        entity::Entity,
+        schedule::{common_conditions::in_state, Condition, IntoSystemConfigs},
+        system::{Commands, Query, Res, ResMut, Resource, SystemParam},
+    },
+    hierarchy::DespawnRecursiveExt,
+    input::{keyboard::KeyCode, mouse::MouseButton, ButtonInput},
+    log::{error, info},
+    math::{IVec2, Vec2},
+    render::texture::Image,
+};
+use bevy_ecs_tilemap::tiles::{TilePos, TileStorage, TileTextureIndex};
+use bevy_inspector_egui::{
+    bevy_egui::EguiContexts,
+    egui::{self, load::SizedTexture},
+};
+use ron::ser::PrettyConfig;
+
+use crate::{
+    loading::{MapAssets, TextureAssets},
+    player::{
+        input::{MouseWorldCoords, PlayerInput, PlayerInputSet},
+        navigation::PlayerNavigationSet,
+    },
+    world::{
+        chunk::{ground_texture_index, Chunk},
+        helpers::world_pos_to_tile_pos,
+        ChunkDeltas,
+    },
+    GameState,
+};
+
+/// Where edits are saved, relative to the asset base path. Matches the asset
+/// folder configured for the `AssetPlugin` in `demo-game`.
+const DELTAS_FILE: &str = "../../assets/maps/world.deltas.ron";
+const PALETTE_TILE_SIZE: f32 = 32.0;
+
+pub struct EditorPlugin;
+
+impl Plugin for EditorPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<EditorState>()
+            .add_systems(
+                PreUpdate,
+                suppress_player_input
+                    .after(PlayerInputSet)
+                    .before(PlayerNavigationSet)
+                    .run_if(in_state(GameState::Playing).and_then(editor_enabled)),
+            )
+            .add_systems(Update, toggle_editor.run_if(in_state(GameState::Playing)))
+            .add_systems(
+                Update,
+                (palette_ui, paint, undo_redo, save_deltas)
+                    .chain()
+                    .after(toggle_editor)
+                    .run_if(in_state(GameState::Playing).and_then(editor_enabled)),
+            );
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+enum Brush {
+    #[default]
+    Ground,
+    Prop,
+}
+
+/// A single change to the world deltas, `None` meaning no hand edit.
+#[derive(Clone, Copy, Debug)]
+struct Edit {
+    brush: Brush,
+    chunk_pos: IVec2,
+    tile_pos: TilePos,
+    before: Option<u32>,
+    after: Option<u32>,
+}
+
+#[derive(Resource, Default)]
+struct EditorState {
+    enabled: bool,
+    brush: Brush,
+    ground_index: u32,
+    prop_index: u32,
+    pointer_over_ui: bool,
+    undo: Vec<Edit>,
+    redo: Vec<Edit>,
+}
+
+fn editor_enabled(state: Res<EditorState>) -> bool {
+    state.enabled
+}
+
+fn toggle_editor(mut state: ResMut<EditorState>, keys: Res<ButtonInput<KeyCode>>) {
+    if keys.just_pressed(KeyCode::F2) {
+        state.enabled = !state.enabled;
+        info!(
+            "World editor {}",
+            if state.enabled { "enabled" } else { "disabled" }
+        );
+    }
+}
+
+/// Mouse buttons paint while the editor is open, so they must not cast skills
+/// or walk the player around. Likewise the undo and save shortcuts must not
+/// drink potions or move the player.
+fn suppress_player_input(keys: Res<ButtonInput<KeyCode>>, mut player_input: ResMut<PlayerInput>) {
+    player_input.is_left_attack = false;
+    player_input.is_right_attack = false;
+    player_input.clicked_at = None;
+    player_input.belt_slot = None;
+    if keys.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight]) {
+        player_input.movement_direction = Vec2::ZERO;
+    }
+}
+
+fn palette_ui(
+    mut contexts: EguiContexts,
+    mut state: ResMut<EditorState>,
+    texture_assets: Res<TextureAssets>,
+    images: Res<Assets<Image>>,
+) {
+    let (atlas, selected) = match state.brush {
+        Brush::Ground => (texture_assets.grass_land.clone_weak(), state.ground_index),
+        Brush::Prop => (
+            texture_assets.grass_land_decorative.clone_weak(),
+            state.prop_index,
+        ),
+    };
+    let Some(image) = images.get(&atlas) else {
+        return;
+    };
+    let columns = image.size().x / PALETTE_TILE_SIZE as u32;
+    let rows = image.size().y / PALETTE_TILE_SIZE as u32;
+    let texture_id = contexts.add_image(atlas);
+
+    let mut brush = state.brush;
+    let mut picked = None;
+    let ctx = contexts.ctx_mut();
+    egui::Window::new("World editor")
+        .default_width(360.0)
+        .show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                ui.selectable_value(&mut brush, Brush::Ground, "Ground");
+                ui.selectable_value(&mut brush, Brush::Prop, "Props");
+            });
+            ui.label(format!("Selected: {selected}"));
+            ui.label("LMB paint, RMB erase, Ctrl+Z/Ctrl+Y undo/redo, Ctrl+S save");
+            ui.separator();
+            egui::ScrollArea::vertical().show(ui, |ui| {
+                ui.horizontal_wrapped(|ui| {
+                    ui.spacing_mut().item_spacing = egui::Vec2::splat(1.0);
+                    for index in 0..columns * rows {
+                        let min = egui::pos2(
+                            (index % columns) as f32 / columns as f32,
+                            (index / columns) as f32 / rows as f32,
+                        );
+                        let max = min + egui::vec2(1.0 / columns as f32, 1.0 / rows as f32);
+                        let button = egui::ImageButton::new(SizedTexture::new(
+                            texture_id,
+                            egui::Vec2::splat(PALETTE_TILE_SIZE),
+                        ))
+                        .uv(egui::Rect::from_min_max(min, max))
+                        .selected(index == selected);
+                        if ui.add(button).clicked() {
+                            picked = Some(index);
+                        }
+                    }
+                });
+            });
+        });
+
+    state.pointer_over_ui = ctx.wants_pointer_input() || ctx.is_pointer_over_area();
+    state.brush = brush;
+    if let Some(index) = picked {
+        match brush {
+            Brush::Ground => state.ground_index = index,
+            Brush::Prop => state.prop_index = index,
+        }
+    }
+}
+
+/// Everything needed to apply an [`Edit`] to the deltas and the spawned world.
+#[derive(SystemParam)]
+struct WorldEdits<'w, 's> {
+    commands: Commands<'w, 's>,
+    map_assets: Res<'w, MapAssets>,
+    chunk_deltas: ResMut<'w, Assets<ChunkDeltas>>,
+    chunk_q: Query<'w, 's, (Entity, &'static Chunk, &'static TileStorage)>,
+    tile_q: Query<'w, 's, &'static mut TileTextureIndex>,
+}
+
+impl WorldEdits<'_, '_> {
+    fn current(&self, brush: Brush, chunk_pos: IVec2, tile_pos: TilePos) -> Option<u32> {
+        let deltas = self.chunk_deltas.get(&self.map_assets.world_deltas)?;
+        match brush {
+            Brush::Ground => deltas.tile(chunk_pos, tile_pos),
+            Brush::Prop => deltas.prop(chunk_pos, tile_pos),
+        }
+    }
+
+    fn apply(&mut self, edit: &Edit, value: Option<u32>) {
+        let Some(deltas) = self.chunk_deltas.get_mut(&self.map_assets.world_deltas) else {
+            return;
+        };
+        match edit.brush {
+            Brush::Ground => deltas.set_tile(edit.chunk_pos, edit.tile_pos, value),
+            Brush::Prop => deltas.set_prop(edit.chunk_pos, edit.tile_pos, value),
+        };
+
+        // chunks that aren't spawned pick the edit up once they are generated
+        let Some((chunk_entity, _, storage)) = self
+            .chunk_q
+            .iter()
+            .find(|(_, chunk, _)| chunk.pos == edit.chunk_pos)
+        else {
+            return;
+        };
+
+        match edit.brush {
+            Brush::Ground => {
+                if let Some(mut texture_index) = storage
+                    .get(&edit.tile_pos)
+                    .and_then(|tile| self.tile_q.get_mut(tile).ok())
+                {
+                    *texture_index =
+                        ground_texture_index(edit.chunk_pos, edit.tile_pos, Some(deltas));
+                }
+            }
+            Brush::Prop => {
+                // regenerated with the new props by the chunk spawner next frame
+                self.commands.entity(chunk_entity).despawn_recursive();
+            }
+        }
+    }
+}
+
+fn paint(
+    mut state: ResMut<EditorState>,
+    mouse: Res<ButtonInput<MouseButton>>,
+    mouse_coords: Res<MouseWorldCoords>,
+    mut world_edits: WorldEdits,
+) {
+    if state.pointer_over_ui {
+        return;
+    }
+    let after = if mouse.pressed(MouseButton::Left) {
+        Some(match state.brush {
+            Brush::Ground => state.ground_index,
+            Brush::Prop => state.prop_index,
+        })
+    } else if mouse.pressed(MouseButton::Right) {
+        None
+    } else {
+        return;
+    };
+
+    let (chunk_pos, tile_pos) = world_pos_to_tile_pos(&mouse_coords.0);
+    let before = world_edits.current(state.brush, chunk_pos, tile_pos);
+    // dragging over a tile that already has the value shouldn't fill the history
+    if before == after {
+        return;
+    }
+
+    let edit = Edit {
+        brush: state.brush,
+        chunk_pos,
+        tile_pos,
+        before,
+        after,
+    };
+    world_edits.apply(&edit, after);
+    state.undo.push(edit);
+    state.redo.clear();
+}
+
+fn undo_redo(
+    mut state: ResMut<EditorState>,
+    keys: Res<ButtonInput<KeyCode>>,
+    mut world_edits: WorldEdits,
+) {
+    if !keys.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight]) {
+        return;
+    }
+    let shift = keys.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]);
+    let is_redo = keys.just_pressed(KeyCode::KeyY) || (shift && keys.just_pressed(KeyCode::KeyZ));
+    let is_undo = !is_redo && keys.just_pressed(KeyCode::KeyZ);
+
+    if is_undo {
+        if let Some(edit) = state.undo.pop() {
+            world_edits.apply(&edit, edit.before);
+            state.redo.push(edit);
+        }
+    } else if is_redo {
+        if let Some(edit) = state.redo.pop() {
+            world_edits.apply(&edit, edit.after);
+            state.undo.push(edit);
+        }
+    }
+}
+
+fn save_deltas(
+    keys: Res<ButtonInput<KeyCode>>,
+    map_assets: Res<MapAssets>,
+    chunk_deltas: Res<Assets<ChunkDeltas>>,
+) {
+    let ctrl = keys.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight]);
+    if !(ctrl && keys.just_pressed(KeyCode::KeyS)) {
+        return;
+    }
+    let Some(deltas) = chunk_deltas.get(&map_assets.world_deltas) else {
+        return;
+    };
+
+    let path = FileAssetReader::get_base_path().join(DELTAS_FILE);
+    let result = ron::ser::to_string_pretty(deltas, PrettyConfig::default())
+        .map_err(|e| e.to_string())
+        .and_then(|ron| fs::write(&path, ron).map_err(|e| e.to_string()));
+    match result {
+        Ok(()) => info!("Saved world edits to {}", path.display()),
+        Err(e) => error!("Failed to save world edits to {}: {}", path.display(), e),
+    }
+}
@@ -0,0 +1,53 @@
+use bevy::{
+    app::{App, Plugin, Update},
+    ecs::{
+        schedule::{common_conditions::in_state, IntoSystemConfigs},
+        system::Query,
+    },
+    gizmos::{
+        config::{GizmoConfig, GizmoConfigGroup},
+        gizmos::Gizmos,
+        AppGizmoBuilder,
+    },
+    reflect::Reflect,
+    render::{color::Color, view::RenderLayers},
+    transform::components::Transform,
+};
+use bevy_magic_light_2d::gi::render_layer::CAMERA_LAYER_OBJECTS;
+
+use crate::{player::navigation::NavPath, GameState};
+
+const PATH_COLOR: Color = Color::YELLOW;
+const DESTINATION_RADIUS: f32 = 4.0;
+
+/// Draws the paths followed with click-to-move.
+pub struct NavigationGizmosPlugin;
+
+impl Plugin for NavigationGizmosPlugin {
+    fn build(&self, app: &mut App) {
+        // the default gizmo layer isn't rendered by the lit cameras
+        app.insert_gizmo_group(
+            NavigationGizmos,
+            GizmoConfig {
+                render_layers: RenderLayers::from_layers(CAMERA_LAYER_OBJECTS),
+                ..Default::default()
+            },
+        )
+        .add_systems(Update, draw_nav_paths.run_if(in_state(GameState::Playing)));
+    }
+}
+
+#[derive(Default, Reflect, GizmoConfigGroup)]
+struct NavigationGizmos;
+
+fn draw_nav_paths(mut gizmos: Gizmos<NavigationGizmos>, path_q: Query<(&Transform, &NavPath)>) {
+    for (transform, path) in path_q.iter() {
+        gizmos.linestrip_2d(
+            std::iter::once(transform.translation.truncate()).chain(path.waypoints.iter().copied()),
+            PATH_COLOR,
+        );
+        if let Some(destination) = path.waypoints.back() {
+            gizmos.circle_2d(*destination, DESTINATION_RADIUS, PATH_COLOR);
+        }
+    }
+}
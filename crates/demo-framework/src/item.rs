@@ -0,0 +1,544 @@
+pub mod consumable;
+pub mod equipment;
+pub mod generation;
+pub mod loot;
+
+use std::collections::HashMap;
+
+use bevy::{
+    app::{App, Plugin, Update},
+    asset::{Asset, Assets},
+    core::Name,
+    ecs::{
+        component::Component,
+        entity::Entity,
+        event::{Event, EventReader},
+        query::{Added, Changed, With},
+        schedule::{common_conditions::in_state, IntoSystemConfigs},
+        system::{Commands, Query, Res, SystemParam},
+    },
+    hierarchy::{BuildChildren, DespawnRecursiveExt},
+    math::{Vec2, Vec3},
+    reflect::TypePath,
+    render::{color::Color, view::RenderLayers},
+    sprite::{Anchor, Sprite, SpriteBundle},
+    text::{Text, Text2dBundle, TextStyle},
+    transform::components::Transform,
+};
+use bevy_magic_light_2d::gi::{render_layer::CAMERA_LAYER_OBJECTS, types::OmniLightSource2D};
+use serde::Deserialize;
+
+use crate::{
+    loading::{DataAssets, TextureAssets},
+    player::{
+        input::PlayerInput,
+        stats::{Modifier, ModifierKind, Modifiers, StatKind},
+        Player,
+    },
+    GameState,
+};
+
+use self::{
+    consumable::{Consumable, ConsumablePlugin},
+    equipment::{EquipmentPlugin, ItemSlot, Requirements},
+    generation::{GeneratedItem, Rarity},
+    loot::{Dropped, LootPlugin},
+};
+
+/// Items on the ground can be picked up from this far away.
+const PICKUP_RANGE: f32 = 40.0;
+const GROUND_ITEM_SIZE: f32 = 10.0;
+const GROUND_ITEM_Z: f32 = 0.5;
+/// Source of the modifiers added by charms in the inventory.
+const INVENTORY_SOURCE: &str = "inventory";
+
+pub struct ItemPlugin;
+
+impl Plugin for ItemPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins((EquipmentPlugin, ConsumablePlugin, LootPlugin))
+            .add_event::<DropItem>()
+            .add_systems(
+                Update,
+                (
+                    starting_items,
+                    pick_up_items,
+                    drop_items,
+                    inventory_modifiers,
+                )
+                    .chain()
+                    .run_if(in_state(GameState::Playing)),
+            );
+    }
+}
+
+/// Identifies an item definition, e.g. `"health_potion"`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Deserialize)]
+#[serde(transparent)]
+pub struct ItemId(pub String);
+
+impl From<&str> for ItemId {
+    fn from(id: &str) -> Self {
+        Self(id.to_string())
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+pub enum ItemKind {
+    #[default]
+    Misc,
+    /// Changes the stats of whoever carries it in their inventory.
+    Charm,
+    /// Changes the stats of whoever wears it.
+    Equipment(ItemSlot),
+}
+
+/// A stat change an item grants, see [`Modifier`].
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize)]
+pub struct ItemModifier {
+    pub stat: StatKind,
+    pub kind: ModifierKind,
+    pub value: f32,
+}
+
+impl ItemModifier {
+    pub fn to_modifier(self, source: &str) -> Modifier {
+        Modifier {
+            stat: self.stat,
+            kind: self.kind,
+            value: self.value,
+            source: source.to_string(),
+        }
+    }
+}
+
+fn default_max_stack() -> u32 {
+    1
+}
+
+fn default_color() -> [f32; 3] {
+    [1.0, 1.0, 1.0]
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct ItemDefinition {
+    pub name: String,
+    #[serde(default)]
+    pub kind: ItemKind,
+    #[serde(default = "default_max_stack")]
+    pub max_stack: u32,
+    #[serde(default)]
+    pub modifiers: Vec<ItemModifier>,
+    /// Needed to equip the item.
+    #[serde(default)]
+    pub requirements: Requirements,
+    /// What using the item does, if it can be used.
+    #[serde(default)]
+    pub consumable: Option<Consumable>,
+    /// Lowest item level this base is generated at.
+    #[serde(default)]
+    pub item_level: u16,
+    /// Color of the item's marker on the ground.
+    #[serde(default = "default_color")]
+    pub color: [f32; 3],
+}
+
+impl ItemDefinition {
+    pub fn color(&self) -> Color {
+        let [r, g, b] = self.color;
+        Color::rgb(r, g, b)
+    }
+}
+
+/// Every item in the game, loaded from `data/*.items.ron`.
+#[derive(Asset, TypePath, Debug, Deserialize)]
+pub struct ItemBook {
+    pub items: HashMap<ItemId, ItemDefinition>,
+    /// What the player starts with.
+    #[serde(default)]
+    pub starting_items: Vec<ItemStack>,
+    /// What the player's potion belt starts bound to.
+    #[serde(default)]
+    pub starting_belt: Vec<ItemId>,
+}
+
+/// Looks item definitions up by id.
+#[derive(SystemParam)]
+pub struct ItemDefinitions<'w> {
+    data_assets: Res<'w, DataAssets>,
+    item_books: Res<'w, Assets<ItemBook>>,
+}
+
+impl ItemDefinitions<'_> {
+    pub fn get(&self, id: &ItemId) -> Option<&ItemDefinition> {
+        self.book().and_then(|book| book.items.get(id))
+    }
+
+    pub fn book(&self) -> Option<&ItemBook> {
+        self.item_books.get(&self.data_assets.items)
+    }
+}
+
+/// Some amount of the same item.
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+pub struct ItemStack {
+    pub item: ItemId,
+    pub count: u32,
+    /// Rarity and affixes of a randomly generated item, which never stacks.
+    #[serde(skip)]
+    pub generated: Option<GeneratedItem>,
+}
+
+impl ItemStack {
+    pub fn new(item: ItemId, count: u32) -> Self {
+        Self {
+            item,
+            count,
+            generated: None,
+        }
+    }
+
+    /// The generated name if any, else the base's.
+    pub fn name<'a>(&'a self, definition: &'a ItemDefinition) -> &'a str {
+        self.generated
+            .as_ref()
+            .map_or(definition.name.as_str(), |generated| {
+                generated.name.as_str()
+            })
+    }
+
+    /// The base's modifiers followed by the rolled ones.
+    pub fn modifiers<'a>(
+        &'a self,
+        definition: &'a ItemDefinition,
+    ) -> impl Iterator<Item = ItemModifier> + 'a {
+        definition
+            .modifiers
+            .iter()
+            .copied()
+            .chain(self.generated.iter().flat_map(GeneratedItem::modifiers))
+    }
+
+    pub fn label_color(&self) -> Color {
+        self.generated
+            .as_ref()
+            .map_or(Color::WHITE, |generated| generated.rarity.color())
+    }
+}
+
+/// A grid of item stacks, stored row by row.
+#[derive(Component, Clone, Debug)]
+pub struct Inventory {
+    pub width: usize,
+    pub height: usize,
+    pub slots: Vec<Option<ItemStack>>,
+}
+
+impl Inventory {
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            slots: vec![None; width * height],
+        }
+    }
+
+    /// Adds up to `count` of an item stacking up to `max_stack`, filling
+    /// existing stacks first. Returns how many didn't fit.
+    pub fn add(&mut self, item: &ItemId, mut count: u32, max_stack: u32) -> u32 {
+        let max_stack = max_stack.max(1);
+        for stack in self.slots.iter_mut().flatten() {
+            if count == 0 {
+                break;
+            }
+            if stack.item == *item && stack.generated.is_none() && stack.count < max_stack {
+                let added = count.min(max_stack - stack.count);
+                stack.count += added;
+                count -= added;
+            }
+        }
+        for slot in self.slots.iter_mut().filter(|slot| slot.is_none()) {
+            if count == 0 {
+                break;
+            }
+            let added = count.min(max_stack);
+            *slot = Some(ItemStack::new(item.clone(), added));
+            count -= added;
+        }
+        count
+    }
+
+    /// Puts a whole stack in the first free slot, giving it back if there is
+    /// none.
+    pub fn insert(&mut self, stack: ItemStack) -> Result<usize, ItemStack> {
+        match self.slots.iter().position(Option::is_none) {
+            Some(slot) => {
+                self.slots[slot] = Some(stack);
+                Ok(slot)
+            }
+            None => Err(stack),
+        }
+    }
+
+    /// Moves as much of a ground stack as fits, leaving the rest in it.
+    /// Generated items are only picked up whole. Returns whether all of it
+    /// was picked up.
+    pub fn pick_up(&mut self, stack: &mut ItemStack, max_stack: u32) -> bool {
+        if stack.generated.is_some() {
+            return self.insert(stack.clone()).is_ok();
+        }
+        stack.count = self.add(&stack.item, stack.count, max_stack);
+        stack.count == 0
+    }
+
+    /// Takes the whole stack out of a slot.
+    pub fn remove(&mut self, slot: usize) -> Option<ItemStack> {
+        self.slots.get_mut(slot).and_then(Option::take)
+    }
+
+    /// Removes `count` of an item if there is enough of it.
+    pub fn take(&mut self, item: &ItemId, mut count: u32) -> bool {
+        if self.count(item) < count {
+            return false;
+        }
+        for slot in self.slots.iter_mut() {
+            let Some(stack) = slot.as_mut().filter(|stack| stack.item == *item) else {
+                continue;
+            };
+            let taken = count.min(stack.count);
+            stack.count -= taken;
+            count -= taken;
+            if stack.count == 0 {
+                *slot = None;
+            }
+            if count == 0 {
+                break;
+            }
+        }
+        true
+    }
+
+    pub fn count(&self, item: &ItemId) -> u32 {
+        self.slots
+            .iter()
+            .flatten()
+            .filter(|stack| stack.item == *item)
+            .map(|stack| stack.count)
+            .sum()
+    }
+
+    pub fn stacks(&self) -> impl Iterator<Item = &ItemStack> {
+        self.slots.iter().flatten()
+    }
+}
+
+impl Default for Inventory {
+    fn default() -> Self {
+        Self::new(10, 4)
+    }
+}
+
+/// How much gold a character carries.
+#[derive(Component, Clone, Copy, Debug, Default)]
+pub struct Gold(pub u32);
+
+/// An item lying in the world.
+#[derive(Component, Debug)]
+pub struct GroundItem(pub ItemStack);
+
+/// Asks to drop the stack in an inventory slot at the entity's feet.
+#[derive(Event, Clone, Copy, Debug)]
+pub struct DropItem {
+    pub entity: Entity,
+    pub slot: usize,
+}
+
+pub fn spawn_ground_item(
+    commands: &mut Commands,
+    texture_assets: &TextureAssets,
+    definition: &ItemDefinition,
+    stack: ItemStack,
+    position: Vec2,
+) -> Entity {
+    let label = if stack.count > 1 {
+        format!("{} ({})", stack.name(definition), stack.count)
+    } else {
+        stack.name(definition).to_string()
+    };
+    let label_color = stack.label_color();
+    // magic and better items glow in their rarity's color
+    let light = stack
+        .generated
+        .as_ref()
+        .filter(|generated| generated.rarity != Rarity::Normal)
+        .map(|generated| OmniLightSource2D {
+            intensity: 0.08,
+            color: generated.rarity.color(),
+            falloff: Vec3::new(6.0, 6.0, 0.05),
+            ..Default::default()
+        });
+
+    let mut entity = commands.spawn((
+        Name::new(format!("ground_item_{}", stack.item.0)),
+        GroundItem(stack),
+        SpriteBundle {
+            sprite: Sprite {
+                color: definition.color(),
+                custom_size: Some(Vec2::splat(GROUND_ITEM_SIZE)),
+                ..Default::default()
+            },
+            transform: Transform::from_translation(position.extend(GROUND_ITEM_Z)),
+            ..Default::default()
+        },
+        RenderLayers::from_layers(CAMERA_LAYER_OBJECTS),
+    ));
+    if let Some(light) = light {
+        entity.insert(light);
+    }
+    entity
+        .with_children(|parent| {
+            parent.spawn(ground_label(
+                texture_assets,
+                label,
+                label_color,
+                GROUND_ITEM_SIZE,
+            ));
+        })
+        .id()
+}
+
+/// The name floating above something on the ground.
+fn ground_label(
+    texture_assets: &TextureAssets,
+    label: String,
+    color: Color,
+    height: f32,
+) -> (Text2dBundle, RenderLayers) {
+    (
+        Text2dBundle {
+            text: Text::from_section(
+                label,
+                TextStyle {
+                    font: texture_assets.dungeon_font.clone(),
+                    font_size: 14.0,
+                    color,
+                },
+            ),
+            text_anchor: Anchor::BottomCenter,
+            transform: Transform::from_translation(Vec3::new(0.0, height, 0.1))
+                .with_scale(Vec3::splat(0.5)),
+            ..Default::default()
+        },
+        RenderLayers::from_layers(CAMERA_LAYER_OBJECTS),
+    )
+}
+
+fn starting_items(
+    mut inventory_q: Query<&mut Inventory, (Added<Inventory>, With<Player>)>,
+    item_definitions: ItemDefinitions,
+) {
+    let Some(book) = item_definitions.book() else {
+        return;
+    };
+    for mut inventory in inventory_q.iter_mut() {
+        for stack in &book.starting_items {
+            let max_stack = book.items.get(&stack.item).map_or(1, |item| item.max_stack);
+            inventory.add(&stack.item, stack.count, max_stack);
+        }
+    }
+}
+
+/// Picks up the items around the player, leaving what doesn't fit.
+fn pick_up_items(
+    mut commands: Commands,
+    mut player_q: Query<(&Transform, &mut Inventory), With<Player>>,
+    mut ground_item_q: Query<(Entity, &mut GroundItem, &Transform)>,
+    item_definitions: ItemDefinitions,
+    player_input: Res<PlayerInput>,
+) {
+    if !player_input.is_picking_up {
+        return;
+    }
+    let Ok((player_transform, mut inventory)) = player_q.get_single_mut() else {
+        return;
+    };
+    let player_pos = player_transform.translation.truncate();
+
+    for (entity, mut ground_item, transform) in ground_item_q.iter_mut() {
+        if transform.translation.truncate().distance(player_pos) > PICKUP_RANGE {
+            continue;
+        }
+        let Some(definition) = item_definitions.get(&ground_item.0.item) else {
+            continue;
+        };
+        if inventory.pick_up(&mut ground_item.0, definition.max_stack) {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+fn drop_items(
+    mut commands: Commands,
+    mut inventory_q: Query<(&Transform, &mut Inventory)>,
+    item_definitions: ItemDefinitions,
+    texture_assets: Res<TextureAssets>,
+    mut drop_events: EventReader<DropItem>,
+) {
+    for event in drop_events.read() {
+        let Ok((transform, mut inventory)) = inventory_q.get_mut(event.entity) else {
+            continue;
+        };
+        let Some(stack) = inventory.remove(event.slot) else {
+            continue;
+        };
+        let Some(definition) = item_definitions.get(&stack.item) else {
+            continue;
+        };
+        let entity = spawn_ground_item(
+            &mut commands,
+            &texture_assets,
+            definition,
+            stack,
+            transform.translation.truncate(),
+        );
+        commands.entity(entity).insert(Dropped);
+    }
+}
+
+/// Applies the modifiers of the charms in an inventory whenever it changes.
+fn inventory_modifiers(
+    mut inventory_q: Query<(&Inventory, &mut Modifiers), Changed<Inventory>>,
+    item_definitions: ItemDefinitions,
+) {
+    for (inventory, mut modifiers) in inventory_q.iter_mut() {
+        modifiers.remove_source(INVENTORY_SOURCE);
+        for stack in inventory.stacks() {
+            let Some(definition) = item_definitions.get(&stack.item) else {
+                continue;
+            };
+            if definition.kind != ItemKind::Charm {
+                continue;
+            }
+            for modifier in stack.modifiers(definition) {
+                for _ in 0..stack.count {
+                    modifiers.add(modifier.to_modifier(INVENTORY_SOURCE));
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pick_up_leaves_what_does_not_fit() {
+        let arrow = ItemId::from("arrow");
+        let mut inventory = Inventory::new(1, 1);
+        let mut stack = ItemStack::new(arrow.clone(), 30);
+        assert!(!inventory.pick_up(&mut stack, 20));
+        assert_eq!(stack.count, 10);
+        assert_eq!(inventory.count(&arrow), 20);
+    }
+}
@@ -0,0 +1,380 @@
+use std::{collections::HashMap, time::Duration};
+
+use bevy::{
+    app::{App, Plugin, Update},
+    ecs::{
+        component::Component,
+        entity::Entity,
+        event::{Event, EventReader, EventWriter},
+        query::{Added, With},
+        schedule::{common_conditions::in_state, IntoSystemConfigs},
+        system::{Query, Res},
+    },
+    log::warn,
+    math::Vec3,
+    time::{Time, Timer, TimerMode},
+    transform::components::{GlobalTransform, Transform},
+};
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::{
+    damage::Health,
+    player::{input::PlayerInput, Player},
+    world::MapSpawnPoint,
+    GameState,
+};
+
+use super::{Inventory, ItemDefinitions, ItemId};
+
+pub const BELT_SLOTS: usize = 4;
+
+pub struct ConsumablePlugin;
+
+impl Plugin for ConsumablePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<UseItem>().add_systems(
+            Update,
+            (
+                starting_belt,
+                tick_consumable_cooldowns,
+                use_belt,
+                use_items,
+                restore_over_time,
+            )
+                .chain()
+                .run_if(in_state(GameState::Playing)),
+        );
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+pub enum ResourcePool {
+    Life,
+    Mana,
+    Stamina,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub enum ConsumeEffect {
+    /// Restores `amount` of a pool, spread over `duration` seconds if any.
+    Restore {
+        pool: ResourcePool,
+        amount: f32,
+        #[serde(default)]
+        duration: f32,
+    },
+    /// Moves the user to the map spawn point of that name.
+    Teleport(String),
+}
+
+/// What happens when an item is used.
+#[derive(Clone, Debug, Deserialize)]
+pub struct Consumable {
+    pub effects: Vec<ConsumeEffect>,
+    /// Items of the same group share their cooldown, e.g. all potions.
+    /// Defaults to the item's own id.
+    #[serde(default)]
+    pub cooldown_group: Option<String>,
+    /// In seconds.
+    #[serde(default)]
+    pub cooldown: f32,
+}
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum UseError {
+    #[error("item can't be used")]
+    NotConsumable,
+    #[error("item is on cooldown")]
+    Cooldown,
+    #[error("no spawn point named `{0}` to teleport to")]
+    NoSpawnPoint(String),
+}
+
+/// Items bound to the quick-use keys, used from the inventory.
+#[derive(Component, Clone, Debug, Default)]
+pub struct PotionBelt {
+    pub slots: [Option<ItemId>; BELT_SLOTS],
+}
+
+/// Cooldowns of consumables, by cooldown group.
+#[derive(Component, Clone, Debug, Default)]
+pub struct ConsumableCooldowns(pub HashMap<String, Timer>);
+
+impl ConsumableCooldowns {
+    pub fn is_ready(&self, group: &str) -> bool {
+        self.0.get(group).map_or(true, Timer::finished)
+    }
+
+    /// From 1 when the cooldown starts down to 0 when it is ready.
+    pub fn remaining_fraction(&self, group: &str) -> f32 {
+        self.0
+            .get(group)
+            .map_or(0.0, |timer| 1.0 - timer.fraction())
+    }
+
+    /// Puts the whole group on cooldown for `seconds`.
+    pub fn start(&mut self, group: &str, seconds: f32) {
+        if seconds > 0.0 {
+            self.0.insert(
+                group.to_string(),
+                Timer::new(Duration::from_secs_f32(seconds), TimerMode::Once),
+            );
+        }
+    }
+}
+
+/// A pool being restored over time.
+#[derive(Clone, Debug)]
+pub struct Restoration {
+    pub pool: ResourcePool,
+    pub per_second: f32,
+    pub timer: Timer,
+}
+
+impl Restoration {
+    pub fn new(pool: ResourcePool, amount: f32, duration: f32) -> Self {
+        Self {
+            pool,
+            per_second: amount / duration,
+            timer: Timer::from_seconds(duration, TimerMode::Once),
+        }
+    }
+
+    /// Advances the restoration, returning the amount to restore.
+    pub fn tick(&mut self, delta: Duration) -> f32 {
+        // the last tick only restores what is left of the duration
+        let elapsed = self.timer.elapsed_secs();
+        self.timer.tick(delta);
+        self.per_second * (self.timer.elapsed_secs() - elapsed)
+    }
+}
+
+#[derive(Component, Clone, Debug, Default)]
+pub struct Restorations(pub Vec<Restoration>);
+
+/// Asks to use the item in an inventory slot.
+#[derive(Event, Clone, Copy, Debug)]
+pub struct UseItem {
+    pub entity: Entity,
+    pub slot: usize,
+}
+
+/// The cooldown group an item belongs to.
+pub fn cooldown_group<'a>(id: &'a ItemId, consumable: &'a Consumable) -> &'a str {
+    consumable.cooldown_group.as_deref().unwrap_or(&id.0)
+}
+
+fn restore(player: &mut Player, health: &mut Health, pool: ResourcePool, amount: f32) {
+    match pool {
+        ResourcePool::Life => health.heal(amount),
+        ResourcePool::Mana => player.stats.mana.restore(amount),
+        ResourcePool::Stamina => player.stats.stamina.restore(amount),
+    }
+}
+
+fn starting_belt(
+    mut belt_q: Query<&mut PotionBelt, Added<PotionBelt>>,
+    item_definitions: ItemDefinitions,
+) {
+    let Some(book) = item_definitions.book() else {
+        return;
+    };
+    for mut belt in belt_q.iter_mut() {
+        for (slot, item) in belt.slots.iter_mut().zip(&book.starting_belt) {
+            *slot = Some(item.clone());
+        }
+    }
+}
+
+fn tick_consumable_cooldowns(mut cooldowns_q: Query<&mut ConsumableCooldowns>, time: Res<Time>) {
+    for mut cooldowns in cooldowns_q.iter_mut() {
+        for timer in cooldowns.0.values_mut() {
+            timer.tick(time.delta());
+        }
+        cooldowns.0.retain(|_, timer| !timer.finished());
+    }
+}
+
+/// Uses the first stack of the item bound to the pressed belt key.
+fn use_belt(
+    player_q: Query<(Entity, &PotionBelt, &Inventory), With<Player>>,
+    player_input: Res<PlayerInput>,
+    mut use_events: EventWriter<UseItem>,
+) {
+    let (Some(belt_slot), Ok((entity, belt, inventory))) =
+        (player_input.belt_slot, player_q.get_single())
+    else {
+        return;
+    };
+    let Some(item) = &belt.slots[belt_slot] else {
+        return;
+    };
+    if let Some(slot) = inventory
+        .slots
+        .iter()
+        .position(|stack| stack.as_ref().is_some_and(|stack| stack.item == *item))
+    {
+        use_events.send(UseItem { entity, slot });
+    }
+}
+
+type ConsumerData<'a> = (
+    &'a mut Player,
+    &'a mut Health,
+    &'a mut Inventory,
+    &'a mut ConsumableCooldowns,
+    &'a mut Restorations,
+    &'a mut Transform,
+);
+
+fn find_spawn_point(
+    spawn_point_q: &Query<(&MapSpawnPoint, &GlobalTransform)>,
+    name: &str,
+) -> Result<Vec3, UseError> {
+    spawn_point_q
+        .iter()
+        .find(|(spawn_point, _)| spawn_point.name == name)
+        .map(|(_, transform)| transform.translation())
+        .ok_or_else(|| UseError::NoSpawnPoint(name.to_string()))
+}
+
+fn use_items(
+    mut player_q: Query<ConsumerData>,
+    spawn_point_q: Query<(&MapSpawnPoint, &GlobalTransform)>,
+    item_definitions: ItemDefinitions,
+    mut use_events: EventReader<UseItem>,
+) {
+    for event in use_events.read() {
+        let Ok((
+            mut player,
+            mut health,
+            mut inventory,
+            mut cooldowns,
+            mut restorations,
+            mut transform,
+        )) = player_q.get_mut(event.entity)
+        else {
+            continue;
+        };
+        let Some((id, consumable)) = inventory
+            .slots
+            .get(event.slot)
+            .and_then(Option::as_ref)
+            .and_then(|stack| {
+                let definition = item_definitions.get(&stack.item)?;
+                Some((stack.item.clone(), definition.consumable.as_ref()?))
+            })
+        else {
+            warn!("can't use item: {}", UseError::NotConsumable);
+            continue;
+        };
+        let group = cooldown_group(&id, consumable);
+        if !cooldowns.is_ready(group) {
+            warn!("can't use item: {}", UseError::Cooldown);
+            continue;
+        }
+        // resolve the targets first so a failed use keeps the item
+        let targets = match consumable
+            .effects
+            .iter()
+            .filter_map(|effect| match effect {
+                ConsumeEffect::Teleport(name) => Some(find_spawn_point(&spawn_point_q, name)),
+                ConsumeEffect::Restore { .. } => None,
+            })
+            .collect::<Result<Vec<_>, _>>()
+        {
+            Ok(targets) => targets,
+            Err(error) => {
+                warn!("can't use item: {error}");
+                continue;
+            }
+        };
+        let mut targets = targets.into_iter();
+
+        for effect in &consumable.effects {
+            match effect {
+                ConsumeEffect::Restore {
+                    pool,
+                    amount,
+                    duration,
+                } if *duration > 0.0 => {
+                    restorations
+                        .0
+                        .push(Restoration::new(*pool, *amount, *duration));
+                }
+                ConsumeEffect::Restore { pool, amount, .. } => {
+                    restore(&mut player, &mut health, *pool, *amount);
+                }
+                ConsumeEffect::Teleport(_) => {
+                    let Some(target) = targets.next() else {
+                        continue;
+                    };
+                    transform.translation.x = target.x;
+                    transform.translation.y = target.y;
+                }
+            }
+        }
+
+        cooldowns.start(group, consumable.cooldown);
+        if let Some(stack) = inventory.slots[event.slot].as_mut() {
+            stack.count -= 1;
+            if stack.count == 0 {
+                inventory.slots[event.slot] = None;
+            }
+        }
+    }
+}
+
+fn restore_over_time(
+    mut player_q: Query<(&mut Player, &mut Health, &mut Restorations)>,
+    time: Res<Time>,
+) {
+    for (mut player, mut health, mut restorations) in player_q.iter_mut() {
+        for restoration in restorations.0.iter_mut() {
+            let amount = restoration.tick(time.delta());
+            restore(&mut player, &mut health, restoration.pool, amount);
+        }
+        restorations
+            .0
+            .retain(|restoration| !restoration.timer.finished());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn potion(group: &str) -> Consumable {
+        Consumable {
+            effects: Vec::new(),
+            cooldown_group: Some(group.to_string()),
+            cooldown: 2.0,
+        }
+    }
+
+    #[test]
+    fn items_of_a_group_share_the_cooldown() {
+        let life = ItemId::from("life_potion");
+        let mana = ItemId::from("mana_potion");
+        let scroll = ItemId::from("town_scroll");
+        let mut cooldowns = ConsumableCooldowns::default();
+        cooldowns.start(cooldown_group(&life, &potion("potion")), 2.0);
+        assert!(!cooldowns.is_ready(cooldown_group(&mana, &potion("potion"))));
+        let own_group = Consumable {
+            cooldown_group: None,
+            ..potion("")
+        };
+        assert!(cooldowns.is_ready(cooldown_group(&scroll, &own_group)));
+    }
+
+    #[test]
+    fn restoration_totals_its_amount() {
+        let mut restoration = Restoration::new(ResourcePool::Life, 50.0, 1.0);
+        let mut total = 0.0;
+        while !restoration.timer.finished() {
+            total += restoration.tick(Duration::from_millis(300));
+        }
+        assert_eq!(total, 50.0);
+    }
+}
@@ -0,0 +1,349 @@
+use std::collections::HashMap;
+
+use bevy::{
+    app::{App, Plugin, Update},
+    ecs::{
+        component::Component,
+        entity::Entity,
+        event::{Event, EventReader, EventWriter},
+        schedule::{common_conditions::in_state, IntoSystemConfigs},
+        system::Query,
+    },
+    log::warn,
+};
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::{
+    player::{
+        stats::{AttributeSet, Modifiers, RespecAttributes},
+        Attributes, Player,
+    },
+    GameState,
+};
+
+use super::{Inventory, ItemDefinition, ItemDefinitions, ItemKind, ItemStack};
+
+pub struct EquipmentPlugin;
+
+impl Plugin for EquipmentPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<EquipItem>()
+            .add_event::<UnequipItem>()
+            .add_systems(
+                Update,
+                (
+                    unequip_unmet_requirements.after(AttributeSet),
+                    equip_items,
+                    unequip_items,
+                )
+                    .chain()
+                    .run_if(in_state(GameState::Playing)),
+            );
+    }
+}
+
+/// Where an item is worn.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Deserialize)]
+pub enum ItemSlot {
+    Weapon,
+    OffHand,
+    Helm,
+    Armour,
+    Gloves,
+    Boots,
+    Ring,
+    Amulet,
+}
+
+impl ItemSlot {
+    /// The equipment slots an item of this slot fits in.
+    pub fn equipment_slots(self) -> &'static [EquipmentSlot] {
+        match self {
+            Self::Weapon => &[EquipmentSlot::Weapon],
+            Self::OffHand => &[EquipmentSlot::OffHand],
+            Self::Helm => &[EquipmentSlot::Helm],
+            Self::Armour => &[EquipmentSlot::Armour],
+            Self::Gloves => &[EquipmentSlot::Gloves],
+            Self::Boots => &[EquipmentSlot::Boots],
+            Self::Ring => &[EquipmentSlot::LeftRing, EquipmentSlot::RightRing],
+            Self::Amulet => &[EquipmentSlot::Amulet],
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum EquipmentSlot {
+    Weapon,
+    OffHand,
+    Helm,
+    Armour,
+    Gloves,
+    Boots,
+    LeftRing,
+    RightRing,
+    Amulet,
+}
+
+impl EquipmentSlot {
+    pub const ALL: [Self; 9] = [
+        Self::Weapon,
+        Self::OffHand,
+        Self::Helm,
+        Self::Armour,
+        Self::Gloves,
+        Self::Boots,
+        Self::LeftRing,
+        Self::RightRing,
+        Self::Amulet,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Weapon => "Weapon",
+            Self::OffHand => "Off-hand",
+            Self::Helm => "Helm",
+            Self::Armour => "Armour",
+            Self::Gloves => "Gloves",
+            Self::Boots => "Boots",
+            Self::LeftRing => "Left ring",
+            Self::RightRing => "Right ring",
+            Self::Amulet => "Amulet",
+        }
+    }
+
+    /// Source of the modifiers added by the item in this slot.
+    fn modifier_source(self) -> String {
+        format!("equipment:{self:?}")
+    }
+}
+
+/// What a character needs to equip an item.
+#[derive(Clone, Copy, Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct Requirements {
+    pub level: u16,
+    pub strength: i32,
+    pub vitality: i32,
+    pub energy: i32,
+}
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum EquipError {
+    #[error("item can't be equipped")]
+    NotEquipment,
+    #[error("requires level {0}")]
+    Level(u16),
+    #[error("requires {0} strength")]
+    Strength(i32),
+    #[error("requires {0} vitality")]
+    Vitality(i32),
+    #[error("requires {0} energy")]
+    Energy(i32),
+    #[error("no room in the inventory")]
+    InventoryFull,
+}
+
+impl Requirements {
+    pub fn check(&self, attributes: &Attributes, level: u16) -> Result<(), EquipError> {
+        if level < self.level {
+            Err(EquipError::Level(self.level))
+        } else if attributes.strength < self.strength {
+            Err(EquipError::Strength(self.strength))
+        } else if attributes.vitality < self.vitality {
+            Err(EquipError::Vitality(self.vitality))
+        } else if attributes.energy < self.energy {
+            Err(EquipError::Energy(self.energy))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// The items a character wears.
+#[derive(Component, Clone, Debug, Default)]
+pub struct Equipment {
+    pub items: HashMap<EquipmentSlot, ItemStack>,
+}
+
+impl Equipment {
+    pub fn get(&self, slot: EquipmentSlot) -> Option<&ItemStack> {
+        self.items.get(&slot)
+    }
+
+    /// The slot an item goes in, preferring an empty one.
+    fn slot_for(&self, item_slot: ItemSlot) -> EquipmentSlot {
+        let slots = item_slot.equipment_slots();
+        slots
+            .iter()
+            .copied()
+            .find(|slot| !self.items.contains_key(slot))
+            .unwrap_or(slots[0])
+    }
+}
+
+/// Asks to equip the item in an inventory slot, swapping out what is worn.
+#[derive(Event, Clone, Copy, Debug)]
+pub struct EquipItem {
+    pub entity: Entity,
+    pub slot: usize,
+}
+
+/// Asks to move a worn item back to the inventory.
+#[derive(Event, Clone, Copy, Debug)]
+pub struct UnequipItem {
+    pub entity: Entity,
+    pub slot: EquipmentSlot,
+}
+
+fn apply_item_modifiers(
+    modifiers: &mut Modifiers,
+    slot: EquipmentSlot,
+    item: Option<(&ItemStack, &ItemDefinition)>,
+) {
+    let source = slot.modifier_source();
+    modifiers.remove_source(&source);
+    let Some((stack, definition)) = item else {
+        return;
+    };
+    for modifier in stack.modifiers(definition) {
+        modifiers.add(modifier.to_modifier(&source));
+    }
+}
+
+fn equip(
+    player: &Player,
+    inventory: &mut Inventory,
+    equipment: &mut Equipment,
+    modifiers: &mut Modifiers,
+    inventory_slot: usize,
+    item_definitions: &ItemDefinitions,
+) -> Result<(), EquipError> {
+    let stack = inventory
+        .slots
+        .get(inventory_slot)
+        .and_then(Option::as_ref)
+        .ok_or(EquipError::NotEquipment)?;
+    let definition = item_definitions
+        .get(&stack.item)
+        .ok_or(EquipError::NotEquipment)?;
+    let ItemKind::Equipment(item_slot) = definition.kind else {
+        return Err(EquipError::NotEquipment);
+    };
+    definition
+        .requirements
+        .check(&player.attributes, player.experience.level)?;
+
+    let slot = equipment.slot_for(item_slot);
+    let Some(stack) = inventory.remove(inventory_slot) else {
+        return Err(EquipError::NotEquipment);
+    };
+    if let Some(worn) = equipment.items.insert(slot, stack) {
+        // the worn item takes the place of the new one
+        inventory.slots[inventory_slot] = Some(worn);
+    }
+    apply_item_modifiers(modifiers, slot, equipment.get(slot).zip(Some(definition)));
+    Ok(())
+}
+
+fn equip_items(
+    mut player_q: Query<(&Player, &mut Inventory, &mut Equipment, &mut Modifiers)>,
+    item_definitions: ItemDefinitions,
+    mut equip_events: EventReader<EquipItem>,
+) {
+    for event in equip_events.read() {
+        let Ok((player, mut inventory, mut equipment, mut modifiers)) =
+            player_q.get_mut(event.entity)
+        else {
+            continue;
+        };
+        if let Err(err) = equip(
+            player,
+            &mut inventory,
+            &mut equipment,
+            &mut modifiers,
+            event.slot,
+            &item_definitions,
+        ) {
+            warn!("can't equip item: {err}");
+        }
+    }
+}
+
+fn unequip_items(
+    mut player_q: Query<(&mut Inventory, &mut Equipment, &mut Modifiers)>,
+    mut unequip_events: EventReader<UnequipItem>,
+) {
+    for event in unequip_events.read() {
+        let Ok((mut inventory, mut equipment, mut modifiers)) = player_q.get_mut(event.entity)
+        else {
+            continue;
+        };
+        let Some(free_slot) = inventory.slots.iter().position(Option::is_none) else {
+            warn!("can't unequip item: {}", EquipError::InventoryFull);
+            continue;
+        };
+        if let Some(stack) = equipment.items.remove(&event.slot) {
+            inventory.slots[free_slot] = Some(stack);
+            apply_item_modifiers(&mut modifiers, event.slot, None);
+        }
+    }
+}
+
+/// Takes off worn items whose requirements aren't met anymore after a respec.
+fn unequip_unmet_requirements(
+    player_q: Query<(&Player, &Equipment)>,
+    item_definitions: ItemDefinitions,
+    mut respec_events: EventReader<RespecAttributes>,
+    mut unequip_events: EventWriter<UnequipItem>,
+) {
+    for event in respec_events.read() {
+        let Ok((player, equipment)) = player_q.get(event.entity) else {
+            continue;
+        };
+        for (slot, stack) in &equipment.items {
+            let unmet = item_definitions.get(&stack.item).is_some_and(|definition| {
+                definition
+                    .requirements
+                    .check(&player.attributes, player.experience.level)
+                    .is_err()
+            });
+            if unmet {
+                unequip_events.send(UnequipItem {
+                    entity: event.entity,
+                    slot: *slot,
+                });
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::player::stats::AttributeKind;
+
+    use super::*;
+
+    #[test]
+    fn spent_attribute_points_meet_requirements() {
+        let requirements = Requirements {
+            strength: 14,
+            ..Default::default()
+        };
+        let mut attributes = Attributes {
+            available_points: 5,
+            ..Default::default()
+        };
+        assert_eq!(
+            requirements.check(&attributes, 1),
+            Err(EquipError::Strength(14))
+        );
+        for _ in 0..4 {
+            attributes.spend(AttributeKind::Strength);
+        }
+        assert_eq!(requirements.check(&attributes, 1), Ok(()));
+
+        attributes.respec();
+        assert!(requirements.check(&attributes, 1).is_err());
+    }
+}
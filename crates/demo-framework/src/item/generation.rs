@@ -0,0 +1,656 @@
+use bevy::{
+    asset::{Asset, Assets},
+    ecs::system::{Res, SystemParam},
+    reflect::TypePath,
+    render::color::Color,
+};
+use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
+use serde::Deserialize;
+
+use crate::{
+    loading::DataAssets,
+    player::stats::{ModifierKind, StatKind},
+};
+
+use super::{equipment::ItemSlot, ItemBook, ItemId, ItemKind, ItemModifier, ItemStack};
+
+/// Affixes a magic item rolls, at most one of them being a prefix and one a
+/// suffix.
+const MAGIC_AFFIXES: (usize, usize) = (1, 2);
+/// Affixes a rare item rolls, at most three of them being prefixes and three
+/// suffixes.
+const RARE_AFFIXES: (usize, usize) = (3, 6);
+/// Rarer items get this much more likely per item level.
+const RARITY_PER_ITEM_LEVEL: f32 = 0.02;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Deserialize)]
+pub enum Rarity {
+    #[default]
+    Normal,
+    Magic,
+    Rare,
+    Unique,
+}
+
+impl Rarity {
+    pub fn color(self) -> Color {
+        match self {
+            Self::Normal => Color::WHITE,
+            Self::Magic => Color::rgb(0.45, 0.55, 1.0),
+            Self::Rare => Color::rgb(1.0, 0.9, 0.35),
+            Self::Unique => Color::rgb(0.8, 0.6, 0.3),
+        }
+    }
+}
+
+/// How likely each rarity is at item level 0.
+#[derive(Clone, Copy, Debug, Deserialize)]
+pub struct RarityWeights {
+    pub normal: f32,
+    pub magic: f32,
+    pub rare: f32,
+    pub unique: f32,
+}
+
+impl Default for RarityWeights {
+    fn default() -> Self {
+        Self {
+            normal: 60.0,
+            magic: 30.0,
+            rare: 9.0,
+            unique: 1.0,
+        }
+    }
+}
+
+impl RarityWeights {
+    /// The weights at `item_level`, higher levels favouring rarer items.
+    pub fn at_level(&self, item_level: u16) -> [(Rarity, f32); 4] {
+        let bonus = 1.0 + item_level as f32 * RARITY_PER_ITEM_LEVEL;
+        [
+            (Rarity::Normal, self.normal),
+            (Rarity::Magic, self.magic * bonus),
+            (Rarity::Rare, self.rare * bonus),
+            (Rarity::Unique, self.unique * bonus),
+        ]
+    }
+}
+
+/// A range of values an affix rolls from, once items are at least
+/// `item_level`.
+#[derive(Clone, Copy, Debug, Deserialize)]
+pub struct AffixTier {
+    pub item_level: u16,
+    pub min: f32,
+    pub max: f32,
+    /// How likely the tier is compared to the other available tiers.
+    pub weight: u32,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+pub enum AffixKind {
+    Prefix,
+    Suffix,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct AffixDefinition {
+    /// Added to the item's name, e.g. `"Frozen"` or `"of the Bear"`.
+    pub name: String,
+    pub kind: AffixKind,
+    pub stat: StatKind,
+    pub modifier: ModifierKind,
+    /// Ordered from the lowest to the highest.
+    pub tiers: Vec<AffixTier>,
+    /// Slots the affix can roll on, any slot when empty.
+    #[serde(default)]
+    pub slots: Vec<ItemSlot>,
+}
+
+impl AffixDefinition {
+    fn fits(&self, slot: ItemSlot, item_level: u16) -> bool {
+        (self.slots.is_empty() || self.slots.contains(&slot))
+            && self.tiers.iter().any(|tier| tier.item_level <= item_level)
+    }
+}
+
+/// A modifier with a fixed range, rolled on every copy of a unique.
+#[derive(Clone, Copy, Debug, Deserialize)]
+pub struct UniqueModifier {
+    pub stat: StatKind,
+    pub kind: ModifierKind,
+    pub min: f32,
+    pub max: f32,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct UniqueDefinition {
+    pub name: String,
+    pub base: ItemId,
+    pub item_level: u16,
+    pub modifiers: Vec<UniqueModifier>,
+}
+
+/// Affixes and uniques for generated items, loaded from `data/*.affixes.ron`.
+#[derive(Asset, TypePath, Debug, Deserialize)]
+pub struct AffixBook {
+    #[serde(default)]
+    pub rarity_weights: RarityWeights,
+    pub affixes: Vec<AffixDefinition>,
+    #[serde(default)]
+    pub uniques: Vec<UniqueDefinition>,
+}
+
+/// An affix as rolled on an item.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RolledAffix {
+    pub name: String,
+    pub kind: AffixKind,
+    /// Index into the affix's tiers.
+    pub tier: usize,
+    pub modifier: ItemModifier,
+}
+
+/// What makes a generated item different from its base.
+#[derive(Clone, Debug, PartialEq)]
+pub struct GeneratedItem {
+    pub name: String,
+    pub rarity: Rarity,
+    pub item_level: u16,
+    pub affixes: Vec<RolledAffix>,
+}
+
+impl GeneratedItem {
+    pub fn modifiers(&self) -> impl Iterator<Item = ItemModifier> + '_ {
+        self.affixes.iter().map(|affix| affix.modifier)
+    }
+}
+
+/// Rolls random items from item bases and affixes. The same seed always
+/// generates the same item.
+pub struct ItemGenerator<'a> {
+    pub items: &'a ItemBook,
+    pub affixes: &'a AffixBook,
+}
+
+impl ItemGenerator<'_> {
+    /// Generates any equipment base available at `item_level`.
+    pub fn generate(&self, item_level: u16, seed: u64) -> Option<ItemStack> {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut bases: Vec<_> = self
+            .items
+            .items
+            .iter()
+            .filter(|(_, item)| {
+                matches!(item.kind, ItemKind::Equipment(_)) && item.item_level <= item_level
+            })
+            .map(|(id, _)| id)
+            .collect();
+        // the map's order isn't stable between runs
+        bases.sort_by(|a, b| a.0.cmp(&b.0));
+        let base = (*bases.choose(&mut rng)?).clone();
+        self.generate_with(&mut rng, &base, item_level)
+    }
+
+    /// Generates an item of the given base.
+    pub fn generate_base(&self, base: &ItemId, item_level: u16, seed: u64) -> Option<ItemStack> {
+        let mut rng = StdRng::seed_from_u64(seed);
+        self.generate_with(&mut rng, base, item_level)
+    }
+
+    fn generate_with(
+        &self,
+        rng: &mut impl Rng,
+        base: &ItemId,
+        item_level: u16,
+    ) -> Option<ItemStack> {
+        let definition = self.items.items.get(base)?;
+        let ItemKind::Equipment(slot) = definition.kind else {
+            return Some(ItemStack::new(base.clone(), 1));
+        };
+
+        let mut rarity = roll_rarity(rng, &self.affixes.rarity_weights, item_level);
+        if rarity == Rarity::Unique {
+            if let Some(unique) = self.roll_unique(rng, base, item_level) {
+                return Some(ItemStack {
+                    generated: Some(unique),
+                    ..ItemStack::new(base.clone(), 1)
+                });
+            }
+            // no unique of this base, so the next best thing
+            rarity = Rarity::Rare;
+        }
+
+        let affixes = match rarity {
+            Rarity::Magic => self.roll_affixes(rng, slot, item_level, MAGIC_AFFIXES, 1),
+            Rarity::Rare => self.roll_affixes(rng, slot, item_level, RARE_AFFIXES, 3),
+            Rarity::Normal | Rarity::Unique => Vec::new(),
+        };
+        let name = affixed_name(&definition.name, &affixes);
+        Some(ItemStack {
+            generated: Some(GeneratedItem {
+                name,
+                rarity,
+                item_level,
+                affixes,
+            }),
+            ..ItemStack::new(base.clone(), 1)
+        })
+    }
+
+    fn roll_unique(
+        &self,
+        rng: &mut impl Rng,
+        base: &ItemId,
+        item_level: u16,
+    ) -> Option<GeneratedItem> {
+        let uniques: Vec<_> = self
+            .affixes
+            .uniques
+            .iter()
+            .filter(|unique| unique.base == *base && unique.item_level <= item_level)
+            .collect();
+        let unique = uniques.choose(rng)?;
+        let affixes = unique
+            .modifiers
+            .iter()
+            .map(|modifier| RolledAffix {
+                name: unique.name.clone(),
+                kind: AffixKind::Suffix,
+                tier: 0,
+                modifier: ItemModifier {
+                    stat: modifier.stat,
+                    kind: modifier.kind,
+                    value: roll_value(rng, modifier.min, modifier.max),
+                },
+            })
+            .collect();
+        Some(GeneratedItem {
+            name: unique.name.clone(),
+            rarity: Rarity::Unique,
+            item_level,
+            affixes,
+        })
+    }
+
+    /// Rolls between `count.0` and `count.1` different affixes, with at most
+    /// `max_per_kind` prefixes and as many suffixes.
+    fn roll_affixes(
+        &self,
+        rng: &mut impl Rng,
+        slot: ItemSlot,
+        item_level: u16,
+        count: (usize, usize),
+        max_per_kind: usize,
+    ) -> Vec<RolledAffix> {
+        let count = rng.gen_range(count.0..=count.1);
+        let mut pool: Vec<&AffixDefinition> = self
+            .affixes
+            .affixes
+            .iter()
+            .filter(|affix| affix.fits(slot, item_level))
+            .collect();
+
+        let mut rolled = Vec::with_capacity(count);
+        while rolled.len() < count && !pool.is_empty() {
+            let affix = pool.swap_remove(rng.gen_range(0..pool.len()));
+            let same_kind = rolled
+                .iter()
+                .filter(|rolled: &&RolledAffix| rolled.kind == affix.kind)
+                .count();
+            if same_kind >= max_per_kind {
+                continue;
+            }
+            if let Some(affix) = roll_affix(rng, affix, item_level) {
+                rolled.push(affix);
+            }
+        }
+        rolled
+    }
+}
+
+pub fn roll_rarity(rng: &mut impl Rng, weights: &RarityWeights, item_level: u16) -> Rarity {
+    let weights = weights.at_level(item_level);
+    let total: f32 = weights.iter().map(|(_, weight)| weight).sum();
+    // an empty range would panic
+    if total <= 0.0 {
+        return Rarity::Normal;
+    }
+    let mut roll = rng.gen_range(0.0..total);
+    for (rarity, weight) in weights {
+        if roll < weight {
+            return rarity;
+        }
+        roll -= weight;
+    }
+    Rarity::Normal
+}
+
+/// Rolls one of the affix's tiers available at `item_level`, then a value
+/// within it.
+pub fn roll_affix(
+    rng: &mut impl Rng,
+    affix: &AffixDefinition,
+    item_level: u16,
+) -> Option<RolledAffix> {
+    let tiers: Vec<_> = affix
+        .tiers
+        .iter()
+        .enumerate()
+        .filter(|(_, tier)| tier.item_level <= item_level)
+        .collect();
+    let (index, tier) = *tiers.choose_weighted(rng, |(_, tier)| tier.weight).ok()?;
+    Some(RolledAffix {
+        name: affix.name.clone(),
+        kind: affix.kind,
+        tier: index,
+        modifier: ItemModifier {
+            stat: affix.stat,
+            kind: affix.modifier,
+            value: roll_value(rng, tier.min, tier.max),
+        },
+    })
+}
+
+fn roll_value(rng: &mut impl Rng, min: f32, max: f32) -> f32 {
+    if min >= max {
+        min
+    } else {
+        rng.gen_range(min..=max)
+    }
+}
+
+/// `"Frozen Iron Helm of the Bear"` from the first prefix and suffix.
+fn affixed_name(base: &str, affixes: &[RolledAffix]) -> String {
+    let first = |kind| affixes.iter().find(|affix| affix.kind == kind);
+    let mut name = base.to_string();
+    if let Some(prefix) = first(AffixKind::Prefix) {
+        name = format!("{} {name}", prefix.name);
+    }
+    if let Some(suffix) = first(AffixKind::Suffix) {
+        name = format!("{name} {}", suffix.name);
+    }
+    name
+}
+
+/// Looks up the books needed to generate items.
+#[derive(SystemParam)]
+pub struct ItemGeneration<'w> {
+    data_assets: Res<'w, DataAssets>,
+    item_books: Res<'w, Assets<ItemBook>>,
+    affix_books: Res<'w, Assets<AffixBook>>,
+}
+
+impl ItemGeneration<'_> {
+    pub fn generator(&self) -> Option<ItemGenerator<'_>> {
+        Some(ItemGenerator {
+            items: self.item_books.get(&self.data_assets.items)?,
+            affixes: self.affix_books.get(&self.data_assets.affixes)?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+    use crate::item::{equipment::Requirements, ItemDefinition};
+
+    fn helm(item_level: u16) -> ItemDefinition {
+        ItemDefinition {
+            name: "Helm".to_string(),
+            kind: ItemKind::Equipment(ItemSlot::Helm),
+            max_stack: 1,
+            modifiers: Vec::new(),
+            requirements: Requirements::default(),
+            consumable: None,
+            item_level,
+            color: [1.0; 3],
+        }
+    }
+
+    fn affix(name: &str, kind: AffixKind, stat: StatKind) -> AffixDefinition {
+        AffixDefinition {
+            name: name.to_string(),
+            kind,
+            stat,
+            modifier: ModifierKind::Flat,
+            tiers: vec![
+                AffixTier {
+                    item_level: 0,
+                    min: 1.0,
+                    max: 5.0,
+                    weight: 3,
+                },
+                AffixTier {
+                    item_level: 10,
+                    min: 6.0,
+                    max: 10.0,
+                    weight: 1,
+                },
+            ],
+            slots: Vec::new(),
+        }
+    }
+
+    fn books() -> (ItemBook, AffixBook) {
+        let items = ItemBook {
+            items: HashMap::from([
+                (ItemId::from("helm"), helm(0)),
+                (ItemId::from("great_helm"), helm(20)),
+            ]),
+            starting_items: Vec::new(),
+            starting_belt: Vec::new(),
+        };
+        let affixes = AffixBook {
+            rarity_weights: RarityWeights::default(),
+            affixes: vec![
+                affix("Sturdy", AffixKind::Prefix, StatKind::Defense),
+                affix("Vital", AffixKind::Prefix, StatKind::MaxLife),
+                affix("Arcane", AffixKind::Prefix, StatKind::MaxMana),
+                affix("Blazing", AffixKind::Prefix, StatKind::FireResistance),
+                affix("of Frost", AffixKind::Suffix, StatKind::ColdResistance),
+                affix(
+                    "of Sparks",
+                    AffixKind::Suffix,
+                    StatKind::LightningResistance,
+                ),
+                affix("of Haste", AffixKind::Suffix, StatKind::CastRate),
+                affix("of Fury", AffixKind::Suffix, StatKind::AttackRate),
+                AffixDefinition {
+                    slots: vec![ItemSlot::Weapon],
+                    ..affix("Sharp", AffixKind::Prefix, StatKind::CritChance)
+                },
+            ],
+            uniques: vec![UniqueDefinition {
+                name: "Crown of Winter".to_string(),
+                base: ItemId::from("helm"),
+                item_level: 5,
+                modifiers: vec![UniqueModifier {
+                    stat: StatKind::ColdResistance,
+                    kind: ModifierKind::Flat,
+                    min: 20.0,
+                    max: 30.0,
+                }],
+            }],
+        };
+        (items, affixes)
+    }
+
+    fn generated(stack: &ItemStack) -> &GeneratedItem {
+        stack
+            .generated
+            .as_ref()
+            .expect("equipment is always generated")
+    }
+
+    #[test]
+    fn same_seed_same_item() {
+        let (items, affixes) = books();
+        let generator = ItemGenerator {
+            items: &items,
+            affixes: &affixes,
+        };
+        for seed in 0..100 {
+            assert_eq!(generator.generate(30, seed), generator.generate(30, seed));
+        }
+    }
+
+    #[test]
+    fn bases_need_their_item_level() {
+        let (items, affixes) = books();
+        let generator = ItemGenerator {
+            items: &items,
+            affixes: &affixes,
+        };
+        for seed in 0..200 {
+            let item = generator.generate(5, seed).unwrap();
+            assert_eq!(item.item, ItemId::from("helm"));
+        }
+        assert!(generator
+            .generate_base(&ItemId::from("nothing"), 5, 0)
+            .is_none());
+    }
+
+    #[test]
+    fn rarity_follows_weights() {
+        let weights = RarityWeights::default();
+        let mut rng = StdRng::seed_from_u64(7);
+        let rolls = 100_000;
+        let mut counts: HashMap<Rarity, u32> = HashMap::new();
+        for _ in 0..rolls {
+            *counts
+                .entry(roll_rarity(&mut rng, &weights, 0))
+                .or_default() += 1;
+        }
+        let share = |rarity| counts.get(&rarity).copied().unwrap_or(0) as f32 / rolls as f32;
+        assert!((share(Rarity::Normal) - 0.60).abs() < 0.01);
+        assert!((share(Rarity::Magic) - 0.30).abs() < 0.01);
+        assert!((share(Rarity::Rare) - 0.09).abs() < 0.01);
+        assert!((share(Rarity::Unique) - 0.01).abs() < 0.005);
+    }
+
+    #[test]
+    fn zero_weights_roll_normal() {
+        let weights = RarityWeights {
+            normal: 0.0,
+            magic: 0.0,
+            rare: 0.0,
+            unique: 0.0,
+        };
+        let mut rng = StdRng::seed_from_u64(7);
+        assert_eq!(roll_rarity(&mut rng, &weights, 50), Rarity::Normal);
+    }
+
+    #[test]
+    fn item_level_favours_rarer_items() {
+        let weights = RarityWeights::default();
+        let mut rng = StdRng::seed_from_u64(7);
+        let normal_share = |rng: &mut StdRng, item_level| {
+            (0..20_000)
+                .filter(|_| roll_rarity(rng, &weights, item_level) == Rarity::Normal)
+                .count()
+        };
+        assert!(normal_share(&mut rng, 50) < normal_share(&mut rng, 0));
+    }
+
+    #[test]
+    fn affix_values_stay_in_their_tier() {
+        let (_, affixes) = books();
+        let mut rng = StdRng::seed_from_u64(3);
+        let mut tier_counts = [0; 2];
+        for _ in 0..10_000 {
+            let rolled = roll_affix(&mut rng, &affixes.affixes[0], 10).unwrap();
+            let tier = &affixes.affixes[0].tiers[rolled.tier];
+            assert!((tier.min..=tier.max).contains(&rolled.modifier.value));
+            tier_counts[rolled.tier] += 1;
+        }
+        // tiers weighted 3 to 1
+        let low_share = tier_counts[0] as f32 / 10_000.0;
+        assert!((low_share - 0.75).abs() < 0.02);
+    }
+
+    #[test]
+    fn higher_tiers_need_their_item_level() {
+        let (_, affixes) = books();
+        let mut rng = StdRng::seed_from_u64(3);
+        for _ in 0..1_000 {
+            let rolled = roll_affix(&mut rng, &affixes.affixes[0], 9).unwrap();
+            assert_eq!(rolled.tier, 0);
+        }
+    }
+
+    #[test]
+    fn affix_counts_match_rarity() {
+        let (items, affixes) = books();
+        let generator = ItemGenerator {
+            items: &items,
+            affixes: &affixes,
+        };
+        for seed in 0..2_000 {
+            let item = generator
+                .generate_base(&ItemId::from("helm"), 1, seed)
+                .unwrap();
+            let item = generated(&item);
+            let count = item.affixes.len();
+            let prefixes = item
+                .affixes
+                .iter()
+                .filter(|affix| affix.kind == AffixKind::Prefix)
+                .count();
+            let suffixes = count - prefixes;
+            match item.rarity {
+                Rarity::Normal => assert_eq!(count, 0),
+                Rarity::Magic => {
+                    assert!((1..=2).contains(&count));
+                    assert!(prefixes <= 1 && suffixes <= 1);
+                }
+                Rarity::Rare => {
+                    assert!((3..=6).contains(&count));
+                    assert!(prefixes <= 3 && suffixes <= 3);
+                }
+                // the only unique needs item level 5
+                Rarity::Unique => panic!("unique below its item level"),
+            }
+
+            let mut names: Vec<_> = item.affixes.iter().map(|affix| &affix.name).collect();
+            names.dedup();
+            names.sort();
+            names.dedup();
+            assert_eq!(names.len(), count, "affixes roll once per item");
+            assert!(names.iter().all(|name| *name != "Sharp"), "weapon only");
+        }
+    }
+
+    #[test]
+    fn uniques_roll_their_own_modifiers() {
+        let (items, mut affixes) = books();
+        affixes.rarity_weights = RarityWeights {
+            normal: 0.0,
+            magic: 0.0,
+            rare: 0.0,
+            unique: 1.0,
+        };
+        let generator = ItemGenerator {
+            items: &items,
+            affixes: &affixes,
+        };
+        for seed in 0..100 {
+            let item = generator
+                .generate_base(&ItemId::from("helm"), 5, seed)
+                .unwrap();
+            let item = generated(&item);
+            assert_eq!(item.rarity, Rarity::Unique);
+            assert_eq!(item.name, "Crown of Winter");
+            let value = item.affixes[0].modifier.value;
+            assert!((20.0..=30.0).contains(&value));
+
+            // no unique for this base, so it rolls rare instead
+            let item = generator
+                .generate_base(&ItemId::from("great_helm"), 20, seed)
+                .unwrap();
+            assert_eq!(generated(&item).rarity, Rarity::Rare);
+        }
+    }
+}
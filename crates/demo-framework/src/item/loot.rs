@@ -0,0 +1,619 @@
+use std::collections::HashMap;
+
+use bevy::{
+    app::{App, Plugin, Update},
+    asset::{Asset, Assets},
+    core::Name,
+    ecs::{
+        component::Component,
+        entity::Entity,
+        event::{Event, EventReader, EventWriter},
+        query::{With, Without},
+        schedule::{common_conditions::in_state, IntoSystemConfigs},
+        system::{Commands, Query, Res, SystemParam},
+    },
+    hierarchy::{BuildChildren, Children, DespawnRecursiveExt},
+    log::warn,
+    math::{Vec2, Vec3},
+    reflect::TypePath,
+    render::{color::Color, view::RenderLayers},
+    sprite::{Sprite, SpriteBundle},
+    transform::components::{GlobalTransform, Transform},
+};
+use bevy_magic_light_2d::gi::{render_layer::CAMERA_LAYER_OBJECTS, types::OmniLightSource2D};
+use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
+use serde::Deserialize;
+
+use crate::{
+    damage::{DamageSet, Died},
+    loading::{DataAssets, TextureAssets},
+    player::{input::PlayerInput, Player},
+    GameState,
+};
+
+use super::{
+    generation::{AffixBook, ItemGenerator},
+    ground_label, spawn_ground_item, Gold, GroundItem, Inventory, ItemBook, ItemDefinitions,
+    ItemId, ItemStack, GROUND_ITEM_SIZE, GROUND_ITEM_Z, PICKUP_RANGE,
+};
+
+/// Nested tables stop rolling this deep, in case they refer to each other.
+const MAX_TABLE_DEPTH: usize = 8;
+/// Drops are scattered this far around where they fell.
+const DROP_SPREAD: f32 = 16.0;
+const GOLD_COLOR: Color = Color::rgb(1.0, 0.84, 0.0);
+const CHEST_COLOR: Color = Color::rgb(0.55, 0.35, 0.15);
+const CHEST_SIZE: Vec2 = Vec2::new(14.0, 10.0);
+const OPENED_CHEST_COLOR: Color = Color::rgb(0.3, 0.2, 0.1);
+const GROUND_GOLD_SIZE: f32 = 6.0;
+
+pub struct LootPlugin;
+
+impl Plugin for LootPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<DropLoot>().add_systems(
+            Update,
+            (
+                (death_loot.after(DamageSet), open_containers),
+                drop_loot,
+                (walk_over_pickup, clicked_pickup),
+            )
+                .chain()
+                .run_if(in_state(GameState::Playing)),
+        );
+    }
+}
+
+fn default_rolls() -> u32 {
+    1
+}
+
+fn default_pickup_radius() -> f32 {
+    12.0
+}
+
+/// What a loot table entry drops.
+#[derive(Clone, Debug, Deserialize)]
+pub enum LootDrop {
+    Nothing,
+    Item {
+        item: ItemId,
+        min: u32,
+        max: u32,
+    },
+    Gold {
+        min: u32,
+        max: u32,
+    },
+    /// Generated equipment of any base available at the item level.
+    Equipment,
+    /// Generated equipment of the given base.
+    Generated(ItemId),
+    /// Rolls another table.
+    Table(String),
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct LootEntry {
+    pub weight: u32,
+    pub drop: LootDrop,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct LootTable {
+    /// How many entries are picked.
+    #[serde(default = "default_rolls")]
+    pub rolls: u32,
+    pub entries: Vec<LootEntry>,
+}
+
+/// Loot tables and pickup settings, loaded from `data/*.loot.ron`.
+#[derive(Asset, TypePath, Debug, Deserialize)]
+pub struct LootBook {
+    /// Gold and items this close to the player are picked up by walking over
+    /// them.
+    #[serde(default = "default_pickup_radius")]
+    pub pickup_radius: f32,
+    pub tables: HashMap<String, LootTable>,
+}
+
+/// What a loot table rolled.
+#[derive(Clone, Debug, PartialEq)]
+pub enum RolledLoot {
+    Item(ItemStack),
+    Gold(u32),
+}
+
+/// Rolls loot from a book's tables.
+pub struct LootRoller<'a> {
+    pub loot: &'a LootBook,
+    pub items: &'a ItemBook,
+    pub affixes: &'a AffixBook,
+}
+
+impl LootRoller<'_> {
+    pub fn roll(&self, table: &str, item_level: u16, rng: &mut impl Rng) -> Vec<RolledLoot> {
+        let mut loot = Vec::new();
+        self.roll_into(table, item_level, rng, 0, &mut loot);
+        loot
+    }
+
+    fn roll_into(
+        &self,
+        table: &str,
+        item_level: u16,
+        rng: &mut impl Rng,
+        depth: usize,
+        loot: &mut Vec<RolledLoot>,
+    ) {
+        if depth >= MAX_TABLE_DEPTH {
+            warn!("loot table `{table}` nests too deep");
+            return;
+        }
+        let Some(table) = self.loot.tables.get(table) else {
+            warn!("unknown loot table `{table}`");
+            return;
+        };
+        let generator = ItemGenerator {
+            items: self.items,
+            affixes: self.affixes,
+        };
+        for _ in 0..table.rolls {
+            let Ok(entry) = table.entries.choose_weighted(rng, |entry| entry.weight) else {
+                return;
+            };
+            match &entry.drop {
+                LootDrop::Nothing => {}
+                LootDrop::Item { item, min, max } => {
+                    let count = rng.gen_range(*min..=(*max).max(*min));
+                    if count > 0 {
+                        loot.push(RolledLoot::Item(ItemStack::new(item.clone(), count)));
+                    }
+                }
+                LootDrop::Gold { min, max } => {
+                    let amount = rng.gen_range(*min..=(*max).max(*min));
+                    if amount > 0 {
+                        loot.push(RolledLoot::Gold(amount));
+                    }
+                }
+                LootDrop::Equipment => {
+                    loot.extend(
+                        generator
+                            .generate(item_level, rng.gen())
+                            .map(RolledLoot::Item),
+                    );
+                }
+                LootDrop::Generated(base) => {
+                    loot.extend(
+                        generator
+                            .generate_base(base, item_level, rng.gen())
+                            .map(RolledLoot::Item),
+                    );
+                }
+                LootDrop::Table(name) => self.roll_into(name, item_level, rng, depth + 1, loot),
+            }
+        }
+    }
+}
+
+/// Looks up the books needed to roll loot.
+#[derive(SystemParam)]
+pub struct LootDefinitions<'w> {
+    data_assets: Res<'w, DataAssets>,
+    loot_books: Res<'w, Assets<LootBook>>,
+    item_books: Res<'w, Assets<ItemBook>>,
+    affix_books: Res<'w, Assets<AffixBook>>,
+}
+
+impl LootDefinitions<'_> {
+    pub fn roller(&self) -> Option<LootRoller<'_>> {
+        Some(LootRoller {
+            loot: self.loot_books.get(&self.data_assets.loot)?,
+            items: self.item_books.get(&self.data_assets.items)?,
+            affixes: self.affix_books.get(&self.data_assets.affixes)?,
+        })
+    }
+}
+
+/// Drops loot from a table when the entity dies or is opened.
+#[derive(Component, Clone, Debug)]
+pub struct Loot {
+    pub table: String,
+    pub item_level: u16,
+}
+
+/// A chest or structure the player opens once to get its loot, with `E` or
+/// by clicking it.
+#[derive(Component, Debug, Default)]
+pub struct LootContainer {
+    pub opened: bool,
+}
+
+/// Gold lying in the world.
+#[derive(Component, Debug)]
+pub struct GroundGold(pub u32);
+
+/// Marks items the player dropped, which aren't picked up by walking over
+/// them until the player walks away.
+#[derive(Component, Debug)]
+pub struct Dropped;
+
+/// Asks to roll a loot table and scatter the loot around `position`.
+#[derive(Event, Clone, Debug)]
+pub struct DropLoot {
+    pub table: String,
+    pub item_level: u16,
+    pub position: Vec2,
+}
+
+/// The marker of a chest placed in a map, darkened once opened.
+#[derive(Component, Debug)]
+pub struct ChestSprite;
+
+pub fn chest_sprite() -> (ChestSprite, SpriteBundle, RenderLayers) {
+    (
+        ChestSprite,
+        SpriteBundle {
+            sprite: Sprite {
+                color: CHEST_COLOR,
+                custom_size: Some(CHEST_SIZE),
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+        RenderLayers::from_layers(CAMERA_LAYER_OBJECTS),
+    )
+}
+
+pub fn spawn_ground_gold(
+    commands: &mut Commands,
+    texture_assets: &TextureAssets,
+    amount: u32,
+    position: Vec2,
+) -> Entity {
+    commands
+        .spawn((
+            Name::new("ground_gold"),
+            GroundGold(amount),
+            SpriteBundle {
+                sprite: Sprite {
+                    color: GOLD_COLOR,
+                    custom_size: Some(Vec2::splat(GROUND_GOLD_SIZE)),
+                    ..Default::default()
+                },
+                transform: Transform::from_translation(position.extend(GROUND_ITEM_Z)),
+                ..Default::default()
+            },
+            OmniLightSource2D {
+                intensity: 0.05,
+                color: GOLD_COLOR,
+                falloff: Vec3::new(4.0, 4.0, 0.05),
+                ..Default::default()
+            },
+            RenderLayers::from_layers(CAMERA_LAYER_OBJECTS),
+        ))
+        .with_children(|parent| {
+            parent.spawn(ground_label(
+                texture_assets,
+                format!("{amount} gold"),
+                GOLD_COLOR,
+                GROUND_GOLD_SIZE,
+            ));
+        })
+        .id()
+}
+
+fn death_loot(
+    loot_q: Query<(&Loot, &GlobalTransform)>,
+    mut died_events: EventReader<Died>,
+    mut drop_events: EventWriter<DropLoot>,
+) {
+    for died in died_events.read() {
+        if let Ok((loot, transform)) = loot_q.get(died.entity) {
+            drop_events.send(DropLoot {
+                table: loot.table.clone(),
+                item_level: loot.item_level,
+                position: transform.translation().truncate(),
+            });
+        }
+    }
+}
+
+/// Opens the containers the player clicks or presses `E` next to.
+fn open_containers(
+    mut container_q: Query<(
+        &mut LootContainer,
+        &Loot,
+        &GlobalTransform,
+        Option<&Children>,
+    )>,
+    mut chest_sprite_q: Query<&mut Sprite, With<ChestSprite>>,
+    player_q: Query<&Transform, With<Player>>,
+    player_input: Res<PlayerInput>,
+    mut drop_events: EventWriter<DropLoot>,
+) {
+    let Ok(player_transform) = player_q.get_single() else {
+        return;
+    };
+    let player_pos = player_transform.translation.truncate();
+
+    for (mut container, loot, transform, children) in container_q.iter_mut() {
+        let position = transform.translation().truncate();
+        let clicked = player_input
+            .clicked_at
+            .is_some_and(|clicked| clicked.distance(position) <= CHEST_SIZE.x);
+        if container.opened
+            || position.distance(player_pos) > PICKUP_RANGE
+            || !(clicked || player_input.is_picking_up)
+        {
+            continue;
+        }
+        container.opened = true;
+        for child in children.into_iter().flatten() {
+            if let Ok(mut sprite) = chest_sprite_q.get_mut(*child) {
+                sprite.color = OPENED_CHEST_COLOR;
+            }
+        }
+        drop_events.send(DropLoot {
+            table: loot.table.clone(),
+            item_level: loot.item_level,
+            position,
+        });
+    }
+}
+
+fn drop_loot(
+    mut commands: Commands,
+    texture_assets: Res<TextureAssets>,
+    loot_definitions: LootDefinitions,
+    mut drop_events: EventReader<DropLoot>,
+) {
+    let Some(roller) = loot_definitions.roller() else {
+        return;
+    };
+    let mut rng = StdRng::from_entropy();
+
+    for event in drop_events.read() {
+        for loot in roller.roll(&event.table, event.item_level, &mut rng) {
+            let offset = Vec2::new(
+                rng.gen_range(-DROP_SPREAD..=DROP_SPREAD),
+                rng.gen_range(-DROP_SPREAD..=DROP_SPREAD),
+            );
+            let position = event.position + offset;
+            match loot {
+                RolledLoot::Item(stack) => {
+                    let Some(definition) = roller.items.items.get(&stack.item) else {
+                        warn!(
+                            "loot table `{}` drops unknown item {:?}",
+                            event.table, stack.item
+                        );
+                        continue;
+                    };
+                    spawn_ground_item(&mut commands, &texture_assets, definition, stack, position);
+                }
+                RolledLoot::Gold(amount) => {
+                    spawn_ground_gold(&mut commands, &texture_assets, amount, position);
+                }
+            }
+        }
+    }
+}
+
+/// Picks up the gold and items the player walks over.
+fn walk_over_pickup(
+    mut commands: Commands,
+    mut player_q: Query<(&Transform, &mut Inventory, &mut Gold), With<Player>>,
+    gold_q: Query<(Entity, &GroundGold, &Transform)>,
+    mut item_q: Query<(Entity, &mut GroundItem, &Transform), Without<Dropped>>,
+    dropped_q: Query<(Entity, &Transform), With<Dropped>>,
+    loot_definitions: LootDefinitions,
+) {
+    let (Ok((player_transform, mut inventory, mut gold)), Some(roller)) =
+        (player_q.get_single_mut(), loot_definitions.roller())
+    else {
+        return;
+    };
+    let player_pos = player_transform.translation.truncate();
+    let in_radius = |transform: &Transform| {
+        transform.translation.truncate().distance(player_pos) <= roller.loot.pickup_radius
+    };
+
+    for (entity, ground_gold, transform) in gold_q.iter() {
+        if in_radius(transform) {
+            gold.0 += ground_gold.0;
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+    for (entity, mut ground_item, transform) in item_q.iter_mut() {
+        if in_radius(transform) && pick_up(&mut inventory, roller.items, &mut ground_item.0) {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+    for (entity, transform) in dropped_q.iter() {
+        if !in_radius(transform) {
+            commands.entity(entity).remove::<Dropped>();
+        }
+    }
+}
+
+/// Picks up the clicked item if the player is close enough.
+fn clicked_pickup(
+    mut commands: Commands,
+    mut player_q: Query<(&Transform, &mut Inventory), With<Player>>,
+    mut item_q: Query<(Entity, &mut GroundItem, &Transform)>,
+    item_definitions: ItemDefinitions,
+    player_input: Res<PlayerInput>,
+) {
+    let (Some(clicked), Ok((player_transform, mut inventory)), Some(items)) = (
+        player_input.clicked_at,
+        player_q.get_single_mut(),
+        item_definitions.book(),
+    ) else {
+        return;
+    };
+    let player_pos = player_transform.translation.truncate();
+
+    let clicked_item = item_q
+        .iter()
+        .map(|(entity, _, transform)| (entity, transform.translation.truncate()))
+        .filter(|(_, position)| position.distance(clicked) <= GROUND_ITEM_SIZE)
+        .min_by(|(_, a), (_, b)| a.distance(clicked).total_cmp(&b.distance(clicked)));
+    let Some((entity, position)) = clicked_item else {
+        return;
+    };
+    if position.distance(player_pos) > PICKUP_RANGE {
+        return;
+    }
+    if let Ok((_, mut ground_item, _)) = item_q.get_mut(entity) {
+        if pick_up(&mut inventory, items, &mut ground_item.0) {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+/// Picks up as much of a ground stack as fits, see [`Inventory::pick_up`].
+fn pick_up(inventory: &mut Inventory, items: &ItemBook, stack: &mut ItemStack) -> bool {
+    items
+        .items
+        .get(&stack.item)
+        .is_some_and(|definition| inventory.pick_up(stack, definition.max_stack))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::item::generation::RarityWeights;
+
+    fn table(entries: Vec<LootDrop>) -> LootTable {
+        LootTable {
+            rolls: 1,
+            entries: entries
+                .into_iter()
+                .map(|drop| LootEntry { weight: 1, drop })
+                .collect(),
+        }
+    }
+
+    fn books(tables: Vec<(&str, LootTable)>) -> (LootBook, ItemBook, AffixBook) {
+        let loot = LootBook {
+            pickup_radius: default_pickup_radius(),
+            tables: tables
+                .into_iter()
+                .map(|(name, table)| (name.to_string(), table))
+                .collect(),
+        };
+        let items = ItemBook {
+            items: HashMap::new(),
+            starting_items: Vec::new(),
+            starting_belt: Vec::new(),
+        };
+        let affixes = AffixBook {
+            rarity_weights: RarityWeights::default(),
+            affixes: Vec::new(),
+            uniques: Vec::new(),
+        };
+        (loot, items, affixes)
+    }
+
+    /// Tables `chain_0` to `chain_{length - 1}` each rolling the next, the last
+    /// one dropping a gold piece.
+    fn chain(length: usize) -> Vec<(String, LootTable)> {
+        (0..length)
+            .map(|i| {
+                let drop = if i + 1 == length {
+                    LootDrop::Gold { min: 1, max: 1 }
+                } else {
+                    LootDrop::Table(format!("chain_{}", i + 1))
+                };
+                (format!("chain_{i}"), table(vec![drop]))
+            })
+            .collect()
+    }
+
+    fn roll_chain(length: usize) -> Vec<RolledLoot> {
+        let tables = chain(length);
+        let (loot, items, affixes) = books(
+            tables
+                .iter()
+                .map(|(name, table)| (name.as_str(), table.clone()))
+                .collect(),
+        );
+        let roller = LootRoller {
+            loot: &loot,
+            items: &items,
+            affixes: &affixes,
+        };
+        roller.roll("chain_0", 1, &mut StdRng::seed_from_u64(1))
+    }
+
+    #[test]
+    fn nested_tables_roll_their_drops() {
+        let potion = ItemId::from("potion");
+        let (loot, items, affixes) = books(vec![
+            ("outer", table(vec![LootDrop::Table("inner".to_string())])),
+            (
+                "inner",
+                table(vec![LootDrop::Item {
+                    item: potion.clone(),
+                    min: 2,
+                    max: 2,
+                }]),
+            ),
+        ]);
+        let roller = LootRoller {
+            loot: &loot,
+            items: &items,
+            affixes: &affixes,
+        };
+        let rolled = roller.roll("outer", 1, &mut StdRng::seed_from_u64(1));
+        assert_eq!(rolled, vec![RolledLoot::Item(ItemStack::new(potion, 2))]);
+    }
+
+    #[test]
+    fn tables_stop_nesting_at_the_max_depth() {
+        assert_eq!(roll_chain(MAX_TABLE_DEPTH), vec![RolledLoot::Gold(1)]);
+        assert!(roll_chain(MAX_TABLE_DEPTH + 1).is_empty());
+    }
+
+    #[test]
+    fn self_referencing_tables_end() {
+        let (loot, items, affixes) = books(vec![(
+            "loop",
+            table(vec![LootDrop::Table("loop".to_string())]),
+        )]);
+        let roller = LootRoller {
+            loot: &loot,
+            items: &items,
+            affixes: &affixes,
+        };
+        assert!(roller
+            .roll("loop", 1, &mut StdRng::seed_from_u64(1))
+            .is_empty());
+    }
+
+    #[test]
+    fn gold_rolls_within_its_range() {
+        let (loot, items, affixes) = books(vec![
+            ("gold", table(vec![LootDrop::Gold { min: 5, max: 10 }])),
+            ("none", table(vec![LootDrop::Gold { min: 0, max: 0 }])),
+            ("reversed", table(vec![LootDrop::Gold { min: 7, max: 3 }])),
+        ]);
+        let roller = LootRoller {
+            loot: &loot,
+            items: &items,
+            affixes: &affixes,
+        };
+        let mut rng = StdRng::seed_from_u64(1);
+        for _ in 0..1_000 {
+            match roller.roll("gold", 1, &mut rng).as_slice() {
+                [RolledLoot::Gold(amount)] => assert!((5..=10).contains(amount)),
+                rolled => panic!("expected one gold drop, got {rolled:?}"),
+            }
+        }
+        assert!(roller.roll("none", 1, &mut rng).is_empty());
+        assert_eq!(
+            roller.roll("reversed", 1, &mut rng),
+            vec![RolledLoot::Gold(7)]
+        );
+    }
+}
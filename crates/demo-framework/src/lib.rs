@@ -1,10 +1,16 @@
 use bevy::prelude::States;
 
+pub mod animation;
 pub mod camera;
+pub mod damage;
 pub mod debug;
+pub mod item;
 pub mod loading;
 pub mod menu;
 pub mod player;
+pub mod projectile;
+pub mod skill;
+pub mod status;
 pub mod ui;
 pub mod world;
 
@@ -14,4 +20,6 @@ pub enum GameState {
     Loading,
     Playing,
     Menu,
+    /// The player died, the game over screen is shown.
+    GameOver,
 }
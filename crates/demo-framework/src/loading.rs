@@ -15,15 +15,35 @@ use bevy_asset_loader::{
 use bevy_trickfilm::asset::AnimationClip2D;
 use iyes_progress::ProgressPlugin;
 
-use crate::GameState;
+mod ron_asset;
+
+pub use ron_asset::{RonAssetAppExt, RonAssetLoader, RonAssetLoaderError};
+
+use crate::{
+    item::{generation::AffixBook, loot::LootBook, ItemBook},
+    player::{character::CharacterBook, experience::Progression},
+    skill::SkillBook,
+    world::{ChunkDeltas, TiledMap},
+    GameState,
+};
 
 pub struct LoadingPlugin;
 
 impl Plugin for LoadingPlugin {
     fn build(&self, app: &mut App) {
-        app.add_plugins(ProgressPlugin::new(GameState::Loading).continue_to(GameState::Menu))
+        app.register_ron_asset::<ChunkDeltas>(&["deltas.ron"])
+            .register_ron_asset::<SkillBook>(&["skills.ron"])
+            .register_ron_asset::<Progression>(&["progression.ron"])
+            .register_ron_asset::<ItemBook>(&["items.ron"])
+            .register_ron_asset::<AffixBook>(&["affixes.ron"])
+            .register_ron_asset::<LootBook>(&["loot.ron"])
+            .register_ron_asset::<CharacterBook>(&["characters.ron"])
+            .add_plugins(ProgressPlugin::new(GameState::Loading).continue_to(GameState::Menu))
             .add_loading_state(
-                LoadingState::new(GameState::Loading).load_collection::<TextureAssets>(),
+                LoadingState::new(GameState::Loading)
+                    .load_collection::<TextureAssets>()
+                    .load_collection::<MapAssets>()
+                    .load_collection::<DataAssets>(),
             );
     }
 }
@@ -44,30 +64,6 @@ pub struct TextureAssets {
     #[asset(path = "textures/ui/borders/border_all_12.png")]
     pub(crate) border: Handle<Image>,
 
-    // player
-    #[asset(texture_atlas_layout(tile_size_x = 32.0, tile_size_y = 48.0, columns = 8, rows = 3))]
-    pub(crate) female_adventurer_layout: Handle<TextureAtlasLayout>,
-    #[asset(path = "textures/npc_characters/female_2.png")]
-    pub(crate) female_adventurer: Handle<Image>,
-    #[asset(
-        paths(
-            "textures/npc_characters/female_2.trickfilm#idle-down",
-            "textures/npc_characters/female_2.trickfilm#idle-left",
-            "textures/npc_characters/female_2.trickfilm#idle-right",
-            "textures/npc_characters/female_2.trickfilm#idle-top",
-            "textures/npc_characters/female_2.trickfilm#walking-down",
-            "textures/npc_characters/female_2.trickfilm#walking-left",
-            "textures/npc_characters/female_2.trickfilm#walking-right",
-            "textures/npc_characters/female_2.trickfilm#walking-top",
-            "textures/npc_characters/female_2.trickfilm#sprinting-down",
-            "textures/npc_characters/female_2.trickfilm#sprinting-left",
-            "textures/npc_characters/female_2.trickfilm#sprinting-right",
-            "textures/npc_characters/female_2.trickfilm#sprinting-top",
-        ),
-        collection(typed)
-    )]
-    pub(crate) female_adventurer_animations: Vec<Handle<AnimationClip2D>>,
-
     // spells
     #[asset(texture_atlas_layout(
         tile_size_x = 192.0,
@@ -87,6 +83,18 @@ pub struct TextureAssets {
         collection(typed)
     )]
     pub(crate) ice_spell_one_animations: Vec<Handle<AnimationClip2D>>,
+    #[asset(texture_atlas_layout(tile_size_x = 48.0, tile_size_y = 32.0, columns = 3, rows = 1))]
+    pub(crate) ice_spell_two_layout: Handle<TextureAtlasLayout>,
+    #[asset(path = "textures/spells/ice_vfx_2.png")]
+    pub(crate) ice_spell_two: Handle<Image>,
+    #[asset(
+        paths(
+            "textures/spells/ice_vfx_2.trickfilm#repeat",
+            "textures/spells/ice_vfx_2.trickfilm#hit",
+        ),
+        collection(typed)
+    )]
+    pub(crate) ice_spell_two_animations: Vec<Handle<AnimationClip2D>>,
 
     // world
     #[asset(image(sampler = nearest))]
@@ -95,4 +103,36 @@ pub struct TextureAssets {
 
     #[asset(path = "textures/grass_land/decorative.png")]
     pub grass_land_decorative: Handle<Image>,
+    #[asset(texture_atlas_layout(
+        tile_size_x = 32.0,
+        tile_size_y = 32.0,
+        columns = 32,
+        rows = 32
+    ))]
+    pub(crate) grass_land_decorative_layout: Handle<TextureAtlasLayout>,
+}
+
+#[derive(AssetCollection, Resource)]
+pub struct MapAssets {
+    #[asset(path = "maps/town.tmj")]
+    pub(crate) town: Handle<TiledMap>,
+    #[asset(path = "maps/world.deltas.ron")]
+    pub(crate) world_deltas: Handle<ChunkDeltas>,
+}
+
+/// Game data designers can tweak without touching code.
+#[derive(AssetCollection, Resource)]
+pub struct DataAssets {
+    #[asset(path = "data/base.skills.ron")]
+    pub(crate) skills: Handle<SkillBook>,
+    #[asset(path = "data/base.progression.ron")]
+    pub(crate) progression: Handle<Progression>,
+    #[asset(path = "data/base.items.ron")]
+    pub(crate) items: Handle<ItemBook>,
+    #[asset(path = "data/base.affixes.ron")]
+    pub(crate) affixes: Handle<AffixBook>,
+    #[asset(path = "data/base.loot.ron")]
+    pub(crate) loot: Handle<LootBook>,
+    #[asset(path = "data/base.characters.ron")]
+    pub(crate) characters: Handle<CharacterBook>,
 }
@@ -0,0 +1,70 @@
+use std::marker::PhantomData;
+
+use bevy::{
+    app::App,
+    asset::{io::Reader, Asset, AssetApp, AssetLoader, AsyncReadExt, LoadContext},
+    utils::BoxedFuture,
+};
+use serde::Deserialize;
+use thiserror::Error;
+
+/// Loads any deserializable asset from a RON file.
+pub struct RonAssetLoader<A> {
+    extensions: Vec<&'static str>,
+    _marker: PhantomData<fn() -> A>,
+}
+
+#[derive(Debug, Error)]
+pub enum RonAssetLoaderError {
+    #[error("could not read asset: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("could not parse asset: {0}")]
+    Ron(#[from] ron::error::SpannedError),
+}
+
+impl<A> AssetLoader for RonAssetLoader<A>
+where
+    A: Asset + for<'de> Deserialize<'de>,
+{
+    type Asset = A;
+    type Settings = ();
+    type Error = RonAssetLoaderError;
+
+    fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader,
+        _settings: &'a (),
+        _load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<Self::Asset, Self::Error>> {
+        Box::pin(async move {
+            let mut bytes = Vec::new();
+            reader.read_to_end(&mut bytes).await?;
+            Ok(ron::de::from_bytes::<A>(&bytes)?)
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &self.extensions
+    }
+}
+
+pub trait RonAssetAppExt {
+    /// Registers `A` as an asset loaded from RON files with the given
+    /// extensions, e.g. `skills.ron`.
+    fn register_ron_asset<A>(&mut self, extensions: &[&'static str]) -> &mut Self
+    where
+        A: Asset + for<'de> Deserialize<'de>;
+}
+
+impl RonAssetAppExt for App {
+    fn register_ron_asset<A>(&mut self, extensions: &[&'static str]) -> &mut Self
+    where
+        A: Asset + for<'de> Deserialize<'de>,
+    {
+        self.init_asset::<A>()
+            .register_asset_loader(RonAssetLoader::<A> {
+                extensions: extensions.to_vec(),
+                _marker: PhantomData,
+            })
+    }
+}
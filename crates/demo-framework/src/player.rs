@@ -1,21 +1,46 @@
-mod attack;
+pub mod attack;
+pub mod character;
+pub mod death;
+mod dodge;
+pub mod experience;
 pub(crate) mod input;
 mod movement;
+pub mod navigation;
 mod state;
+pub mod stats;
 
 use std::{collections::HashMap, time::Duration};
 
-use crate::{loading::TextureAssets, GameState};
+use crate::{
+    animation::{AnimationAction, AnimationState},
+    damage::Health,
+    item::{
+        consumable::{ConsumableCooldowns, PotionBelt, Restorations},
+        equipment::Equipment,
+        Gold, Inventory,
+    },
+    loading::DataAssets,
+    skill::{SkillCast, SkillCooldowns, SkillId, Skills},
+    status::StatusEffects,
+    GameState,
+};
 
 use bevy::{
     app::{App, Plugin},
-    ecs::entity::Entity,
+    asset::Assets,
+    ecs::{
+        entity::Entity,
+        schedule::{
+            common_conditions::{any_with_component, not},
+            IntoSystemConfigs,
+        },
+    },
     hierarchy::BuildChildren,
+    log::warn,
     math::{Vec2, Vec3},
     prelude::{Commands, Component, Name, OnEnter, Res, SpatialBundle, Transform},
     render::view::RenderLayers,
     sprite::{SpriteSheetBundle, TextureAtlas},
-    time::{Timer, TimerMode},
     transform::TransformBundle,
 };
 use bevy_magic_light_2d::gi::{render_layer::CAMERA_LAYER_OBJECTS, types::LightOccluder2D};
@@ -25,40 +50,67 @@ use bevy_rapier2d::{
 };
 use bevy_trickfilm::prelude::AnimationPlayer2D;
 
-use self::state::{CooldownTimer, MovementState};
+use self::{
+    character::{CharacterSprites, SelectedCharacter},
+    dodge::DodgeCooldown,
+    dodge::DODGE_SPEED,
+    experience::Progression,
+    movement::{SPRINT_SPEED, WALK_SPEED},
+    state::MovementState,
+    stats::{AttributeKind, Modifiers, Pool},
+};
 
 const PLAYER_SPAWN_POS: Vec3 = Vec3::new(0.0, 0.0, 1.0);
 const PLAYER_SCALE: Vec3 = Vec3::splat(0.5);
+/// Number of hotbar slots bound to the number keys.
+pub const HOTBAR_SLOTS: usize = 9;
+/// Shared by every skill so they can't all be used on the same frame.
+const GLOBAL_COOLDOWN: Duration = Duration::from_millis(250);
 const PLAYER_COLLISION_GROUPS: CollisionGroups = CollisionGroups::new(Group::ALL, Group::ALL);
 
 pub struct PlayerPlugin;
 
 impl Plugin for PlayerPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(OnEnter(GameState::Playing), spawn_player)
-            .add_plugins((
-                attack::PlayerAttackPlugin,
-                input::PlayerInputPlugin,
-                state::PlayerStatePlugin,
-                movement::PlayerMovementPlugin,
-            ));
+        app.add_systems(
+            OnEnter(GameState::Playing),
+            spawn_player.run_if(not(any_with_component::<Player>)),
+        )
+        .add_plugins((
+            attack::PlayerAttackPlugin,
+            input::PlayerInputPlugin,
+            state::PlayerStatePlugin,
+            movement::PlayerMovementPlugin,
+            stats::PlayerStatsPlugin,
+            experience::PlayerExperiencePlugin,
+            death::PlayerDeathPlugin,
+            dodge::PlayerDodgePlugin,
+            character::PlayerCharacterPlugin,
+            navigation::PlayerNavigationPlugin,
+        ));
     }
 }
 
 #[derive(Component, Debug)]
 pub(crate) struct Player {
+    /// Chosen on character creation.
+    pub name: String,
     pub(crate) movement_state: MovementState,
     pub(crate) current_direction: Vec2,
-    #[allow(dead_code)]
     pub(crate) collider_entity: Entity,
+    /// Out of stamina, and walking until it recovers.
+    pub(crate) is_exhausted: bool,
     pub attributes: Attributes,
     pub stats: Stats,
-    pub resistances: Resistances,
     pub experience: Experience,
     pub skills: Skills,
-    pub(crate) attack_cooldown: CooldownTimer,
-    pub selected_left_skill: Skill,
-    pub selected_right_skill: Skill,
+    pub cooldowns: SkillCooldowns,
+    /// The skill being cast, if it has a cast time.
+    pub casting: Option<SkillCast>,
+    pub selected_left_skill: SkillId,
+    pub selected_right_skill: SkillId,
+    /// Skills bound to the number keys 1 to 9.
+    pub hotbar: [Option<SkillId>; HOTBAR_SLOTS],
 }
 
 #[derive(Component, Debug)]
@@ -66,6 +118,10 @@ pub struct Attributes {
     pub strength: i32,
     pub vitality: i32,
     pub energy: i32,
+    /// Gained on level up, available to spend on attributes.
+    pub available_points: u16,
+    /// Points spent on each attribute, given back on respec.
+    pub spent: HashMap<AttributeKind, u16>,
 }
 
 impl Default for Attributes {
@@ -74,32 +130,34 @@ impl Default for Attributes {
             strength: 10,
             vitality: 10,
             energy: 15,
+            available_points: 0,
+            spent: HashMap::new(),
         }
     }
 }
 
+/// Derived from attributes, level and [`Modifiers`], see [`Stats::derive`].
 #[derive(Component, Debug)]
 pub struct Stats {
-    pub life: f32,
-    pub mana: f32,
-    pub stamina: f32,
+    /// Current life is tracked by [`Health`].
+    pub max_life: f32,
+    pub mana: Pool,
+    pub stamina: Pool,
     pub defense: f32,
     pub cast_rate: f32,
     pub attack_rate: f32,
+    /// Between 0 and 1.
+    pub crit_chance: f32,
+    /// Per second.
+    pub life_regen: f32,
+    pub mana_regen: f32,
+    pub stamina_regen: f32,
     pub resistances: Resistances,
 }
 
 impl Default for Stats {
     fn default() -> Self {
-        Self {
-            life: 15.0,                          // vitality * 1.5
-            mana: 30.0,                          // energy * 2
-            stamina: 20.0,                       // vitality * 2
-            defense: 12.0,                       // strength * 1.2
-            cast_rate: 0.0,                      // influenced by items
-            attack_rate: 0.0,                    // influenced by items
-            resistances: Resistances::default(), // influenced by items
-        }
+        Self::derive(&Attributes::default(), 1, &Modifiers::default())
     }
 }
 
@@ -117,138 +175,54 @@ pub struct Experience {
     pub level: u16,
 }
 
-impl Default for Experience {
-    fn default() -> Self {
+impl Experience {
+    /// At level 1, with the experience needed for level 2 from `progression`.
+    pub fn new(progression: &Progression) -> Self {
         Self {
             current: 0,
-            remaining: 100, // base_exp => 100, growth => 1.5, base_exp * growth.powi(level - 1)).round()
+            remaining: progression.exp_to_next_level(1),
             level: 1,
         }
     }
 }
 
-#[derive(Clone, Copy, Debug, Default)]
-pub enum CostType {
-    #[default]
-    None,
-    Life,
-    Mana,
-    Stamina,
-}
-
-#[derive(Clone, Copy, Debug, Default)]
-pub struct Cost {
-    pub cost_type: CostType,
-    pub value: f32,
-}
-
-impl Cost {
-    pub fn new(cost_type: CostType, value: f32) -> Self {
-        Self { cost_type, value }
-    }
-}
-
-#[allow(dead_code)]
-#[derive(Copy, Clone, Debug)]
-pub enum Skill {
-    // Melee
-    Attack {
-        base_damage: f32,
-        range: f32,
-    },
-    // Cold spells
-    IceBolt {
-        base_damage: f32,
-        mana_cost: f32,
-        level: u16,
-        range: f32,
-    },
-    IceBlast {
-        base_damage: f32,
-        mana_cost: f32,
-        level: u16,
-        range: f32,
-    },
-    IceNova {
-        base_damage: f32,
-        mana_cost: f32,
-        level: u16,
-        range: f32,
-    },
-}
-
-impl Skill {
-    pub fn cost(&self) -> Cost {
-        match self {
-            Skill::Attack { .. } => Cost::default(),
-            Skill::IceBolt { mana_cost, .. } => Cost::new(CostType::Mana, *mana_cost),
-            Skill::IceBlast { mana_cost, .. } => Cost::new(CostType::Mana, *mana_cost),
-            Skill::IceNova { mana_cost, .. } => Cost::new(CostType::Mana, *mana_cost),
-        }
-    }
-
-    pub fn range(&self) -> f32 {
-        match self {
-            Skill::Attack { range, .. } => range.clone(),
-            Skill::IceBolt { range, .. } => range.clone(),
-            Skill::IceBlast { range, .. } => range.clone(),
-            Skill::IceNova { range, .. } => range.clone(),
-        }
-    }
-}
-
-#[derive(Component, Debug, Clone)]
-pub struct Skills {
-    pub skills: HashMap<String, Skill>,
-    pub available_points: u16, // available to spend on upgrading
-}
-
-impl Default for Skills {
-    fn default() -> Self {
-        let mut skills = HashMap::new();
-
-        skills.insert(
-            "Attack".to_string(),
-            Skill::Attack {
-                base_damage: 3.0,
-                range: 100.0,
-            },
-        );
-
-        Self {
-            skills,
-            available_points: 0,
-        }
-    }
-}
-
 impl Player {
-    fn new(collider_entity: Entity) -> Self {
-        let skills = Skills::default();
-
-        let selected_left_skill = *skills.skills.get("Attack").unwrap();
-        let selected_right_skill = *skills.skills.get("Attack").unwrap();
-
+    fn new(name: String, collider_entity: Entity, progression: &Progression) -> Self {
         Self {
+            name,
             movement_state: MovementState::default(),
             current_direction: Vec2::ZERO,
             collider_entity,
+            is_exhausted: false,
             attributes: Attributes::default(),
             stats: Stats::default(),
-            resistances: Resistances::default(),
-            experience: Experience::default(),
-            skills: skills.clone(),
-            attack_cooldown: CooldownTimer(Timer::new(
-                Duration::from_millis(400),
-                TimerMode::Repeating,
-            )),
-            selected_left_skill,
-            selected_right_skill,
+            experience: Experience::new(progression),
+            skills: Skills::default(),
+            cooldowns: SkillCooldowns::new(GLOBAL_COOLDOWN),
+            casting: None,
+            selected_left_skill: SkillId::from("attack"),
+            selected_right_skill: SkillId::from("ice_bolt"),
+            hotbar: Default::default(),
         }
     }
 }
 
-pub fn spawn_player(mut commands: Commands, my_assets: Res<TextureAssets>) {
+pub fn spawn_player(
+    mut commands: Commands,
+    characters: Res<CharacterSprites>,
+    selected: Res<SelectedCharacter>,
+    data_assets: Res<DataAssets>,
+    progressions: Res<Assets<Progression>>,
+) {
+    let Some(character) = characters.get(&selected.character).or(characters.0.first()) else {
+        warn!("no character to spawn the player as");
+        return;
+    };
+    let Some(progression) = progressions.get(&data_assets.progression) else {
+        warn!("progression data isn't loaded, can't spawn the player");
+        return;
+    };
+
     let collider = commands
         .spawn((
             Collider::capsule_y(15.0, 9.0),
@@ -258,23 +232,44 @@ pub fn spawn_player(mut commands: Commands, my_assets: Res<TextureAssets>) {
         ))
         .id();
 
-    let mut animator = AnimationPlayer2D::default();
-    animator.play(my_assets.female_adventurer_animations[0].clone_weak());
+    let animations = character
+        .animations
+        .clone()
+        .with_reference_speed(AnimationAction::Walk, WALK_SPEED)
+        .with_reference_speed(AnimationAction::Sprint, SPRINT_SPEED)
+        // the roll plays the sprint clip twice as fast
+        .with_reference_speed(AnimationAction::Dodge, DODGE_SPEED / 2.0);
 
     commands
         .spawn((
             Name::new("Player"),
-            Player::new(collider),
+            Player::new(selected.name.clone(), collider, progression),
+            Health::new(Stats::default().max_life),
+            Modifiers::default(),
+            StatusEffects::default(),
+            (
+                Inventory::default(),
+                Equipment::default(),
+                Gold::default(),
+                PotionBelt::default(),
+                ConsumableCooldowns::default(),
+                Restorations::default(),
+            ),
+            DodgeCooldown::default(),
             RigidBody::Dynamic,
             LockedAxes::ROTATION_LOCKED,
             Velocity::zero(),
             Ccd::enabled(),
-            animator,
+            (
+                AnimationPlayer2D::default(),
+                animations,
+                AnimationState::facing(Vec2::NEG_X),
+            ),
             SpriteSheetBundle {
                 transform: Transform::from_translation(PLAYER_SPAWN_POS),
-                texture: my_assets.female_adventurer.clone(),
+                texture: character.texture.clone(),
                 atlas: TextureAtlas {
-                    layout: my_assets.female_adventurer_layout.clone(),
+                    layout: character.layout.clone(),
                     index: 0,
                 },
                 ..Default::default()
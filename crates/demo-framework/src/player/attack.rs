@@ -1,230 +1,789 @@
 use bevy::render::color::Color;
 use bevy::render::view::RenderLayers;
 use bevy::transform::TransformBundle;
+use std::time::Duration;
+
 use bevy::{
     app::{App, Plugin, Update},
+    asset::Handle,
     ecs::{
         component::Component,
         entity::Entity,
         event::{Event, EventReader, EventWriter},
-        query::With,
+        query::{With, Without},
         schedule::{common_conditions::in_state, IntoSystemConfigs},
-        system::{Commands, Query, Res},
+        system::{Commands, Query, Res, SystemParam},
     },
-    hierarchy::{BuildChildren, DespawnRecursiveExt},
-    math::{EulerRot, Quat, Vec2, Vec3},
-    sprite::{SpriteSheetBundle, TextureAtlas},
-    time::Time,
-    transform::components::Transform,
+    hierarchy::{BuildChildren, DespawnRecursiveExt, Parent},
+    math::{Quat, Vec2, Vec3},
+    render::texture::Image,
+    sprite::{SpriteSheetBundle, TextureAtlas, TextureAtlasLayout},
+    time::{Time, Timer, TimerMode},
+    transform::components::{GlobalTransform, Transform},
 };
 use bevy_magic_light_2d::gi::render_layer::CAMERA_LAYER_OBJECTS;
 use bevy_magic_light_2d::gi::types::OmniLightSource2D;
-use bevy_rapier2d::geometry::Collider;
-use bevy_trickfilm::animation::AnimationPlayer2D;
+use bevy_rapier2d::{
+    geometry::{ActiveCollisionTypes, Collider, Sensor},
+    pipeline::QueryFilter,
+    plugin::RapierContext,
+};
+use bevy_trickfilm::{animation::AnimationPlayer2D, asset::AnimationClip2D};
 
-use crate::{loading::TextureAssets, GameState};
+use crate::{
+    damage::{DamageEvent, DamageFlags, DamageSet, Health},
+    loading::TextureAssets,
+    projectile::{Homing, Projectile, ProjectileEnded, ProjectileHit, ProjectileSet},
+    skill::{
+        falloff_damage, rate_multiplier, CostType, Element, Explosion, SkillBehaviour, SkillCast,
+        SkillDefinitions, SkillId, SkillVfx,
+    },
+    status::StatusEffects,
+    GameState,
+};
 
 use super::{
     input::{MouseWorldCoords, PlayerInput},
-    CostType, Player,
+    Player,
 };
 
+/// Radius of the ice missile's hit effect in its unscaled sprite.
+const EXPLOSION_SPRITE_RADIUS: f32 = 64.0;
+const NOVA_SHARD_SCALE: f32 = 0.75;
+const SWING_DURATION: Duration = Duration::from_millis(250);
+const SWING_WIDTH: f32 = 30.0;
+/// Melee damage gained per point of strength, as a fraction of the skill's damage.
+const STRENGTH_DAMAGE_BONUS: f32 = 0.01;
+/// How far from where a homing missile was aimed its target can be.
+const HOMING_TARGET_RADIUS: f32 = 80.0;
+
 pub struct PlayerAttackPlugin;
 
 impl Plugin for PlayerAttackPlugin {
     fn build(&self, app: &mut App) {
-        app.add_event::<SpawnMissile>().add_systems(
-            Update,
-            (
-                spawn_missile,
-                despawn_missile,
-                spell_casting,
-                spell_cooldown.after(spell_casting),
-                update_missile_path,
-                update_animations,
-            )
-                .run_if(in_state(GameState::Playing)),
-        );
+        app.add_event::<SpawnMissile>()
+            .add_event::<SpawnExplosion>()
+            .add_event::<SkillHit>()
+            .add_event::<MissileImpact>()
+            .add_event::<NotEnoughResource>()
+            .add_event::<SkillUsed>()
+            .add_systems(
+                Update,
+                (
+                    spawn_missile.after(spell_casting),
+                    (missile_hits, missile_ended).after(ProjectileSet),
+                    missile_impacts.after(missile_ended),
+                    spawn_explosion.after(missile_impacts),
+                    spell_casting,
+                    spell_cooldown.after(spell_casting),
+                    update_animations,
+                    despawn_finished_effects,
+                    melee_hits,
+                    skill_damage.before(DamageSet),
+                )
+                    .run_if(in_state(GameState::Playing)),
+            );
     }
 }
 
+/// The damage carried by a missile or explosion.
+#[derive(Clone, Copy, Debug)]
+pub struct SkillDamage {
+    pub amount: f32,
+    pub element: Element,
+}
+
+/// What a skill [`Projectile`] carries.
+#[derive(Component)]
+pub struct Missile {
+    pub damage: SkillDamage,
+    /// Fraction of the damage lost by the end of the range.
+    pub falloff: f32,
+    pub vfx: SkillVfx,
+    pub explosion: Option<Explosion>,
+}
+
+impl Missile {
+    /// Damage dealt when hitting something at `position`.
+    pub fn damage_at(&self, projectile: &Projectile, position: Vec2) -> f32 {
+        falloff_damage(
+            self.damage.amount,
+            projectile.origin.distance(position),
+            projectile.max_distance,
+            self.falloff,
+        )
+    }
+}
+
+/// A short-lived hitbox in front of the attacker, hitting each target once.
+#[derive(Component)]
+pub struct MeleeSwing {
+    pub direction: Vec2,
+    pub damage: SkillDamage,
+    pub timer: Timer,
+    pub hit: Vec<Entity>,
+}
+
+/// A one-shot visual, despawned once its animation is over.
 #[derive(Component)]
-pub struct Missile(pub f32);
+pub struct SkillEffect;
 
 #[derive(Event)]
 pub struct SpawnMissile {
+    pub caster: Entity,
+    pub origin: Vec2,
+    pub direction: Vec2,
     pub range: f32,
-    pub target_pos: Vec2,
+    pub speed: f32,
+    pub acceleration: f32,
+    pub pierce: u32,
+    pub bounces: u32,
+    pub damage: SkillDamage,
+    pub falloff: f32,
+    pub vfx: SkillVfx,
+    pub explosion: Option<Explosion>,
+    pub scale: f32,
+    pub lifetime: Option<Duration>,
+    pub homing: Option<Homing>,
 }
 
-#[derive(Component)]
-pub struct Direction(pub Vec2);
+#[derive(Event)]
+pub struct SpawnExplosion {
+    pub position: Vec2,
+    pub explosion: Explosion,
+    pub damage: SkillDamage,
+}
+
+/// Sent for every entity caught by a skill, with the damage it should take.
+#[derive(Event, Debug)]
+pub struct SkillHit {
+    pub target: Entity,
+    pub position: Vec2,
+    pub damage: f32,
+    pub element: Element,
+}
+
+/// Sent when a missile ends, either by hitting something or at the end of its
+/// range.
+#[derive(Event, Debug)]
+pub struct MissileImpact {
+    pub position: Vec2,
+    /// The collider hit, if any.
+    pub target: Option<Entity>,
+    pub vfx: SkillVfx,
+    pub scale: f32,
+    pub damage: SkillDamage,
+    pub explosion: Option<Explosion>,
+}
+
+/// Sent when a skill goes off, once any cast time is over.
+#[derive(Event, Debug)]
+pub struct SkillUsed {
+    pub caster: Entity,
+    pub skill: SkillId,
+    /// Toward where the skill was aimed, zero when aimed at the caster.
+    pub direction: Vec2,
+}
+
+/// Sent when a skill can't be cast for lack of life, mana or stamina.
+#[derive(Event, Debug)]
+pub struct NotEnoughResource {
+    pub cost_type: CostType,
+}
+
+#[derive(SystemParam)]
+struct CastEvents<'w> {
+    spawn_missile: EventWriter<'w, SpawnMissile>,
+    spawn_explosion: EventWriter<'w, SpawnExplosion>,
+    not_enough_resource: EventWriter<'w, NotEnoughResource>,
+    skill_used: EventWriter<'w, SkillUsed>,
+}
 
-pub fn spawn_missile(
+/// Sprite sheet and clips of a skill effect, the last clip being its hit.
+fn vfx_sprites(
+    texture_assets: &TextureAssets,
+    vfx: SkillVfx,
+) -> (
+    Handle<Image>,
+    Handle<TextureAtlasLayout>,
+    &[Handle<AnimationClip2D>],
+) {
+    match vfx {
+        SkillVfx::IceMissile => (
+            texture_assets.ice_spell_one.clone(),
+            texture_assets.ice_spell_one_layout.clone(),
+            &texture_assets.ice_spell_one_animations,
+        ),
+        SkillVfx::IceShard => (
+            texture_assets.ice_spell_two.clone(),
+            texture_assets.ice_spell_two_layout.clone(),
+            &texture_assets.ice_spell_two_animations,
+        ),
+    }
+}
+
+fn spawn_hit_effect(
+    commands: &mut Commands,
+    texture_assets: &TextureAssets,
+    vfx: SkillVfx,
+    position: Vec2,
+    scale: f32,
+) {
+    let (texture, layout, clips) = vfx_sprites(texture_assets, vfx);
+    let mut animator = AnimationPlayer2D::default();
+    if let Some(hit) = clips.last() {
+        animator.play(hit.clone());
+    }
+
+    commands.spawn((
+        SkillEffect,
+        animator,
+        SpriteSheetBundle {
+            transform: Transform::from_translation(position.extend(1.0))
+                .with_scale(Vec3::splat(scale)),
+            texture,
+            atlas: TextureAtlas { layout, index: 0 },
+            ..Default::default()
+        },
+        RenderLayers::from_layers(CAMERA_LAYER_OBJECTS),
+    ));
+}
+
+fn spawn_missile(
     mut commands: Commands,
     texture_assets: Res<TextureAssets>,
-    player_q: Query<(Entity, &Transform), With<Player>>,
     mut spawn_missile_events: EventReader<SpawnMissile>,
 ) {
-    if let Ok((entity, transform)) = player_q.get_single() {
-        let player_pos = transform.translation.truncate();
+    for event in spawn_missile_events.read() {
+        let (texture, layout, clips) = vfx_sprites(&texture_assets, event.vfx);
+        let mut animator = AnimationPlayer2D::default();
+        match event.vfx {
+            // plays the start clip once, then loops the flight clip
+            SkillVfx::IceMissile => animator.play(clips[0].clone()),
+            SkillVfx::IceShard => animator.play(clips[0].clone()).repeat(),
+        };
 
-        for event in spawn_missile_events.read() {
-            let mut animator = AnimationPlayer2D::default();
-            animator
-                .play(texture_assets.ice_spell_one_animations[0].clone())
-                .repeat();
+        let collider = match event.vfx {
+            SkillVfx::IceMissile => Collider::cuboid(10.0, 5.0),
+            SkillVfx::IceShard => Collider::cuboid(8.0, 3.0),
+        };
 
-            let direction = (event.target_pos - player_pos).normalize_or_zero();
-            let rotation = if direction == Vec2::ZERO {
-                Quat::IDENTITY
-            } else {
-                Quat::from_euler(EulerRot::XYZ, 0.0, 0.0, Vec2::X.angle_between(direction))
-            };
-            let missile_transform = Transform::from_translation(
-                player_pos.extend(1.0) + rotation.mul_vec3(Vec3::X) * Vec3::new(25.0, 50.0, 0.0),
-            )
-            .with_rotation(rotation);
-
-            let missile = commands
-                .spawn((
-                    Missile(event.range),
-                    Direction(direction),
-                    animator,
-                    SpriteSheetBundle {
-                        transform: missile_transform,
-                        texture: texture_assets.ice_spell_one.clone(),
-                        atlas: TextureAtlas {
-                            layout: texture_assets.ice_spell_one_layout.clone(),
-                            index: 0,
-                        },
-                        ..Default::default()
-                    },
-                ))
-                .insert(RenderLayers::from_layers(CAMERA_LAYER_OBJECTS))
-                .insert(OmniLightSource2D {
-                    intensity: 0.09,
-                    color: Color::rgb_u8(89, 128, 235),
-                    falloff: Vec3::new(10.0, 10.0, 0.05),
-                    jitter_intensity: 0.2,
-                    jitter_translation: 2.0,
+        let mut projectile = Projectile::new(
+            event.caster,
+            event.origin,
+            event.direction,
+            event.speed,
+            event.range,
+        )
+        .with_acceleration(event.acceleration)
+        .with_pierce(event.pierce)
+        .with_bounces(event.bounces);
+        if let Some(lifetime) = event.lifetime {
+            projectile = projectile.with_lifetime(lifetime);
+        }
+        if let Some(homing) = event.homing {
+            projectile = projectile.with_homing(homing);
+        }
+
+        commands
+            .spawn((
+                projectile,
+                Missile {
+                    damage: event.damage,
+                    falloff: event.falloff,
+                    vfx: event.vfx,
+                    explosion: event.explosion,
+                },
+                animator,
+                SpriteSheetBundle {
+                    transform: Transform::from_translation(event.origin.extend(1.0))
+                        .with_rotation(Quat::from_rotation_z(
+                            Vec2::X.angle_between(event.direction),
+                        ))
+                        .with_scale(Vec3::splat(event.scale)),
+                    texture,
+                    atlas: TextureAtlas { layout, index: 0 },
                     ..Default::default()
-                })
-                .id();
-
-            let collider = commands
-                .spawn((
-                    Collider::cuboid(10.0, 5.0),
-                    TransformBundle::from_transform(Transform::from_translation(Vec3::new(
-                        -4.0, 0.0, 0.0,
-                    ))),
-                ))
-                .insert(RenderLayers::from_layers(CAMERA_LAYER_OBJECTS))
-                .id();
-
-            commands.entity(missile).push_children(&[collider]);
+                },
+                collider,
+                Sensor,
+                // the missile has no rigid body, so it also has to detect fixed colliders
+                ActiveCollisionTypes::all(),
+            ))
+            .insert(RenderLayers::from_layers(CAMERA_LAYER_OBJECTS))
+            .insert(OmniLightSource2D {
+                intensity: 0.09 * event.scale,
+                color: Color::rgb_u8(89, 128, 235),
+                falloff: Vec3::new(10.0, 10.0, 0.05),
+                jitter_intensity: 0.2,
+                jitter_translation: 2.0,
+                ..Default::default()
+            });
+    }
+}
+
+/// Damages what missiles touch, unless they explode, in which case the
+/// explosion deals the damage instead.
+fn missile_hits(
+    missile_q: Query<(&Projectile, &Missile)>,
+    mut projectile_hit_events: EventReader<ProjectileHit>,
+    mut skill_hit_events: EventWriter<SkillHit>,
+) {
+    for hit in projectile_hit_events.read() {
+        let Ok((projectile, missile)) = missile_q.get(hit.projectile) else {
+            continue;
+        };
+        if missile.explosion.is_none() {
+            skill_hit_events.send(SkillHit {
+                target: hit.target,
+                position: hit.position,
+                damage: missile.damage_at(projectile, hit.position),
+                element: missile.damage.element,
+            });
         }
     }
 }
 
-pub fn despawn_missile(
+fn missile_ended(
+    missile_q: Query<(&Missile, &Transform)>,
+    mut projectile_ended_events: EventReader<ProjectileEnded>,
+    mut missile_impact_events: EventWriter<MissileImpact>,
+) {
+    for ended in projectile_ended_events.read() {
+        let Ok((missile, transform)) = missile_q.get(ended.projectile) else {
+            continue;
+        };
+        missile_impact_events.send(MissileImpact {
+            position: ended.position,
+            target: ended.target,
+            vfx: missile.vfx,
+            scale: transform.scale.x,
+            damage: missile.damage,
+            explosion: missile.explosion,
+        });
+    }
+}
+
+/// Shatters or explodes missiles where they ended.
+fn missile_impacts(
     mut commands: Commands,
-    missiles_q: Query<(&Transform, Entity, &Missile)>,
-    player_q: Query<&Transform, With<Player>>,
+    texture_assets: Res<TextureAssets>,
+    mut missile_impact_events: EventReader<MissileImpact>,
+    mut spawn_explosion_events: EventWriter<SpawnExplosion>,
 ) {
-    if let Ok(player_transform) = player_q.get_single() {
-        let player_pos = player_transform.translation.truncate();
-        for (transform, entity, missile) in &missiles_q {
-            let missile_pos = transform.translation.truncate();
-            let dist = player_pos.distance(missile_pos);
-            if dist >= missile.0 * 2.0 {
-                println!("Despawning missile: {:?}, distance => {:?}", entity, dist);
-                commands.entity(entity).despawn_recursive();
-            }
+    for impact in missile_impact_events.read() {
+        if let Some(explosion) = impact.explosion {
+            spawn_explosion_events.send(SpawnExplosion {
+                position: impact.position,
+                explosion,
+                damage: impact.damage,
+            });
+        } else {
+            spawn_hit_effect(
+                &mut commands,
+                &texture_assets,
+                impact.vfx,
+                impact.position,
+                impact.scale,
+            );
         }
     }
 }
 
+/// Plays the explosion and hits everything inside its radius, dealing less
+/// damage the further a target is from the center.
+fn spawn_explosion(
+    mut commands: Commands,
+    texture_assets: Res<TextureAssets>,
+    rapier_context: Res<RapierContext>,
+    player_q: Query<Entity, With<Player>>,
+    transform_q: Query<&GlobalTransform>,
+    mut spawn_explosion_events: EventReader<SpawnExplosion>,
+    mut skill_hit_events: EventWriter<SkillHit>,
+) {
+    for event in spawn_explosion_events.read() {
+        let SpawnExplosion {
+            position,
+            explosion,
+            damage,
+        } = *event;
+
+        spawn_hit_effect(
+            &mut commands,
+            &texture_assets,
+            SkillVfx::IceMissile,
+            position,
+            explosion.radius / EXPLOSION_SPRITE_RADIUS,
+        );
+
+        let mut filter = QueryFilter::new().exclude_sensors();
+        if let Ok(player) = player_q.get_single() {
+            filter = filter.exclude_rigid_body(player);
+        }
+        rapier_context.intersections_with_shape(
+            position,
+            0.0,
+            &Collider::ball(explosion.radius),
+            filter,
+            |target| {
+                let target_pos = transform_q
+                    .get(target)
+                    .map(|transform| transform.translation().truncate())
+                    .unwrap_or(position);
+                skill_hit_events.send(SkillHit {
+                    target,
+                    position: target_pos,
+                    damage: falloff_damage(
+                        damage.amount,
+                        position.distance(target_pos),
+                        explosion.radius,
+                        explosion.falloff,
+                    ),
+                    element: damage.element,
+                });
+                true
+            },
+        );
+    }
+}
+
 fn spell_cooldown(mut player_q: Query<&mut Player>, time: Res<Time>) {
     for mut player in player_q.iter_mut() {
-        player.attack_cooldown.0.tick(time.delta());
+        player.cooldowns.tick(time.delta());
+        if let Some(cast) = player.casting.as_mut() {
+            cast.timer.tick(time.delta());
+        }
     }
 }
 
 fn spell_casting(
-    mut player_q: Query<&mut Player>,
+    mut commands: Commands,
+    mut player_q: Query<(
+        Entity,
+        &mut Player,
+        &mut Health,
+        &Transform,
+        Option<&StatusEffects>,
+    )>,
+    target_q: Query<(Entity, &GlobalTransform, &Health), Without<Player>>,
     player_input: Res<PlayerInput>,
     mouse_coords: Res<MouseWorldCoords>,
-    mut spawn_missile_event: EventWriter<SpawnMissile>,
+    skill_definitions: SkillDefinitions,
+    mut cast_events: CastEvents,
 ) {
-    if let Ok(mut player) = player_q.get_single_mut() {
-        if player.attack_cooldown.0.finished() {
-            let skill_to_use = if player_input.is_left_attack {
-                &player.selected_left_skill
-            } else if player_input.is_right_attack {
-                &player.selected_right_skill
-            } else {
-                return;
-            };
+    if let Ok((player_entity, mut player, mut health, transform, effects)) =
+        player_q.get_single_mut()
+    {
+        if effects.is_some_and(StatusEffects::is_frozen) {
+            // being frozen interrupts casting
+            player.casting = None;
+            return;
+        }
 
-            let range = skill_to_use.range().clone();
-
-            let cost = skill_to_use.cost();
-            let mut casted = false;
-            match cost.cost_type {
-                CostType::Mana => {
-                    if player.stats.mana >= cost.value {
-                        player.stats.mana -= cost.value;
-                        casted = true;
-                        spawn_missile_event.send(SpawnMissile {
-                            range,
-                            target_pos: mouse_coords.0,
-                        });
-                    }
+        let (skill_id, target_pos, cast_finished) = match player.casting.take() {
+            Some(cast) if cast.timer.finished() => (cast.skill, cast.target, true),
+            Some(cast) => {
+                player.casting = Some(cast);
+                return;
+            }
+            None => {
+                let skill_id = if player_input.is_left_attack {
+                    Some(&player.selected_left_skill)
+                } else if player_input.is_right_attack {
+                    Some(&player.selected_right_skill)
+                } else {
+                    player_input
+                        .hotbar_slot
+                        .and_then(|slot| player.hotbar[slot].as_ref())
+                };
+                let Some(skill_id) = skill_id else {
+                    return;
+                };
+                if !player.cooldowns.is_ready(skill_id) {
+                    return;
                 }
-                CostType::None => {
-                    casted = true;
-                    spawn_missile_event.send(SpawnMissile {
+                (
+                    skill_id.clone(),
+                    player_input.aim.unwrap_or(mouse_coords.0),
+                    false,
+                )
+            }
+        };
+
+        let (Some(skill), Some(level)) = (
+            skill_definitions.get(&skill_id),
+            player.skills.level(&skill_id),
+        ) else {
+            return;
+        };
+
+        let rate = if skill.is_attack() {
+            player.stats.attack_rate
+        } else {
+            player.stats.cast_rate
+        };
+        if !cast_finished && skill.cast_time > 0.0 {
+            let cast_time = skill.cast_time / rate_multiplier(rate);
+            player.casting = Some(SkillCast {
+                skill: skill_id,
+                target: target_pos,
+                timer: Timer::from_seconds(cast_time, TimerMode::Once),
+            });
+            return;
+        }
+
+        let range = skill.range(level);
+        let damage = SkillDamage {
+            amount: skill.damage(level),
+            element: skill.element,
+        };
+        let vfx = skill.vfx.unwrap_or(SkillVfx::IceMissile);
+
+        let cost = skill.cost(level);
+        let casted = match cost.cost_type {
+            CostType::None => true,
+            // life-costed skills can't kill the caster
+            CostType::Life if health.current > cost.value => {
+                health.current -= cost.value;
+                true
+            }
+            CostType::Life => false,
+            CostType::Mana => player.stats.mana.spend(cost.value),
+            CostType::Stamina => player.stats.stamina.spend(cost.value),
+        };
+        if !casted {
+            cast_events.not_enough_resource.send(NotEnoughResource {
+                cost_type: cost.cost_type,
+            });
+            return;
+        }
+
+        let player_pos = transform.translation.truncate();
+        cast_events.skill_used.send(SkillUsed {
+            caster: player_entity,
+            skill: skill_id.clone(),
+            direction: (target_pos - player_pos).normalize_or_zero(),
+        });
+        // missiles leave from around the player's hands rather than their feet
+        let launch = |target: Vec2| {
+            let direction = (target - player_pos).try_normalize().unwrap_or(Vec2::X);
+            (player_pos + direction * Vec2::new(25.0, 50.0), direction)
+        };
+        match skill.behaviour {
+            SkillBehaviour::Melee => {
+                let direction = match (target_pos - player_pos).try_normalize() {
+                    Some(direction) => direction,
+                    None if player.current_direction != Vec2::ZERO => player.current_direction,
+                    None => Vec2::NEG_X,
+                };
+                let damage = SkillDamage {
+                    amount: damage.amount
+                        * (1.0 + player.attributes.strength as f32 * STRENGTH_DAMAGE_BONUS),
+                    ..damage
+                };
+                let swing = commands
+                    .spawn((
+                        MeleeSwing {
+                            direction,
+                            damage,
+                            timer: Timer::new(SWING_DURATION, TimerMode::Once),
+                            hit: Vec::new(),
+                        },
+                        Collider::cuboid(range / 2.0, SWING_WIDTH / 2.0),
+                        Sensor,
+                        TransformBundle::from_transform(
+                            Transform::from_translation((direction * range / 2.0).extend(0.0))
+                                .with_rotation(Quat::from_rotation_z(
+                                    Vec2::X.angle_between(direction),
+                                )),
+                        ),
+                    ))
+                    .id();
+                commands.entity(player_entity).add_child(swing);
+            }
+            SkillBehaviour::Projectile {
+                speed,
+                acceleration,
+                pierce,
+                bounces,
+                falloff,
+                explosion,
+                lifetime,
+                homing,
+            } => {
+                let (origin, direction) = launch(target_pos);
+                // exploding missiles land on the cursor when it is in range
+                let range = match explosion {
+                    Some(_) => range.min(origin.distance(target_pos)),
+                    None => range,
+                };
+                let homing = homing.and_then(|turn_rate| {
+                    target_q
+                        .iter()
+                        .filter(|(_, _, health)| !health.is_dead())
+                        .map(|(target, transform, _)| {
+                            (
+                                target,
+                                transform.translation().truncate().distance(target_pos),
+                            )
+                        })
+                        .filter(|(_, distance)| *distance <= HOMING_TARGET_RADIUS)
+                        .min_by(|(_, a), (_, b)| a.total_cmp(b))
+                        .map(|(target, _)| Homing { target, turn_rate })
+                });
+                cast_events.spawn_missile.send(SpawnMissile {
+                    caster: player_entity,
+                    origin,
+                    direction,
+                    range,
+                    speed,
+                    acceleration,
+                    pierce,
+                    bounces,
+                    damage,
+                    falloff,
+                    vfx,
+                    explosion,
+                    scale: 1.0,
+                    lifetime: lifetime.map(Duration::from_secs_f32),
+                    homing,
+                });
+            }
+            SkillBehaviour::Nova {
+                speed,
+                shards,
+                falloff,
+            } => {
+                for shard in 0..shards {
+                    let angle = std::f32::consts::TAU * shard as f32 / shards as f32;
+                    let (origin, direction) = launch(player_pos + Vec2::from_angle(angle));
+                    cast_events.spawn_missile.send(SpawnMissile {
+                        caster: player_entity,
+                        origin,
+                        direction,
                         range,
-                        target_pos: mouse_coords.0,
+                        speed,
+                        acceleration: 0.0,
+                        pierce: 0,
+                        bounces: 0,
+                        damage,
+                        falloff,
+                        vfx,
+                        explosion: None,
+                        scale: NOVA_SHARD_SCALE,
+                        lifetime: None,
+                        homing: None,
                     });
                 }
-                _ => {
-                    println!("unimplemented..");
-                    casted = false;
-                }
             }
-
-            if casted {
-                player.attack_cooldown.0.reset();
+            SkillBehaviour::Area(explosion) => {
+                let offset = target_pos - player_pos;
+                cast_events.spawn_explosion.send(SpawnExplosion {
+                    position: player_pos + offset.clamp_length_max(range),
+                    explosion,
+                    damage,
+                });
             }
         }
+
+        let cooldown = Duration::from_secs_f32(skill.cooldown);
+        player.cooldowns.start(&skill_id, cooldown, rate);
     }
 }
 
-pub fn update_missile_path(
-    mut missile_q: Query<(&Direction, &mut Transform), With<Missile>>,
+fn update_animations(
+    mut missile_q: Query<(&Missile, &mut AnimationPlayer2D)>,
+    texture_assets: Res<TextureAssets>,
+) {
+    for (missile, mut animator) in missile_q.iter_mut() {
+        if missile.vfx == SkillVfx::IceMissile && animator.is_finished() {
+            let clip = texture_assets.ice_spell_one_animations[1].clone();
+            animator.play(clip).repeat();
+        }
+    }
+}
+
+/// Hits whatever the swing overlaps, then removes it once the swing is over.
+fn melee_hits(
+    mut commands: Commands,
     time: Res<Time>,
+    rapier_context: Res<RapierContext>,
+    player_q: Query<&Player>,
+    mut swing_q: Query<(Entity, &mut MeleeSwing)>,
+    transform_q: Query<&GlobalTransform>,
+    mut skill_hit_events: EventWriter<SkillHit>,
 ) {
-    let missile_speed = 200.0;
-    for (direction, mut transform) in missile_q.iter_mut() {
-        let displacement = direction.0 * missile_speed * time.delta_seconds();
-        // println!(
-        //     "Direction {:?}, Displacement {:?}",
-        //     direction.0, displacement
-        // );
-        transform.translation += displacement.extend(0.0);
+    let player_collider = player_q
+        .get_single()
+        .ok()
+        .map(|player| player.collider_entity);
+    for (entity, mut swing) in swing_q.iter_mut() {
+        for (collider1, collider2, intersecting) in rapier_context.intersection_pairs_with(entity) {
+            let target = if collider1 == entity {
+                collider2
+            } else {
+                collider1
+            };
+            if !intersecting || Some(target) == player_collider || swing.hit.contains(&target) {
+                continue;
+            }
+            swing.hit.push(target);
+            skill_hit_events.send(SkillHit {
+                target,
+                position: transform_q
+                    .get(target)
+                    .map(|transform| transform.translation().truncate())
+                    .unwrap_or_default(),
+                damage: swing.damage.amount,
+                element: swing.damage.element,
+            });
+        }
+
+        swing.timer.tick(time.delta());
+        if swing.timer.finished() {
+            commands.entity(entity).despawn_recursive();
+        }
     }
 }
 
-fn update_animations(
-    mut missile_q: Query<&mut AnimationPlayer2D, With<Missile>>,
-    texture_assets: Res<TextureAssets>,
+/// Turns skill hits into damage for whatever owns the collider hit.
+fn skill_damage(
+    player_q: Query<(Entity, &Player)>,
+    health_q: Query<(), With<Health>>,
+    parent_q: Query<&Parent>,
+    mut skill_hit_events: EventReader<SkillHit>,
+    mut damage_events: EventWriter<DamageEvent>,
+) {
+    let Ok((player_entity, player)) = player_q.get_single() else {
+        return;
+    };
+    for hit in skill_hit_events.read() {
+        let target = if health_q.contains(hit.target) {
+            hit.target
+        } else {
+            match parent_q.get(hit.target) {
+                Ok(parent) if health_q.contains(parent.get()) => parent.get(),
+                _ => continue,
+            }
+        };
+        damage_events.send(DamageEvent {
+            source: Some(player_entity),
+            target,
+            element: hit.element,
+            amount: hit.damage,
+            crit_chance: player.stats.crit_chance,
+            flags: DamageFlags::NONE,
+        });
+    }
+}
+
+fn despawn_finished_effects(
+    mut commands: Commands,
+    effect_q: Query<(Entity, &AnimationPlayer2D), With<SkillEffect>>,
 ) {
-    if let Ok(mut animator) = missile_q.get_single_mut() {
+    for (entity, animator) in &effect_q {
         if animator.is_finished() {
-            let clip = texture_assets.ice_spell_one_animations[1].clone();
-            animator.play(clip).repeat();
+            commands.entity(entity).despawn_recursive();
         }
     }
 }
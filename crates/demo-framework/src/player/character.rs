@@ -0,0 +1,153 @@
+use bevy::{
+    app::{App, Plugin},
+    asset::{Asset, AssetServer, Assets, Handle},
+    ecs::{
+        schedule::OnExit,
+        system::{Res, ResMut, Resource},
+    },
+    math::Vec2,
+    reflect::TypePath,
+    render::texture::Image,
+    sprite::TextureAtlasLayout,
+};
+use serde::Deserialize;
+
+use crate::{
+    animation::{AnimationAction, AnimationSet, Facing},
+    loading::DataAssets,
+    GameState,
+};
+
+/// Clips every character's animation file must define, with the action and
+/// facing they are played for. The sheets only face left, right is mirrored.
+pub const CHARACTER_CLIPS: [(&str, AnimationAction, Facing); 16] = [
+    ("idle-down", AnimationAction::Idle, Facing::Down),
+    ("idle-left", AnimationAction::Idle, Facing::Left),
+    ("idle-right", AnimationAction::Idle, Facing::Right),
+    ("idle-top", AnimationAction::Idle, Facing::Up),
+    ("walking-down", AnimationAction::Walk, Facing::Down),
+    ("walking-left", AnimationAction::Walk, Facing::Left),
+    ("walking-right", AnimationAction::Walk, Facing::Right),
+    ("walking-top", AnimationAction::Walk, Facing::Up),
+    ("sprinting-down", AnimationAction::Sprint, Facing::Down),
+    ("sprinting-left", AnimationAction::Sprint, Facing::Left),
+    ("sprinting-right", AnimationAction::Sprint, Facing::Right),
+    ("sprinting-top", AnimationAction::Sprint, Facing::Up),
+    ("attacking-down", AnimationAction::Attack, Facing::Down),
+    ("attacking-left", AnimationAction::Attack, Facing::Left),
+    ("attacking-right", AnimationAction::Attack, Facing::Right),
+    ("attacking-top", AnimationAction::Attack, Facing::Up),
+];
+/// Atlas index of the frame shown when previewing a character.
+pub const PREVIEW_FRAME: usize = 8;
+
+pub struct PlayerCharacterPlugin;
+
+impl Plugin for PlayerCharacterPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CharacterSprites>()
+            .init_resource::<SelectedCharacter>()
+            .add_systems(OnExit(GameState::Loading), load_character_sprites);
+    }
+}
+
+/// Identifies a character definition, e.g. `"female_2"`.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash, Deserialize)]
+#[serde(transparent)]
+pub struct CharacterId(pub String);
+
+/// A playable appearance: a sprite sheet region and its animations.
+#[derive(Clone, Debug, Deserialize)]
+pub struct CharacterDefinition {
+    pub id: CharacterId,
+    pub name: String,
+    pub texture: String,
+    /// Trickfilm file defining [`CHARACTER_CLIPS`] for the sheet.
+    pub animations: String,
+    pub tile_size: Vec2,
+    pub columns: usize,
+    pub rows: usize,
+    /// Where the character's frames start in the sheet, in pixels.
+    #[serde(default)]
+    pub offset: Vec2,
+}
+
+/// The characters offered on character creation, in order.
+#[derive(Asset, TypePath, Debug, Deserialize)]
+pub struct CharacterBook {
+    pub characters: Vec<CharacterDefinition>,
+}
+
+/// Loaded handles of a character's sprite sheet.
+#[derive(Clone, Debug)]
+pub struct CharacterSprite {
+    pub id: CharacterId,
+    pub name: String,
+    pub texture: Handle<Image>,
+    pub layout: Handle<TextureAtlasLayout>,
+    pub animations: AnimationSet,
+}
+
+/// Sprites of every character in the character book, in order.
+#[derive(Resource, Default, Debug)]
+pub struct CharacterSprites(pub Vec<CharacterSprite>);
+
+impl CharacterSprites {
+    pub fn get(&self, id: &CharacterId) -> Option<&CharacterSprite> {
+        self.0.iter().find(|sprite| sprite.id == *id)
+    }
+}
+
+/// The appearance and name the next player is spawned with.
+#[derive(Resource, Default, Debug)]
+pub struct SelectedCharacter {
+    pub character: CharacterId,
+    pub name: String,
+}
+
+/// Loads [`CHARACTER_CLIPS`] from a trickfilm file.
+fn character_animations(asset_server: &AssetServer, path: &str) -> AnimationSet {
+    let mut set = AnimationSet::default();
+    for (clip, action, facing) in CHARACTER_CLIPS {
+        set.insert(
+            action,
+            facing,
+            asset_server.load(format!("{path}#{clip}")),
+            facing == Facing::Right,
+        );
+    }
+    set
+}
+
+fn load_character_sprites(
+    asset_server: Res<AssetServer>,
+    data_assets: Res<DataAssets>,
+    character_books: Res<Assets<CharacterBook>>,
+    mut layouts: ResMut<Assets<TextureAtlasLayout>>,
+    mut sprites: ResMut<CharacterSprites>,
+    mut selected: ResMut<SelectedCharacter>,
+) {
+    let Some(book) = character_books.get(&data_assets.characters) else {
+        return;
+    };
+    sprites.0 = book
+        .characters
+        .iter()
+        .map(|definition| CharacterSprite {
+            id: definition.id.clone(),
+            name: definition.name.clone(),
+            texture: asset_server.load(&definition.texture),
+            layout: layouts.add(TextureAtlasLayout::from_grid(
+                definition.tile_size,
+                definition.columns,
+                definition.rows,
+                None,
+                Some(definition.offset),
+            )),
+            animations: character_animations(&asset_server, &definition.animations),
+        })
+        .collect();
+    if let Some(first) = sprites.0.first() {
+        selected.character = first.id.clone();
+    }
+}
@@ -0,0 +1,247 @@
+use std::{f32::consts::FRAC_PI_2, time::Duration};
+
+use bevy::{
+    app::{App, Plugin, Update},
+    asset::Assets,
+    ecs::{
+        component::Component,
+        entity::Entity,
+        event::EventReader,
+        query::With,
+        schedule::{common_conditions::in_state, IntoSystemConfigs, NextState, OnEnter, OnExit},
+        system::{Commands, Query, Res, ResMut, Resource},
+    },
+    hierarchy::DespawnRecursiveExt,
+    math::{Quat, Vec2},
+    render::color::Color,
+    sprite::Sprite,
+    time::{Time, Timer, TimerMode},
+    transform::components::{GlobalTransform, Transform},
+};
+use bevy_rapier2d::dynamics::Velocity;
+use bevy_trickfilm::animation::AnimationPlayer2D;
+
+use crate::{
+    animation::{AnimationAction, AnimationSet, AnimationState},
+    damage::{DamageSet, Died, Health, Invulnerable},
+    item::{consumable::Restorations, Gold},
+    loading::DataAssets,
+    status::StatusEffects,
+    world::{MapProperties, MapTriggerEvent},
+    GameState,
+};
+
+use super::{
+    dodge::Dodge,
+    experience::{Discoveries, Progression},
+    navigation::NavPath,
+    stats::Modifiers,
+    Player, Stats, PLAYER_SPAWN_POS,
+};
+
+const DEATH_ANIMATION_DURATION: Duration = Duration::from_millis(800);
+const DEAD_TINT: Color = Color::rgb(0.5, 0.2, 0.2);
+
+pub struct PlayerDeathPlugin;
+
+impl Plugin for PlayerDeathPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Hardcore>()
+            .init_resource::<Checkpoint>()
+            .init_resource::<LastDeath>()
+            .add_systems(
+                Update,
+                (player_death.after(DamageSet), reach_checkpoints)
+                    .run_if(in_state(GameState::Playing)),
+            )
+            .add_systems(OnEnter(GameState::GameOver), death_penalty)
+            .add_systems(Update, death_animation)
+            .add_systems(OnExit(GameState::GameOver), respawn_player);
+    }
+}
+
+/// Whether death deletes the character instead of respawning it.
+#[derive(Resource, Default, Debug)]
+pub struct Hardcore(pub bool);
+
+/// Where the player respawns, the spawn position until a checkpoint is
+/// reached. Map triggers with a `checkpoint` property are checkpoints.
+#[derive(Resource, Default, Debug)]
+pub struct Checkpoint(pub Option<Vec2>);
+
+/// What the player lost when they last died.
+#[derive(Resource, Default, Debug)]
+pub struct LastDeath {
+    pub exp_lost: u32,
+    pub gold_lost: u32,
+}
+
+/// The player toppling over and fading after dying, for characters without a
+/// death clip.
+#[derive(Component)]
+struct Dying {
+    timer: Timer,
+}
+
+fn player_death(
+    mut commands: Commands,
+    mut player_q: Query<(&mut Velocity, &AnimationSet, &mut AnimationState), With<Player>>,
+    mut died_events: EventReader<Died>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    for died in died_events.read() {
+        let Ok((mut velocity, animations, mut animation)) = player_q.get_mut(died.entity) else {
+            continue;
+        };
+        *velocity = Velocity::zero();
+        animation.play_once(animations, AnimationAction::Die);
+        commands.entity(died.entity).insert(Dying {
+            timer: Timer::new(DEATH_ANIMATION_DURATION, TimerMode::Once),
+        });
+        next_state.set(GameState::GameOver);
+    }
+}
+
+/// Takes some experience and gold from the player, unless in hardcore where
+/// the whole character is lost.
+fn death_penalty(
+    mut player_q: Query<(&mut Player, &mut Gold), With<Dying>>,
+    hardcore: Res<Hardcore>,
+    data_assets: Res<DataAssets>,
+    progressions: Res<Assets<Progression>>,
+    mut last_death: ResMut<LastDeath>,
+) {
+    *last_death = LastDeath::default();
+    let (Ok((mut player, mut gold)), Some(progression)) = (
+        player_q.get_single_mut(),
+        progressions.get(&data_assets.progression),
+    ) else {
+        return;
+    };
+    if hardcore.0 {
+        return;
+    }
+
+    // experience is lost from the current level only, never de-leveling
+    let experience = &mut player.experience;
+    let needed = experience.current + experience.remaining;
+    let exp_lost =
+        ((needed as f32 * progression.death_exp_loss).round() as u32).min(experience.current);
+    experience.current -= exp_lost;
+    experience.remaining += exp_lost;
+    let gold_lost = (gold.0 as f32 * progression.death_gold_loss).round() as u32;
+    gold.0 -= gold_lost;
+    *last_death = LastDeath {
+        exp_lost,
+        gold_lost,
+    };
+}
+
+fn reach_checkpoints(
+    trigger_q: Query<(&MapProperties, &GlobalTransform)>,
+    mut checkpoint: ResMut<Checkpoint>,
+    mut map_trigger_events: EventReader<MapTriggerEvent>,
+) {
+    for event in map_trigger_events.read() {
+        let Ok((properties, transform)) = trigger_q.get(event.trigger) else {
+            continue;
+        };
+        if event.entered && properties.get_bool("checkpoint").unwrap_or(false) {
+            checkpoint.0 = Some(transform.translation().truncate());
+        }
+    }
+}
+
+fn death_animation(
+    mut dying_q: Query<(
+        &mut Dying,
+        &mut Transform,
+        &mut Sprite,
+        &mut AnimationPlayer2D,
+        &AnimationState,
+    )>,
+    time: Res<Time>,
+) {
+    for (mut dying, mut transform, mut sprite, mut animator, animation) in dying_q.iter_mut() {
+        if dying.timer.finished() || animation.is_playing_once(AnimationAction::Die) {
+            continue;
+        }
+        dying.timer.tick(time.delta());
+        let progress = dying.timer.fraction();
+        if !animator.is_paused() {
+            animator.pause();
+        }
+        transform.rotation = Quat::from_rotation_z(FRAC_PI_2 * progress);
+        let [r, g, b, _] = DEAD_TINT.as_rgba_f32();
+        sprite.color = Color::rgb(
+            1.0 + (r - 1.0) * progress,
+            1.0 + (g - 1.0) * progress,
+            1.0 + (b - 1.0) * progress,
+        );
+    }
+}
+
+type RespawnData<'a> = (
+    Entity,
+    &'a mut Player,
+    &'a Modifiers,
+    &'a mut Health,
+    &'a mut StatusEffects,
+    &'a mut Restorations,
+    &'a mut Transform,
+    &'a mut Sprite,
+    &'a mut AnimationPlayer2D,
+    &'a mut AnimationState,
+);
+
+/// Brings the player back at the last checkpoint once the game over screen is
+/// left, or deletes the character in hardcore.
+fn respawn_player(
+    mut commands: Commands,
+    mut player_q: Query<RespawnData, With<Dying>>,
+    hardcore: Res<Hardcore>,
+    mut checkpoint: ResMut<Checkpoint>,
+    mut discoveries: ResMut<Discoveries>,
+) {
+    let Ok((
+        entity,
+        mut player,
+        modifiers,
+        mut health,
+        mut effects,
+        mut restorations,
+        mut transform,
+        mut sprite,
+        mut animator,
+        mut animation,
+    )) = player_q.get_single_mut()
+    else {
+        return;
+    };
+
+    if hardcore.0 {
+        checkpoint.0 = None;
+        *discoveries = Discoveries::default();
+        // a new character is made from the menu
+        commands.entity(entity).despawn_recursive();
+        return;
+    }
+
+    commands
+        .entity(entity)
+        .remove::<(Dying, Dodge, Invulnerable, NavPath)>();
+    let stats = Stats::derive(&player.attributes, player.experience.level, modifiers);
+    *health = Health::new(stats.max_life);
+    player.stats.mana = stats.mana;
+    player.stats.stamina = stats.stamina;
+    player.casting = None;
+    *effects = StatusEffects::default();
+    *restorations = Restorations::default();
+
+    let position = checkpoint.0.unwrap_or(PLAYER_SPAWN_POS.truncate());
+    transform.translation = position.extend(PLAYER_SPAWN_POS.z);
+    transform.rotation = Quat::IDENTITY;
+    sprite.color = Color::WHITE;
+    animator.resume();
+    animation.reset();
+}
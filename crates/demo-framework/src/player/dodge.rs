@@ -0,0 +1,144 @@
+use std::time::Duration;
+
+use bevy::{
+    app::{App, Plugin, Update},
+    ecs::{
+        component::Component,
+        entity::Entity,
+        query::{With, Without},
+        schedule::{common_conditions::in_state, IntoSystemConfigs},
+        system::{Commands, Query, Res},
+    },
+    math::Vec2,
+    time::{Time, Timer, TimerMode},
+    transform::components::Transform,
+};
+use bevy_rapier2d::dynamics::Velocity;
+
+use crate::{
+    damage::{DamageSet, Invulnerable},
+    status::StatusEffects,
+    GameState,
+};
+
+use super::{
+    attack::MeleeSwing,
+    input::{MouseWorldCoords, PlayerInput},
+    Player,
+};
+
+pub(super) const DODGE_SPEED: f32 = 260.0;
+const DODGE_DURATION: Duration = Duration::from_millis(250);
+/// How long damage is ignored from the start of the dodge.
+const DODGE_INVULNERABILITY: Duration = Duration::from_millis(200);
+/// Counted from the start of the dodge.
+const DODGE_COOLDOWN: Duration = Duration::from_millis(800);
+const DODGE_STAMINA_COST: f32 = 15.0;
+
+pub struct PlayerDodgePlugin;
+
+impl Plugin for PlayerDodgePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (tick_dodge_cooldown, start_dodge, dodge_movement)
+                .chain()
+                .before(DamageSet)
+                .run_if(in_state(GameState::Playing)),
+        );
+    }
+}
+
+/// The player rolling in `direction`, moving too fast to be steered.
+#[derive(Component, Clone, Debug)]
+pub(crate) struct Dodge {
+    pub(crate) direction: Vec2,
+    timer: Timer,
+}
+
+/// Time until the player can dodge again.
+#[derive(Component, Clone, Debug, Default)]
+pub(crate) struct DodgeCooldown(Option<Timer>);
+
+fn tick_dodge_cooldown(mut cooldown_q: Query<&mut DodgeCooldown>, time: Res<Time>) {
+    for mut cooldown in cooldown_q.iter_mut() {
+        if cooldown
+            .0
+            .as_mut()
+            .is_some_and(|timer| timer.tick(time.delta()).finished())
+        {
+            cooldown.0 = None;
+        }
+    }
+}
+
+type DodgingPlayer<'a> = (
+    Entity,
+    &'a mut Player,
+    &'a mut DodgeCooldown,
+    &'a Transform,
+    Option<&'a StatusEffects>,
+);
+
+/// Dodges in the movement direction, or toward the mouse when standing still.
+/// Being frozen prevents it.
+fn start_dodge(
+    mut commands: Commands,
+    mut player_q: Query<DodgingPlayer, Without<Dodge>>,
+    swing_q: Query<&MeleeSwing>,
+    player_input: Res<PlayerInput>,
+    mouse_coords: Res<MouseWorldCoords>,
+) {
+    let Ok((entity, mut player, mut cooldown, transform, effects)) = player_q.get_single_mut()
+    else {
+        return;
+    };
+    if !player_input.is_dodging
+        || cooldown.0.is_some()
+        || !swing_q.is_empty()
+        || effects.is_some_and(StatusEffects::is_frozen)
+    {
+        return;
+    }
+
+    let direction = [
+        player_input.movement_direction,
+        (mouse_coords.0 - transform.translation.truncate()).normalize_or_zero(),
+        player.current_direction,
+    ]
+    .into_iter()
+    .find(|direction| *direction != Vec2::ZERO)
+    .unwrap_or(Vec2::NEG_X);
+    if !player.stats.stamina.spend(DODGE_STAMINA_COST) {
+        return;
+    }
+
+    player.current_direction = direction;
+    cooldown.0 = Some(Timer::new(DODGE_COOLDOWN, TimerMode::Once));
+    commands.entity(entity).insert((
+        Dodge {
+            direction,
+            timer: Timer::new(DODGE_DURATION, TimerMode::Once),
+        },
+        Invulnerable(Timer::new(DODGE_INVULNERABILITY, TimerMode::Once)),
+    ));
+}
+
+/// Moves the player through the physics engine so walls still stop the roll.
+/// Chill slows it like walking.
+fn dodge_movement(
+    mut commands: Commands,
+    mut player_q: Query<(Entity, &mut Dodge, &mut Velocity, Option<&StatusEffects>), With<Player>>,
+    time: Res<Time>,
+) {
+    for (entity, mut dodge, mut velocity, effects) in player_q.iter_mut() {
+        if dodge.timer.tick(time.delta()).finished() {
+            velocity.linvel = Vec2::ZERO;
+            commands.entity(entity).remove::<Dodge>();
+        } else {
+            velocity.linvel = dodge.direction
+                * DODGE_SPEED
+                * effects.map_or(1.0, StatusEffects::speed_multiplier);
+        }
+    }
+}
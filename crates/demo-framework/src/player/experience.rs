@@ -0,0 +1,230 @@
+use std::{collections::HashSet, time::Duration};
+
+use bevy::{
+    app::{App, Plugin, Update},
+    asset::{Asset, Assets},
+    ecs::{
+        component::Component,
+        entity::Entity,
+        event::{Event, EventReader, EventWriter},
+        query::With,
+        schedule::{common_conditions::in_state, IntoSystemConfigs},
+        system::{Commands, Query, Res, ResMut, Resource},
+    },
+    hierarchy::{BuildChildren, DespawnRecursiveExt},
+    math::Vec3,
+    reflect::TypePath,
+    render::color::Color,
+    time::{Time, Timer, TimerMode},
+    transform::{components::Transform, TransformBundle},
+};
+use bevy_magic_light_2d::gi::types::OmniLightSource2D;
+use serde::Deserialize;
+
+use crate::{
+    damage::{DamageSet, Died, Health},
+    loading::DataAssets,
+    world::MapTriggerEvent,
+    GameState,
+};
+
+use super::{stats::Modifiers, Player, Stats};
+
+const LEVEL_UP_EFFECT_DURATION: Duration = Duration::from_millis(1200);
+
+pub struct PlayerExperiencePlugin;
+
+impl Plugin for PlayerExperiencePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Discoveries>()
+            .add_event::<GainExperience>()
+            .add_event::<LevelUp>()
+            .add_systems(
+                Update,
+                (
+                    (kill_experience.after(DamageSet), discovery_experience),
+                    gain_experience,
+                    level_up_effect,
+                )
+                    .chain()
+                    .run_if(in_state(GameState::Playing)),
+            );
+    }
+}
+
+/// How experience and levels work, loaded from `data/*.progression.ron`.
+#[derive(Asset, TypePath, Debug, Deserialize)]
+pub struct Progression {
+    /// Experience needed to reach level 2.
+    pub base_exp: f32,
+    /// Each level needs this many times the experience of the previous one.
+    pub growth: f32,
+    pub max_level: u16,
+    pub attribute_points_per_level: u16,
+    pub skill_points_per_level: u16,
+    /// Experience for entering a map area for the first time.
+    pub discovery_exp: u32,
+    /// Fraction of the current level's experience lost on death.
+    #[serde(default)]
+    pub death_exp_loss: f32,
+    /// Fraction of the carried gold lost on death.
+    #[serde(default)]
+    pub death_gold_loss: f32,
+}
+
+impl Progression {
+    /// Experience needed to go from `level` to the next one.
+    pub fn exp_to_next_level(&self, level: u16) -> u32 {
+        (self.base_exp * self.growth.powi(level as i32 - 1)).round() as u32
+    }
+}
+
+/// Experience granted to whoever kills the entity.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct ExperienceReward(pub u32);
+
+/// Map areas the player has already been rewarded for discovering.
+#[derive(Resource, Default, Debug)]
+pub struct Discoveries(pub HashSet<String>);
+
+#[derive(Event, Clone, Copy, Debug)]
+pub struct GainExperience {
+    pub entity: Entity,
+    pub amount: u32,
+}
+
+#[derive(Event, Clone, Copy, Debug)]
+pub struct LevelUp {
+    pub entity: Entity,
+    pub level: u16,
+}
+
+/// A golden glow around the player after leveling up.
+#[derive(Component)]
+struct LevelUpEffect {
+    timer: Timer,
+}
+
+fn kill_experience(
+    reward_q: Query<&ExperienceReward>,
+    player_q: Query<(), With<Player>>,
+    mut died_events: EventReader<Died>,
+    mut gain_experience_events: EventWriter<GainExperience>,
+) {
+    for died in died_events.read() {
+        let (Ok(reward), Some(killer)) = (reward_q.get(died.entity), died.killer) else {
+            continue;
+        };
+        if player_q.contains(killer) {
+            gain_experience_events.send(GainExperience {
+                entity: killer,
+                amount: reward.0,
+            });
+        }
+    }
+}
+
+fn discovery_experience(
+    player_q: Query<Entity, With<Player>>,
+    data_assets: Res<DataAssets>,
+    progressions: Res<Assets<Progression>>,
+    mut discoveries: ResMut<Discoveries>,
+    mut map_trigger_events: EventReader<MapTriggerEvent>,
+    mut gain_experience_events: EventWriter<GainExperience>,
+) {
+    let (Ok(player), Some(progression)) = (
+        player_q.get_single(),
+        progressions.get(&data_assets.progression),
+    ) else {
+        return;
+    };
+    for event in map_trigger_events.read() {
+        if event.entered && discoveries.0.insert(event.name.clone()) {
+            gain_experience_events.send(GainExperience {
+                entity: player,
+                amount: progression.discovery_exp,
+            });
+        }
+    }
+}
+
+fn gain_experience(
+    mut commands: Commands,
+    mut player_q: Query<(&mut Player, &Modifiers, &mut Health)>,
+    data_assets: Res<DataAssets>,
+    progressions: Res<Assets<Progression>>,
+    mut gain_experience_events: EventReader<GainExperience>,
+    mut level_up_events: EventWriter<LevelUp>,
+) {
+    let Some(progression) = progressions.get(&data_assets.progression) else {
+        return;
+    };
+    for event in gain_experience_events.read() {
+        let Ok((mut player, modifiers, mut health)) = player_q.get_mut(event.entity) else {
+            continue;
+        };
+
+        let mut amount = event.amount;
+        let mut leveled_up = false;
+        while amount > 0 && player.experience.level < progression.max_level {
+            let gained = amount.min(player.experience.remaining);
+            amount -= gained;
+            player.experience.current += gained;
+            player.experience.remaining -= gained;
+            if player.experience.remaining > 0 {
+                break;
+            }
+
+            let level = player.experience.level + 1;
+            player.experience.level = level;
+            player.experience.current = 0;
+            player.experience.remaining = progression.exp_to_next_level(level);
+            player.attributes.available_points += progression.attribute_points_per_level;
+            player.skills.available_points += progression.skill_points_per_level;
+            leveled_up = true;
+            level_up_events.send(LevelUp {
+                entity: event.entity,
+                level,
+            });
+        }
+
+        if leveled_up {
+            // leveling up fully restores life, mana and stamina
+            let stats = Stats::derive(&player.attributes, player.experience.level, modifiers);
+            *health = Health::new(stats.max_life);
+            player.stats.mana = stats.mana;
+            player.stats.stamina = stats.stamina;
+            commands.entity(event.entity).with_children(|parent| {
+                parent.spawn((
+                    LevelUpEffect {
+                        timer: Timer::new(LEVEL_UP_EFFECT_DURATION, TimerMode::Once),
+                    },
+                    OmniLightSource2D {
+                        intensity: 0.0,
+                        color: Color::rgb_u8(255, 214, 102),
+                        falloff: Vec3::new(20.0, 20.0, 0.05),
+                        ..Default::default()
+                    },
+                    TransformBundle::from_transform(Transform::default()),
+                ));
+            });
+        }
+    }
+}
+
+/// Flares up, then fades out the level-up glow.
+fn level_up_effect(
+    mut commands: Commands,
+    mut effect_q: Query<(Entity, &mut LevelUpEffect, &mut OmniLightSource2D)>,
+    time: Res<Time>,
+) {
+    for (entity, mut effect, mut light) in effect_q.iter_mut() {
+        effect.timer.tick(time.delta());
+        if effect.timer.finished() {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+        let t = effect.timer.fraction();
+        light.intensity = 0.4 * (t * std::f32::consts::PI).sin();
+    }
+}
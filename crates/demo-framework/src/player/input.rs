@@ -3,6 +3,7 @@ use bevy::{
     ecs::{
         event::EventReader,
         query::With,
+        schedule::SystemSet,
         schedule::{common_conditions::in_state, IntoSystemConfigs},
         system::{Query, Res, ResMut, Resource},
     },
@@ -14,10 +15,28 @@ use bevy::{
     math::Vec2,
     render::camera::Camera,
     transform::components::GlobalTransform,
+    ui::{Interaction, UiSystem},
     window::{PrimaryWindow, Window},
 };
 
-use crate::{camera::MainCamera, GameState};
+use crate::{camera::MainCamera, item::consumable::BELT_SLOTS, GameState};
+
+use super::HOTBAR_SLOTS;
+
+const HOTBAR_KEYS: [KeyCode; HOTBAR_SLOTS] = [
+    KeyCode::Digit1,
+    KeyCode::Digit2,
+    KeyCode::Digit3,
+    KeyCode::Digit4,
+    KeyCode::Digit5,
+    KeyCode::Digit6,
+    KeyCode::Digit7,
+    KeyCode::Digit8,
+    KeyCode::Digit9,
+];
+
+pub(crate) const BELT_KEYS: [KeyCode; BELT_SLOTS] =
+    [KeyCode::KeyZ, KeyCode::KeyX, KeyCode::KeyC, KeyCode::KeyV];
 
 pub struct PlayerInputPlugin;
 
@@ -34,8 +53,16 @@ impl Plugin for PlayerInputPlugin {
                         update_scroll_event,
                         update_movement_direction,
                         update_is_running,
-                        update_is_attacking,
+                        update_is_dodging,
+                        update_is_attacking.after(UiSystem::Focus),
+                        update_hotbar_slot,
+                        update_belt_slot,
+                        update_is_picking_up,
+                        update_clicked_at
+                            .after(update_mouse_position_in_world)
+                            .after(UiSystem::Focus),
                     )
+                        .in_set(PlayerInputSet)
                         .after(InputSystem),
                 )
                     .run_if(in_state(GameState::Playing)),
@@ -43,18 +70,43 @@ impl Plugin for PlayerInputPlugin {
     }
 }
 
+/// Systems translating raw input into [`PlayerInput`].
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub(crate) struct PlayerInputSet;
+
 #[derive(Default, Resource)]
 pub(crate) struct PlayerInput {
     pub movement_direction: Vec2,
     pub zoom: f32,
     pub is_running: bool,
+    /// The dodge key was just pressed.
+    pub is_dodging: bool,
     pub is_left_attack: bool,
     pub is_right_attack: bool,
+    /// Index of the hotbar slot whose key is held.
+    pub hotbar_slot: Option<usize>,
+    /// Index of the potion belt slot whose key was just pressed.
+    pub belt_slot: Option<usize>,
+    pub is_picking_up: bool,
+    /// Where in the world the left mouse button was just pressed, if not on
+    /// the UI.
+    pub clicked_at: Option<Vec2>,
+    /// Where skills are aimed instead of the cursor, e.g. at a clicked enemy.
+    pub aim: Option<Vec2>,
 }
 
 #[derive(Resource, Default)]
 pub struct MouseWorldCoords(pub Vec2);
 
+/// How a key is shown in the UI, e.g. `Z` for [`KeyCode::KeyZ`].
+pub(crate) fn key_label(code: KeyCode) -> String {
+    let name = format!("{code:?}");
+    name.strip_prefix("Key")
+        .or_else(|| name.strip_prefix("Digit"))
+        .unwrap_or(&name)
+        .to_string()
+}
+
 fn reset_player_input(mut player_input: ResMut<PlayerInput>) {
     *player_input = PlayerInput::default();
 }
@@ -110,10 +162,23 @@ fn update_is_running(key: Res<ButtonInput<KeyCode>>, mut player_input: ResMut<Pl
     player_input.is_running = key.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]);
 }
 
+fn update_is_dodging(key: Res<ButtonInput<KeyCode>>, mut player_input: ResMut<PlayerInput>) {
+    player_input.is_dodging = key.just_pressed(KeyCode::Space);
+}
+
 fn update_is_attacking(
     mouse: Res<ButtonInput<MouseButton>>,
+    interaction_q: Query<&Interaction>,
     mut player_input: ResMut<PlayerInput>,
 ) {
+    // clicks on the UI aren't attacks
+    if interaction_q
+        .iter()
+        .any(|interaction| *interaction != Interaction::None)
+    {
+        return;
+    }
+
     if mouse.pressed(MouseButton::Left) {
         player_input.is_left_attack = true;
         player_input.is_right_attack = false;
@@ -122,3 +187,43 @@ fn update_is_attacking(
         player_input.is_left_attack = false;
     }
 }
+
+fn update_hotbar_slot(key: Res<ButtonInput<KeyCode>>, mut player_input: ResMut<PlayerInput>) {
+    player_input.hotbar_slot = HOTBAR_KEYS.iter().position(|code| key.pressed(*code));
+}
+
+fn update_belt_slot(key: Res<ButtonInput<KeyCode>>, mut player_input: ResMut<PlayerInput>) {
+    player_input.belt_slot = BELT_KEYS.iter().position(|code| key.just_pressed(*code));
+}
+
+fn update_is_picking_up(key: Res<ButtonInput<KeyCode>>, mut player_input: ResMut<PlayerInput>) {
+    player_input.is_picking_up = key.just_pressed(KeyCode::KeyE);
+}
+
+fn update_clicked_at(
+    mouse: Res<ButtonInput<MouseButton>>,
+    mouse_coords: Res<MouseWorldCoords>,
+    interaction_q: Query<&Interaction>,
+    mut player_input: ResMut<PlayerInput>,
+) {
+    if !mouse.just_pressed(MouseButton::Left)
+        || interaction_q
+            .iter()
+            .any(|interaction| *interaction != Interaction::None)
+    {
+        return;
+    }
+    player_input.clicked_at = Some(mouse_coords.0);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn key_labels_drop_the_code_prefix() {
+        assert_eq!(key_label(KeyCode::KeyZ), "Z");
+        assert_eq!(key_label(KeyCode::Digit1), "1");
+        assert_eq!(key_label(KeyCode::Space), "Space");
+    }
+}
@@ -2,13 +2,16 @@ use bevy::{
     app::{App, Plugin, Update},
     ecs::schedule::{common_conditions::in_state, IntoSystemConfigs},
     math::Vec2,
-    prelude::{Query, Res},
+    prelude::{Query, Res, Without},
 };
 use bevy_rapier2d::dynamics::Velocity;
 
-use crate::GameState;
+use crate::{status::StatusEffects, GameState};
 
-use super::{attack::SpawnMissile, input::PlayerInput, MovementState, Player};
+use super::{attack::SpawnMissile, dodge::Dodge, input::PlayerInput, MovementState, Player};
+
+pub(super) const WALK_SPEED: f32 = 75.0;
+pub(super) const SPRINT_SPEED: f32 = 100.0;
 
 pub struct PlayerMovementPlugin;
 
@@ -19,10 +22,10 @@ impl Plugin for PlayerMovementPlugin {
 }
 
 fn player_movement(
-    mut player_q: Query<(&mut Velocity, &mut Player)>,
+    mut player_q: Query<(&mut Velocity, &mut Player, Option<&StatusEffects>), Without<Dodge>>,
     player_input: Res<PlayerInput>,
 ) {
-    if let Ok((mut velocity, mut player)) = player_q.get_single_mut() {
+    if let Ok((mut velocity, mut player, effects)) = player_q.get_single_mut() {
         let dir = player_input.movement_direction;
         if dir == Vec2::default() {
             velocity.linvel = Vec2::ZERO;
@@ -30,12 +33,12 @@ fn player_movement(
         }
 
         let speed = if player.movement_state == MovementState::Sprinting {
-            100.0
+            SPRINT_SPEED
         } else {
-            75.0
+            WALK_SPEED
         };
 
         player.current_direction = dir;
-        velocity.linvel = dir * speed;
+        velocity.linvel = dir * speed * effects.map_or(1.0, StatusEffects::speed_multiplier);
     }
 }
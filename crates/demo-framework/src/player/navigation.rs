@@ -0,0 +1,194 @@
+use std::collections::VecDeque;
+
+use bevy::{
+    app::{App, Plugin, PreUpdate},
+    ecs::{
+        component::Component,
+        entity::Entity,
+        query::{With, Without},
+        schedule::{
+            common_conditions::{in_state, resource_equals},
+            Condition, IntoSystemConfigs, SystemSet,
+        },
+        system::{Commands, Query, Res, ResMut, Resource},
+    },
+    input::{mouse::MouseButton, ButtonInput},
+    math::Vec2,
+    transform::components::{GlobalTransform, Transform},
+};
+
+use crate::{
+    damage::Health,
+    skill::SkillDefinitions,
+    world::{helpers::TILE_SIZE, NavGrid},
+    GameState,
+};
+
+use super::{
+    input::{PlayerInput, PlayerInputSet},
+    Player,
+};
+
+/// How close to an entity a click has to be to target it.
+const TARGET_CLICK_RADIUS: f32 = 20.0;
+/// How close to a waypoint counts as reaching it.
+const WAYPOINT_RADIUS: f32 = 4.0;
+/// Attacks start a bit inside the skill's range so they connect.
+const ATTACK_RANGE_MARGIN: f32 = 0.8;
+
+pub struct PlayerNavigationPlugin;
+
+impl Plugin for PlayerNavigationPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ControlScheme>().add_systems(
+            PreUpdate,
+            (click_to_move, follow_nav_path)
+                .chain()
+                .in_set(PlayerNavigationSet)
+                .after(PlayerInputSet)
+                .run_if(
+                    in_state(GameState::Playing)
+                        .and_then(resource_equals(ControlScheme::ClickToMove)),
+                ),
+        );
+    }
+}
+
+/// How the player moves, toggled from the main menu.
+#[derive(Resource, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ControlScheme {
+    /// WASD movement, the mouse buttons attack toward the cursor.
+    #[default]
+    Keyboard,
+    /// Left clicks walk to the ground or attack what was clicked. WASD still
+    /// moves and cancels the walk.
+    ClickToMove,
+}
+
+/// Systems steering the player through [`PlayerInput`] along its [`NavPath`].
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub(crate) struct PlayerNavigationSet;
+
+/// Where the player walks to with click-to-move.
+#[derive(Component, Clone, Debug)]
+pub(crate) struct NavPath {
+    /// Left to walk through, the next one first.
+    pub(crate) waypoints: VecDeque<Vec2>,
+    /// Attacked with the left skill once in range.
+    target: Option<Entity>,
+    /// Where the path was planned to, to notice the target moving away.
+    destination: Vec2,
+}
+
+impl NavPath {
+    fn new(nav_grid: &NavGrid, from: Vec2, to: Vec2, target: Option<Entity>) -> Self {
+        Self {
+            waypoints: nav_grid.path(from, to).into(),
+            target,
+            destination: to,
+        }
+    }
+}
+
+fn click_to_move(
+    mut commands: Commands,
+    player_q: Query<(Entity, &Transform), With<Player>>,
+    target_q: Query<(Entity, &GlobalTransform, &Health), Without<Player>>,
+    nav_grid: NavGrid,
+    mut player_input: ResMut<PlayerInput>,
+) {
+    let Ok((entity, transform)) = player_q.get_single() else {
+        return;
+    };
+    // the left button walks instead of attacking
+    player_input.is_left_attack = false;
+    if player_input.movement_direction != Vec2::ZERO {
+        commands.entity(entity).remove::<NavPath>();
+        return;
+    }
+    let Some(clicked) = player_input.clicked_at else {
+        return;
+    };
+
+    let target = target_q
+        .iter()
+        .filter(|(_, _, health)| health.current > 0.0)
+        .map(|(target, transform, _)| (target, transform.translation().truncate()))
+        .filter(|(_, position)| position.distance(clicked) <= TARGET_CLICK_RADIUS)
+        .min_by(|(_, a), (_, b)| a.distance(clicked).total_cmp(&b.distance(clicked)));
+    let destination = target.map_or(clicked, |(_, position)| position);
+    commands.entity(entity).insert(NavPath::new(
+        &nav_grid,
+        transform.translation.truncate(),
+        destination,
+        target.map(|(target, _)| target),
+    ));
+}
+
+/// Walks toward the next waypoint, attacking the target once in range.
+fn follow_nav_path(
+    mut commands: Commands,
+    mut player_q: Query<(Entity, &Player, &Transform, &mut NavPath)>,
+    target_q: Query<(&GlobalTransform, &Health)>,
+    nav_grid: NavGrid,
+    skill_definitions: SkillDefinitions,
+    mouse: Res<ButtonInput<MouseButton>>,
+    mut player_input: ResMut<PlayerInput>,
+) {
+    let Ok((entity, player, transform, mut path)) = player_q.get_single_mut() else {
+        return;
+    };
+    let position = transform.translation.truncate();
+
+    if let Some(target) = path.target {
+        let Some(target_pos) = target_q
+            .get(target)
+            .ok()
+            .filter(|(_, health)| health.current > 0.0)
+            .map(|(transform, _)| transform.translation().truncate())
+        else {
+            commands.entity(entity).remove::<NavPath>();
+            return;
+        };
+
+        let skill = &player.selected_left_skill;
+        let range = skill_definitions
+            .get(skill)
+            .zip(player.skills.level(skill))
+            .map_or(0.0, |(definition, level)| definition.range(level));
+        if position.distance(target_pos) <= range * ATTACK_RANGE_MARGIN {
+            path.waypoints.clear();
+            // waits for the skill rather than dropping the attack
+            if player.casting.is_none() && player.cooldowns.is_ready(skill) {
+                player_input.is_left_attack = true;
+                player_input.aim = Some(target_pos);
+                // holding the button keeps attacking
+                if !mouse.pressed(MouseButton::Left) {
+                    commands.entity(entity).remove::<NavPath>();
+                }
+            }
+            return;
+        }
+
+        if target_pos.distance(path.destination) > TILE_SIZE.x {
+            *path = NavPath::new(&nav_grid, position, target_pos, Some(target));
+        }
+    }
+
+    while path
+        .waypoints
+        .front()
+        .is_some_and(|waypoint| waypoint.distance(position) <= WAYPOINT_RADIUS)
+    {
+        path.waypoints.pop_front();
+    }
+    match path.waypoints.front() {
+        Some(waypoint) => {
+            player_input.movement_direction = (*waypoint - position).normalize_or_zero();
+        }
+        // arrived, or as close as the path gets to the target
+        None => {
+            commands.entity(entity).remove::<NavPath>();
+        }
+    }
+}
@@ -1,22 +1,29 @@
-use std::f32::consts::PI;
-
 use bevy::{
     app::{App, Plugin, PostUpdate},
     ecs::{
-        component::Component,
+        entity::Entity,
+        event::EventReader,
+        query::Has,
         schedule::{common_conditions::in_state, IntoSystemConfigs},
         system::{Query, Res},
     },
     math::Vec2,
-    sprite::Sprite,
-    time::Timer,
 };
 use bevy_rapier2d::dynamics::Velocity;
-use bevy_trickfilm::animation::AnimationPlayer2D;
 
-use crate::{loading::TextureAssets, GameState};
+use crate::{
+    animation::{AnimationAction, AnimationSet, AnimationState, AnimationSystem},
+    damage::DamageDealt,
+    skill::SkillDefinitions,
+    GameState,
+};
 
-use super::{input::PlayerInput, Player};
+use super::{
+    attack::{MeleeSwing, SkillUsed},
+    dodge::Dodge,
+    input::PlayerInput,
+    Player,
+};
 
 pub struct PlayerStatePlugin;
 
@@ -26,32 +33,38 @@ impl Plugin for PlayerStatePlugin {
             PostUpdate,
             (
                 update_player_movement_state,
-                update_animations.after(update_player_movement_state),
+                update_player_animation
+                    .after(update_player_movement_state)
+                    .before(AnimationSystem),
             )
                 .run_if(in_state(GameState::Playing)),
         );
     }
 }
 
-#[derive(Component, Debug, Default)]
-pub struct CooldownTimer(pub Timer);
-
 #[derive(Clone, Copy, Debug, Default, PartialEq)]
 pub(crate) enum MovementState {
     #[default]
     Idle,
     Walking,
     Sprinting,
+    Attacking,
+    Dodging,
 }
 
 fn update_player_movement_state(
-    mut player_q: Query<(&Velocity, &mut Player)>,
+    mut player_q: Query<(&Velocity, &mut Player, Has<Dodge>)>,
+    swing_q: Query<&MeleeSwing>,
     player_input: Res<PlayerInput>,
 ) {
-    if let Ok((velocity, mut player)) = player_q.get_single_mut() {
-        let state = if velocity.linvel == Vec2::ZERO {
+    if let Ok((velocity, mut player, is_dodging)) = player_q.get_single_mut() {
+        let state = if is_dodging {
+            MovementState::Dodging
+        } else if !swing_q.is_empty() {
+            MovementState::Attacking
+        } else if velocity.linvel == Vec2::ZERO {
             MovementState::Idle
-        } else if player_input.is_running {
+        } else if player_input.is_running && !player.is_exhausted {
             MovementState::Sprinting
         } else {
             MovementState::Walking
@@ -60,43 +73,66 @@ fn update_player_movement_state(
     }
 }
 
-fn update_animations(
-    mut player_q: Query<(&Velocity, &mut AnimationPlayer2D, &mut Sprite, &Player)>,
-    texture_assets: Res<TextureAssets>,
-) {
-    if let Ok((velocity, mut animator, mut sprite, player)) = player_q.get_single_mut() {
-        let dir = if velocity.linvel == Vec2::ZERO {
-            if player.current_direction == Vec2::ZERO {
-                Vec2::NEG_X
-            } else {
-                player.current_direction
-            }
-        } else {
-            velocity.linvel
-        };
+type PlayerAnimationData<'a> = (
+    Entity,
+    &'a Velocity,
+    &'a Player,
+    &'a AnimationSet,
+    &'a mut AnimationState,
+);
 
-        let angle = Vec2::X.angle_between(dir);
-        let dir_index = match angle {
-            a if (-3.0 / 4.0 * PI..=-1.0 / 4.0 * PI).contains(&a) => 0, // Down
-            a if (1.0 / 4.0 * PI..=3.0 / 4.0 * PI).contains(&a) => 3,   // Up
-            a if !(-3.0 / 4.0 * PI..=3.0 / 4.0 * PI).contains(&a) => 1, // Left
-            _ => 2,                                                     // Right
-        };
+/// Feeds the player's movement, skills and hits to their animation state.
+fn update_player_animation(
+    mut player_q: Query<PlayerAnimationData>,
+    skill_definitions: SkillDefinitions,
+    mut dealt_events: EventReader<DamageDealt>,
+    mut skill_used_events: EventReader<SkillUsed>,
+) {
+    let Ok((entity, velocity, player, set, mut state)) = player_q.get_single_mut() else {
+        return;
+    };
 
-        sprite.flip_x = dir_index == 2;
+    let direction = if velocity.linvel == Vec2::ZERO {
+        player.current_direction
+    } else {
+        velocity.linvel
+    };
+    if direction != Vec2::ZERO {
+        state.direction = direction;
+    }
+    state.locomotion = match player.movement_state {
+        MovementState::Idle | MovementState::Attacking => AnimationAction::Idle,
+        MovementState::Walking => AnimationAction::Walk,
+        MovementState::Sprinting => AnimationAction::Sprint,
+        MovementState::Dodging => AnimationAction::Dodge,
+    };
 
-        let (animation_index, repeat) = match player.movement_state {
-            MovementState::Idle => (dir_index, true),
-            MovementState::Walking => (4 + dir_index, true),
-            MovementState::Sprinting => (8 + dir_index, true),
-            // Attack => no repeat probably??
+    if dealt_events.read().any(|dealt| dealt.target == entity) {
+        state.play_once(set, AnimationAction::Hit);
+    }
+    for used in skill_used_events
+        .read()
+        .filter(|used| used.caster == entity)
+    {
+        // skills face where they are aimed
+        if used.direction != Vec2::ZERO {
+            state.direction = used.direction;
+        }
+        let Some(definition) = skill_definitions.get(&used.skill) else {
+            continue;
         };
-
-        let clip = texture_assets.female_adventurer_animations[animation_index].clone();
-        if repeat {
-            animator.play(clip).repeat();
-        } else {
-            animator.play(clip);
+        // skills with a cast time already played theirs while casting
+        if let Some(action) = definition.animation.filter(|_| definition.cast_time <= 0.0) {
+            state.play_once(set, action);
+        }
+    }
+    if let Some(action) = player.casting.as_ref().and_then(|cast| {
+        skill_definitions
+            .get(&cast.skill)
+            .and_then(|definition| definition.animation)
+    }) {
+        if !state.is_playing_once(action) {
+            state.play_once(set, action);
         }
     }
 }
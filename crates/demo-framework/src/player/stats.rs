@@ -0,0 +1,388 @@
+use bevy::{
+    app::{App, Plugin, Update},
+    ecs::{
+        component::Component,
+        entity::Entity,
+        event::{Event, EventReader},
+        schedule::{common_conditions::in_state, IntoSystemConfigs, SystemSet},
+        system::{Query, Res},
+    },
+    log::warn,
+    time::Time,
+};
+use serde::Deserialize;
+
+use crate::{damage::Health, GameState};
+
+use super::{Attributes, MovementState, Player, Resistances, Stats};
+
+// base values per attribute point and per level past the first
+const LIFE_PER_VITALITY: f32 = 1.5;
+const LIFE_PER_LEVEL: f32 = 2.0;
+const MANA_PER_ENERGY: f32 = 2.0;
+const MANA_PER_LEVEL: f32 = 1.5;
+const STAMINA_PER_VITALITY: f32 = 2.0;
+const STAMINA_PER_LEVEL: f32 = 1.0;
+const DEFENSE_PER_STRENGTH: f32 = 1.2;
+const BASE_CRIT_CHANCE: f32 = 0.05;
+const LIFE_REGEN_PER_VITALITY: f32 = 0.05;
+const MANA_REGEN_PER_ENERGY: f32 = 0.1;
+const BASE_STAMINA_REGEN: f32 = 4.0;
+const STAMINA_REGEN_PER_VITALITY: f32 = 0.1;
+/// Stamina spent per second of sprinting.
+const SPRINT_STAMINA_COST: f32 = 6.0;
+/// Fraction of max stamina needed to sprint again after running out.
+const SPRINT_RECOVERY: f32 = 0.25;
+
+pub struct PlayerStatsPlugin;
+
+impl Plugin for PlayerStatsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<SpendAttributePoint>()
+            .add_event::<RespecAttributes>()
+            .add_systems(
+                Update,
+                (
+                    (spend_attribute_points, respec_attributes).in_set(AttributeSet),
+                    derive_stats,
+                    regenerate,
+                    sprint_stamina,
+                )
+                    .chain()
+                    .run_if(in_state(GameState::Playing)),
+            );
+    }
+}
+
+/// Systems spending and refunding attribute points.
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct AttributeSet;
+
+/// An attribute level-up points can be spent on.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum AttributeKind {
+    Strength,
+    Vitality,
+    Energy,
+}
+
+impl AttributeKind {
+    pub const ALL: [Self; 3] = [Self::Strength, Self::Vitality, Self::Energy];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Strength => "Strength",
+            Self::Vitality => "Vitality",
+            Self::Energy => "Energy",
+        }
+    }
+}
+
+impl Attributes {
+    pub fn get(&self, kind: AttributeKind) -> i32 {
+        match kind {
+            AttributeKind::Strength => self.strength,
+            AttributeKind::Vitality => self.vitality,
+            AttributeKind::Energy => self.energy,
+        }
+    }
+
+    fn get_mut(&mut self, kind: AttributeKind) -> &mut i32 {
+        match kind {
+            AttributeKind::Strength => &mut self.strength,
+            AttributeKind::Vitality => &mut self.vitality,
+            AttributeKind::Energy => &mut self.energy,
+        }
+    }
+
+    /// Raises `kind` by one if there is a point to spend.
+    pub fn spend(&mut self, kind: AttributeKind) -> bool {
+        if self.available_points == 0 {
+            return false;
+        }
+        self.available_points -= 1;
+        *self.get_mut(kind) += 1;
+        *self.spent.entry(kind).or_default() += 1;
+        true
+    }
+
+    /// Takes back every spent point.
+    pub fn respec(&mut self) {
+        for (kind, points) in std::mem::take(&mut self.spent) {
+            *self.get_mut(kind) -= points as i32;
+            self.available_points += points;
+        }
+    }
+}
+
+/// Asks to spend one of the player's attribute points on `attribute`.
+#[derive(Event, Clone, Copy, Debug)]
+pub struct SpendAttributePoint {
+    pub entity: Entity,
+    pub attribute: AttributeKind,
+}
+
+/// Asks to refund every attribute point the player spent.
+#[derive(Event, Clone, Copy, Debug)]
+pub struct RespecAttributes {
+    pub entity: Entity,
+}
+
+/// A stat that modifiers can change.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Deserialize)]
+pub enum StatKind {
+    MaxLife,
+    MaxMana,
+    MaxStamina,
+    Defense,
+    CastRate,
+    AttackRate,
+    CritChance,
+    LifeRegen,
+    ManaRegen,
+    StaminaRegen,
+    ColdResistance,
+    FireResistance,
+    LightningResistance,
+}
+
+/// How a modifier combines with the base value. Stats are computed as
+/// `(base + flat) * (1 + sum of additive) * product of (1 + multiplicative)`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+pub enum ModifierKind {
+    Flat,
+    /// A fraction, e.g. `0.1` for +10%, summed with the other additive modifiers.
+    Additive,
+    /// A fraction, e.g. `0.1` for 10% more, applied on its own.
+    Multiplicative,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Modifier {
+    pub stat: StatKind,
+    pub kind: ModifierKind,
+    pub value: f32,
+    /// What added the modifier, so it can be removed again, e.g. `"item:42"`.
+    pub source: String,
+}
+
+/// Modifiers to an entity's stats from equipment, effects and the like.
+#[derive(Component, Clone, Debug, Default)]
+pub struct Modifiers {
+    pub modifiers: Vec<Modifier>,
+}
+
+impl Modifiers {
+    pub fn add(&mut self, modifier: Modifier) {
+        self.modifiers.push(modifier);
+    }
+
+    /// Removes every modifier added by `source`.
+    pub fn remove_source(&mut self, source: &str) {
+        self.modifiers.retain(|modifier| modifier.source != source);
+    }
+
+    /// Applies the modifiers of `stat` to its base value.
+    pub fn apply(&self, stat: StatKind, base: f32) -> f32 {
+        let mut flat = 0.0;
+        let mut additive = 0.0;
+        let mut multiplicative = 1.0;
+        for modifier in self
+            .modifiers
+            .iter()
+            .filter(|modifier| modifier.stat == stat)
+        {
+            match modifier.kind {
+                ModifierKind::Flat => flat += modifier.value,
+                ModifierKind::Additive => additive += modifier.value,
+                ModifierKind::Multiplicative => multiplicative *= 1.0 + modifier.value,
+            }
+        }
+        ((base + flat) * (1.0 + additive) * multiplicative).max(0.0)
+    }
+}
+
+/// A resource with a current and a max value, like mana.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Pool {
+    pub current: f32,
+    pub max: f32,
+}
+
+impl Pool {
+    pub fn full(max: f32) -> Self {
+        Self { current: max, max }
+    }
+
+    /// Changes the max, keeping the current value within it.
+    pub fn set_max(&mut self, max: f32) {
+        self.max = max;
+        self.current = self.current.min(max);
+    }
+
+    /// Spends `amount` if there is enough of it.
+    pub fn spend(&mut self, amount: f32) -> bool {
+        if self.current < amount {
+            return false;
+        }
+        self.current -= amount;
+        true
+    }
+
+    pub fn restore(&mut self, amount: f32) {
+        self.current = (self.current + amount).min(self.max);
+    }
+}
+
+impl Stats {
+    /// Stats derived from attributes and level, with full pools.
+    pub fn derive(attributes: &Attributes, level: u16, modifiers: &Modifiers) -> Self {
+        let levels = level.saturating_sub(1) as f32;
+        let strength = attributes.strength as f32;
+        let vitality = attributes.vitality as f32;
+        let energy = attributes.energy as f32;
+
+        Self {
+            max_life: modifiers.apply(
+                StatKind::MaxLife,
+                vitality * LIFE_PER_VITALITY + levels * LIFE_PER_LEVEL,
+            ),
+            mana: Pool::full(modifiers.apply(
+                StatKind::MaxMana,
+                energy * MANA_PER_ENERGY + levels * MANA_PER_LEVEL,
+            )),
+            stamina: Pool::full(modifiers.apply(
+                StatKind::MaxStamina,
+                vitality * STAMINA_PER_VITALITY + levels * STAMINA_PER_LEVEL,
+            )),
+            defense: modifiers.apply(StatKind::Defense, strength * DEFENSE_PER_STRENGTH),
+            cast_rate: modifiers.apply(StatKind::CastRate, 0.0),
+            attack_rate: modifiers.apply(StatKind::AttackRate, 0.0),
+            crit_chance: modifiers
+                .apply(StatKind::CritChance, BASE_CRIT_CHANCE)
+                .min(1.0),
+            life_regen: modifiers.apply(StatKind::LifeRegen, vitality * LIFE_REGEN_PER_VITALITY),
+            mana_regen: modifiers.apply(StatKind::ManaRegen, energy * MANA_REGEN_PER_ENERGY),
+            stamina_regen: modifiers.apply(
+                StatKind::StaminaRegen,
+                BASE_STAMINA_REGEN + vitality * STAMINA_REGEN_PER_VITALITY,
+            ),
+            resistances: Resistances {
+                light: modifiers.apply(StatKind::LightningResistance, 0.0),
+                fire: modifiers.apply(StatKind::FireResistance, 0.0),
+                cold: modifiers.apply(StatKind::ColdResistance, 0.0),
+            },
+        }
+    }
+}
+
+fn spend_attribute_points(
+    mut player_q: Query<&mut Player>,
+    mut spend_events: EventReader<SpendAttributePoint>,
+) {
+    for event in spend_events.read() {
+        if let Ok(mut player) = player_q.get_mut(event.entity) {
+            if !player.attributes.spend(event.attribute) {
+                warn!(
+                    "can't spend a point on {:?}: no attribute points available",
+                    event.attribute
+                );
+            }
+        }
+    }
+}
+
+fn respec_attributes(
+    mut player_q: Query<&mut Player>,
+    mut respec_events: EventReader<RespecAttributes>,
+) {
+    for event in respec_events.read() {
+        if let Ok(mut player) = player_q.get_mut(event.entity) {
+            player.attributes.respec();
+        }
+    }
+}
+
+/// Recomputes stats from attributes, level and modifiers, keeping the current
+/// life, mana and stamina. Cheap enough to run every frame.
+fn derive_stats(mut player_q: Query<(&mut Player, &Modifiers, &mut Health)>) {
+    for (mut player, modifiers, mut health) in player_q.iter_mut() {
+        let derived = Stats::derive(&player.attributes, player.experience.level, modifiers);
+        let stats = &mut player.stats;
+
+        if health.max != derived.max_life {
+            health.max = derived.max_life;
+            health.current = health.current.min(health.max);
+        }
+        stats.max_life = derived.max_life;
+        stats.mana.set_max(derived.mana.max);
+        stats.stamina.set_max(derived.stamina.max);
+        stats.defense = derived.defense;
+        stats.cast_rate = derived.cast_rate;
+        stats.attack_rate = derived.attack_rate;
+        stats.crit_chance = derived.crit_chance;
+        stats.life_regen = derived.life_regen;
+        stats.mana_regen = derived.mana_regen;
+        stats.stamina_regen = derived.stamina_regen;
+        stats.resistances = derived.resistances;
+    }
+}
+
+/// Refills life, mana and stamina over time. Stamina doesn't refill while
+/// sprinting.
+fn regenerate(mut player_q: Query<(&mut Player, &mut Health)>, time: Res<Time>) {
+    let delta = time.delta_seconds();
+    for (mut player, mut health) in player_q.iter_mut() {
+        health.heal(player.stats.life_regen * delta);
+
+        let stats = &mut player.stats;
+        let mana_regen = stats.mana_regen;
+        stats.mana.restore(mana_regen * delta);
+        if player.movement_state != MovementState::Sprinting {
+            let stamina_regen = player.stats.stamina_regen;
+            player.stats.stamina.restore(stamina_regen * delta);
+        }
+    }
+}
+
+/// Drains stamina while sprinting. Running out exhausts the player, who can't
+/// sprint again until some stamina is back.
+fn sprint_stamina(mut player_q: Query<&mut Player>, time: Res<Time>) {
+    for mut player in player_q.iter_mut() {
+        if player.movement_state == MovementState::Sprinting {
+            let stamina = &mut player.stats.stamina;
+            stamina.current =
+                (stamina.current - SPRINT_STAMINA_COST * time.delta_seconds()).max(0.0);
+            if stamina.current <= 0.0 {
+                player.is_exhausted = true;
+            }
+        } else if player.is_exhausted
+            && player.stats.stamina.current >= player.stats.stamina.max * SPRINT_RECOVERY
+        {
+            player.is_exhausted = false;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn spent_attribute_points_are_refunded_on_respec() {
+        let mut attributes = Attributes {
+            available_points: 2,
+            ..Default::default()
+        };
+        assert!(attributes.spend(AttributeKind::Strength));
+        assert!(attributes.spend(AttributeKind::Strength));
+        assert!(!attributes.spend(AttributeKind::Energy));
+        assert_eq!(attributes.strength, 12);
+        assert_eq!(attributes.available_points, 0);
+
+        attributes.respec();
+        assert_eq!(attributes.strength, 10);
+        assert_eq!(attributes.available_points, 2);
+        assert!(attributes.spent.is_empty());
+    }
+}
@@ -0,0 +1,390 @@
+use std::time::Duration;
+
+use bevy::{
+    app::{App, Plugin, PostUpdate, Update},
+    ecs::{
+        component::Component,
+        entity::Entity,
+        event::{Event, EventReader, EventWriter},
+        query::With,
+        schedule::{common_conditions::in_state, IntoSystemConfigs, SystemSet},
+        system::{Commands, Query, Res},
+    },
+    hierarchy::{DespawnRecursiveExt, Parent},
+    math::{Quat, Vec2},
+    time::{Time, Timer, TimerMode},
+    transform::components::{GlobalTransform, Transform},
+};
+use bevy_rapier2d::{dynamics::RigidBody, plugin::RapierContext};
+
+use crate::GameState;
+
+pub struct ProjectilePlugin;
+
+impl Plugin for ProjectilePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<ProjectileHit>()
+            .add_event::<ProjectileEnded>()
+            .add_systems(
+                Update,
+                (move_projectiles, projectile_contacts)
+                    .chain()
+                    .in_set(ProjectileSet)
+                    .run_if(in_state(GameState::Playing)),
+            )
+            .add_systems(
+                PostUpdate,
+                despawn_ended_projectiles.run_if(in_state(GameState::Playing)),
+            );
+    }
+}
+
+/// Moves projectiles and resolves their contacts. Systems reading
+/// [`ProjectileHit`] or [`ProjectileEnded`] should run after it, while the
+/// ended projectiles are still around.
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ProjectileSet;
+
+/// Steers a projectile towards a target.
+#[derive(Clone, Copy, Debug)]
+pub struct Homing {
+    pub target: Entity,
+    /// In radians per second.
+    pub turn_rate: f32,
+}
+
+/// Something flying away from its caster and hitting what it touches.
+///
+/// The entity is expected to carry a sensor [`Collider`](bevy_rapier2d::geometry::Collider)
+/// able to detect fixed colliders. Rigid bodies are damageable targets, while
+/// colliders without one, or with a fixed one, are solid terrain.
+#[derive(Component, Debug)]
+pub struct Projectile {
+    /// The rigid body that launched the projectile, never hit by it.
+    pub caster: Entity,
+    pub origin: Vec2,
+    pub direction: Vec2,
+    pub speed: f32,
+    /// Change in speed per second, the projectile stopping at zero.
+    pub acceleration: f32,
+    /// Furthest distance from the origin before the projectile ends.
+    pub max_distance: f32,
+    pub lifetime: Option<Timer>,
+    /// Targets the projectile flies through before ending on the next one.
+    pub pierce: u32,
+    pub homing: Option<Homing>,
+    /// Times the projectile bounces off terrain before ending on it.
+    pub bounces: u32,
+    /// Targets and terrain already touched, each only counted once.
+    pub touched: Vec<Entity>,
+}
+
+impl Projectile {
+    pub fn new(
+        caster: Entity,
+        origin: Vec2,
+        direction: Vec2,
+        speed: f32,
+        max_distance: f32,
+    ) -> Self {
+        Self {
+            caster,
+            origin,
+            direction,
+            speed,
+            acceleration: 0.0,
+            max_distance,
+            lifetime: None,
+            pierce: 0,
+            homing: None,
+            bounces: 0,
+            touched: Vec::new(),
+        }
+    }
+
+    pub fn with_acceleration(mut self, acceleration: f32) -> Self {
+        self.acceleration = acceleration;
+        self
+    }
+
+    pub fn with_lifetime(mut self, lifetime: Duration) -> Self {
+        self.lifetime = Some(Timer::new(lifetime, TimerMode::Once));
+        self
+    }
+
+    pub fn with_pierce(mut self, pierce: u32) -> Self {
+        self.pierce = pierce;
+        self
+    }
+
+    pub fn with_homing(mut self, homing: Homing) -> Self {
+        self.homing = Some(homing);
+        self
+    }
+
+    pub fn with_bounces(mut self, bounces: u32) -> Self {
+        self.bounces = bounces;
+        self
+    }
+
+    /// Turns from `position` toward `target`, at most by the homing turn rate
+    /// over `delta` seconds.
+    fn steer(&mut self, position: Vec2, target: Vec2, delta: f32) {
+        let Some(homing) = self.homing else {
+            return;
+        };
+        let to_target = target - position;
+        if to_target == Vec2::ZERO {
+            return;
+        }
+        let angle = self.direction.angle_between(to_target);
+        let turn = angle.clamp(-homing.turn_rate * delta, homing.turn_rate * delta);
+        self.direction = Vec2::from_angle(turn).rotate(self.direction);
+    }
+
+    /// Counts a target hit, returning whether the projectile ends on it.
+    fn hit_target(&mut self) -> bool {
+        if self.pierce == 0 {
+            return true;
+        }
+        self.pierce -= 1;
+        false
+    }
+
+    /// Bounces off terrain facing `normal`, returning whether the projectile
+    /// ends on it instead.
+    fn hit_terrain(&mut self, normal: Vec2) -> bool {
+        if self.bounces == 0 {
+            return true;
+        }
+        self.bounces -= 1;
+        self.direction -= 2.0 * self.direction.dot(normal) * normal;
+        false
+    }
+
+    /// Whether the projectile ran out of lifetime or range, or stopped.
+    fn is_spent(&self, position: Vec2) -> bool {
+        self.lifetime.as_ref().is_some_and(Timer::finished)
+            || self.origin.distance(position) >= self.max_distance
+            || self.speed <= 0.0
+    }
+}
+
+/// Sent once per target a projectile touches.
+#[derive(Event, Debug)]
+pub struct ProjectileHit {
+    pub projectile: Entity,
+    /// The collider hit.
+    pub target: Entity,
+    pub position: Vec2,
+}
+
+/// Sent when a projectile ends, on a target, on terrain, or when it runs out
+/// of range or lifetime. The projectile is despawned in `PostUpdate`.
+#[derive(Event, Debug)]
+pub struct ProjectileEnded {
+    pub projectile: Entity,
+    pub position: Vec2,
+    /// The collider the projectile ended on, if any.
+    pub target: Option<Entity>,
+}
+
+fn move_projectiles(
+    mut projectile_q: Query<(&mut Projectile, &mut Transform)>,
+    target_q: Query<&GlobalTransform>,
+    time: Res<Time>,
+) {
+    let delta = time.delta_seconds();
+    for (mut projectile, mut transform) in projectile_q.iter_mut() {
+        if let Some(lifetime) = projectile.lifetime.as_mut() {
+            lifetime.tick(time.delta());
+        }
+
+        if let Some(target) = projectile
+            .homing
+            .and_then(|homing| target_q.get(homing.target).ok())
+        {
+            let position = transform.translation.truncate();
+            projectile.steer(position, target.translation().truncate(), delta);
+        }
+
+        projectile.speed = (projectile.speed + projectile.acceleration * delta).max(0.0);
+        transform.translation += (projectile.direction * projectile.speed * delta).extend(0.0);
+        transform.rotation = Quat::from_rotation_z(Vec2::X.angle_between(projectile.direction));
+    }
+}
+
+fn projectile_contacts(
+    rapier_context: Res<RapierContext>,
+    mut projectile_q: Query<(Entity, &mut Projectile, &Transform)>,
+    body_q: Query<&RigidBody>,
+    parent_q: Query<&Parent>,
+    transform_q: Query<&GlobalTransform, With<RigidBody>>,
+    mut hit_events: EventWriter<ProjectileHit>,
+    mut ended_events: EventWriter<ProjectileEnded>,
+) {
+    // the rigid body a collider belongs to, if any
+    let body_of = |collider: Entity| {
+        if body_q.contains(collider) {
+            Some(collider)
+        } else {
+            parent_q
+                .get(collider)
+                .ok()
+                .map(|parent| parent.get())
+                .filter(|parent| body_q.contains(*parent))
+        }
+    };
+
+    for (entity, mut projectile, transform) in projectile_q.iter_mut() {
+        let position = transform.translation.truncate();
+        let mut ended_on = None;
+
+        let contacts: Vec<Entity> = rapier_context
+            .intersection_pairs_with(entity)
+            .filter(|(_, _, intersecting)| *intersecting)
+            .map(|(collider1, collider2, _)| {
+                if collider1 == entity {
+                    collider2
+                } else {
+                    collider1
+                }
+            })
+            .collect();
+        for collider in contacts {
+            let body = body_of(collider);
+            if body == Some(projectile.caster) || projectile.touched.contains(&collider) {
+                continue;
+            }
+            projectile.touched.push(collider);
+
+            let is_terrain = body.map_or(true, |body| {
+                matches!(body_q.get(body), Ok(RigidBody::Fixed))
+            });
+            let ended = if is_terrain {
+                // without contact normals, bounce away from the collider's center
+                let normal = body
+                    .and_then(|body| transform_q.get(body).ok())
+                    .map(|transform| {
+                        (position - transform.translation().truncate()).normalize_or_zero()
+                    })
+                    .filter(|normal| *normal != Vec2::ZERO)
+                    .unwrap_or(-projectile.direction);
+                projectile.hit_terrain(normal)
+            } else {
+                hit_events.send(ProjectileHit {
+                    projectile: entity,
+                    target: collider,
+                    position,
+                });
+                projectile.hit_target()
+            };
+            if ended {
+                ended_on = Some(collider);
+                break;
+            }
+        }
+
+        if ended_on.is_some() || projectile.is_spent(position) {
+            ended_events.send(ProjectileEnded {
+                projectile: entity,
+                position,
+                target: ended_on,
+            });
+        }
+    }
+}
+
+fn despawn_ended_projectiles(
+    mut commands: Commands,
+    mut ended_events: EventReader<ProjectileEnded>,
+) {
+    for event in ended_events.read() {
+        if let Some(entity) = commands.get_entity(event.projectile) {
+            entity.despawn_recursive();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::FRAC_PI_2;
+
+    use super::*;
+
+    fn launched(direction: Vec2) -> Projectile {
+        Projectile::new(Entity::PLACEHOLDER, Vec2::ZERO, direction, 100.0, 50.0)
+    }
+
+    #[test]
+    fn pierces_its_count_of_targets() {
+        let mut projectile = launched(Vec2::X).with_pierce(2);
+        assert!(!projectile.hit_target());
+        assert!(!projectile.hit_target());
+        assert!(projectile.hit_target());
+    }
+
+    #[test]
+    fn bounces_reflect_off_terrain() {
+        let mut projectile = launched(Vec2::new(1.0, -1.0).normalize()).with_bounces(2);
+        assert!(!projectile.hit_terrain(Vec2::Y));
+        assert!(projectile
+            .direction
+            .abs_diff_eq(Vec2::new(1.0, 1.0).normalize(), 1e-6));
+        assert!(!projectile.hit_terrain(Vec2::NEG_X));
+        assert!(projectile
+            .direction
+            .abs_diff_eq(Vec2::new(-1.0, 1.0).normalize(), 1e-6));
+        assert!(projectile.hit_terrain(Vec2::NEG_Y));
+    }
+
+    #[test]
+    fn ends_out_of_range_lifetime_or_speed() {
+        let projectile = launched(Vec2::X);
+        assert!(!projectile.is_spent(Vec2::new(49.0, 0.0)));
+        assert!(projectile.is_spent(Vec2::new(50.0, 0.0)));
+
+        let mut projectile = projectile.with_lifetime(Duration::from_secs(1));
+        projectile
+            .lifetime
+            .as_mut()
+            .unwrap()
+            .tick(Duration::from_millis(500));
+        assert!(!projectile.is_spent(Vec2::ZERO));
+        projectile
+            .lifetime
+            .as_mut()
+            .unwrap()
+            .tick(Duration::from_millis(500));
+        assert!(projectile.is_spent(Vec2::ZERO));
+
+        let stopped = Projectile {
+            speed: 0.0,
+            ..launched(Vec2::X)
+        };
+        assert!(stopped.is_spent(Vec2::ZERO));
+    }
+
+    #[test]
+    fn homing_turns_toward_the_target_at_its_rate() {
+        let mut projectile = launched(Vec2::X).with_homing(Homing {
+            target: Entity::PLACEHOLDER,
+            turn_rate: 1.0,
+        });
+        let target = Vec2::new(0.0, 10.0);
+        projectile.steer(Vec2::ZERO, target, 0.5);
+        assert!(projectile
+            .direction
+            .abs_diff_eq(Vec2::from_angle(0.5), 1e-6));
+
+        // never turns past the target
+        projectile.steer(Vec2::ZERO, target, 10.0);
+        assert!(projectile
+            .direction
+            .abs_diff_eq(Vec2::from_angle(FRAC_PI_2), 1e-6));
+
+        let mut straight = launched(Vec2::X);
+        straight.steer(Vec2::ZERO, target, 0.5);
+        assert_eq!(straight.direction, Vec2::X);
+    }
+}
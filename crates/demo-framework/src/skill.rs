@@ -0,0 +1,469 @@
+use std::{collections::HashMap, time::Duration};
+
+use bevy::{
+    app::{App, Plugin, Update},
+    asset::{Asset, Assets},
+    ecs::{
+        component::Component,
+        entity::Entity,
+        event::{Event, EventReader},
+        schedule::{common_conditions::in_state, IntoSystemConfigs},
+        system::{Query, Res, SystemParam},
+    },
+    log::warn,
+    math::Vec2,
+    reflect::TypePath,
+    time::{Timer, TimerMode},
+};
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::{animation::AnimationAction, loading::DataAssets, player::Player, GameState};
+
+pub struct SkillPlugin;
+
+impl Plugin for SkillPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<SpendSkillPoint>()
+            .add_event::<RespecSkills>()
+            .add_systems(
+                Update,
+                (spend_skill_points, respec_skills).run_if(in_state(GameState::Playing)),
+            );
+    }
+}
+
+/// Identifies a skill definition, e.g. `"ice_bolt"`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Deserialize)]
+#[serde(transparent)]
+pub struct SkillId(pub String);
+
+impl From<&str> for SkillId {
+    fn from(id: &str) -> Self {
+        Self(id.to_string())
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+pub enum Element {
+    #[default]
+    Physical,
+    Cold,
+    Fire,
+    Lightning,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+pub enum CostType {
+    #[default]
+    None,
+    Life,
+    Mana,
+    Stamina,
+}
+
+#[derive(Clone, Copy, Debug, Default, Deserialize)]
+pub struct Cost {
+    pub cost_type: CostType,
+    pub value: f32,
+}
+
+impl Cost {
+    pub fn new(cost_type: CostType, value: f32) -> Self {
+        Self { cost_type, value }
+    }
+}
+
+/// Damage in a radius around a point.
+#[derive(Clone, Copy, Debug, Deserialize)]
+pub struct Explosion {
+    pub radius: f32,
+    /// Fraction of the damage lost at the edge of the radius.
+    #[serde(default)]
+    pub falloff: f32,
+}
+
+/// How a skill reaches its targets once cast.
+#[derive(Clone, Copy, Debug, Deserialize)]
+pub enum SkillBehaviour {
+    /// A hit right in front of the caster.
+    Melee,
+    /// A missile flying towards the cursor.
+    Projectile {
+        speed: f32,
+        /// Change in speed per second.
+        #[serde(default)]
+        acceleration: f32,
+        /// Targets flown through before stopping on the next one.
+        #[serde(default)]
+        pierce: u32,
+        /// Times the missile bounces off terrain.
+        #[serde(default)]
+        bounces: u32,
+        /// Fraction of the damage lost by the end of the range.
+        #[serde(default)]
+        falloff: f32,
+        /// Explodes where the missile lands, at the cursor or the end of its range.
+        #[serde(default)]
+        explosion: Option<Explosion>,
+        /// Seconds before the missile ends, however far it got.
+        #[serde(default)]
+        lifetime: Option<f32>,
+        /// Radians per second the missile turns toward the target closest to
+        /// where it was aimed.
+        #[serde(default)]
+        homing: Option<f32>,
+    },
+    /// A ring of missiles expanding from the caster up to the skill's range.
+    Nova {
+        speed: f32,
+        shards: u32,
+        /// Fraction of the damage lost by the end of the range.
+        #[serde(default)]
+        falloff: f32,
+    },
+    /// An explosion at the target location.
+    Area(Explosion),
+}
+
+/// The visual effect spawned by a skill.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+pub enum SkillVfx {
+    IceMissile,
+    IceShard,
+}
+
+/// Damage left after `distance` for effects weakening towards `reach`.
+pub fn falloff_damage(damage: f32, distance: f32, reach: f32, falloff: f32) -> f32 {
+    if reach <= 0.0 {
+        return damage;
+    }
+    damage * (1.0 - falloff * (distance / reach).clamp(0.0, 1.0))
+}
+
+/// How much a skill grows with each level past the first.
+#[derive(Clone, Copy, Debug, Default, Deserialize)]
+pub struct SkillScaling {
+    #[serde(default)]
+    pub damage: f32,
+    #[serde(default)]
+    pub cost: f32,
+    #[serde(default)]
+    pub range: f32,
+}
+
+/// A skill that must be learned up to `level` before another one.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+pub struct SkillRequirement {
+    pub skill: SkillId,
+    pub level: u16,
+}
+
+fn default_max_level() -> u16 {
+    20
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct SkillDefinition {
+    pub name: String,
+    #[serde(default)]
+    pub element: Element,
+    #[serde(default)]
+    pub cost: Cost,
+    pub damage: f32,
+    pub range: f32,
+    pub behaviour: SkillBehaviour,
+    #[serde(default)]
+    pub vfx: Option<SkillVfx>,
+    /// Played once by the caster when the skill goes off, or while casting
+    /// skills with a cast time.
+    #[serde(default)]
+    pub animation: Option<AnimationAction>,
+    /// In seconds.
+    #[serde(default)]
+    pub cooldown: f32,
+    /// Seconds spent casting before the skill goes off, zero for instant skills.
+    #[serde(default)]
+    pub cast_time: f32,
+    #[serde(default)]
+    pub scaling: SkillScaling,
+    #[serde(default = "default_max_level")]
+    pub max_level: u16,
+    /// Row of the skill tree the skill is shown in.
+    #[serde(default)]
+    pub tier: u16,
+    #[serde(default)]
+    pub requires: Vec<SkillRequirement>,
+}
+
+impl SkillDefinition {
+    pub fn damage(&self, level: u16) -> f32 {
+        self.damage + self.scaling.damage * level.saturating_sub(1) as f32
+    }
+
+    pub fn cost(&self, level: u16) -> Cost {
+        Cost::new(
+            self.cost.cost_type,
+            self.cost.value + self.scaling.cost * level.saturating_sub(1) as f32,
+        )
+    }
+
+    pub fn range(&self, level: u16) -> f32 {
+        self.range + self.scaling.range * level.saturating_sub(1) as f32
+    }
+
+    /// Whether attack rate rather than cast rate speeds the skill up.
+    pub fn is_attack(&self) -> bool {
+        matches!(self.behaviour, SkillBehaviour::Melee)
+    }
+}
+
+/// Divides durations by this to apply a cast or attack rate, e.g. `0.25` for
+/// 25% faster.
+pub fn rate_multiplier(rate: f32) -> f32 {
+    (1.0 + rate).max(0.1)
+}
+
+/// Cooldowns of the skills a character used, and a global cooldown started by
+/// every skill.
+#[derive(Clone, Debug, Default)]
+pub struct SkillCooldowns {
+    cooldowns: HashMap<SkillId, Timer>,
+    global: Option<Timer>,
+    /// Zero to only use per-skill cooldowns.
+    pub global_cooldown: Duration,
+}
+
+impl SkillCooldowns {
+    pub fn new(global_cooldown: Duration) -> Self {
+        Self {
+            global_cooldown,
+            ..Default::default()
+        }
+    }
+
+    /// Puts a skill and the global cooldown on cooldown, both shortened by
+    /// `rate` (see [`rate_multiplier`]).
+    pub fn start(&mut self, id: &SkillId, cooldown: Duration, rate: f32) {
+        let multiplier = rate_multiplier(rate);
+        if !cooldown.is_zero() {
+            let timer = Timer::new(cooldown.div_f32(multiplier), TimerMode::Once);
+            self.cooldowns.insert(id.clone(), timer);
+        }
+        if !self.global_cooldown.is_zero() {
+            let timer = Timer::new(self.global_cooldown.div_f32(multiplier), TimerMode::Once);
+            self.global = Some(timer);
+        }
+    }
+
+    pub fn tick(&mut self, delta: Duration) {
+        for timer in self.cooldowns.values_mut() {
+            timer.tick(delta);
+        }
+        self.cooldowns.retain(|_, timer| !timer.finished());
+        if let Some(global) = self.global.as_mut() {
+            if global.tick(delta).finished() {
+                self.global = None;
+            }
+        }
+    }
+
+    pub fn is_ready(&self, id: &SkillId) -> bool {
+        self.global.is_none() && !self.cooldowns.contains_key(id)
+    }
+
+    /// Fraction of the cooldown left before the skill can be used again, 0
+    /// once ready.
+    pub fn remaining_fraction(&self, id: &SkillId) -> f32 {
+        let remaining = |timer: &Timer| 1.0 - timer.fraction();
+        let skill = self.cooldowns.get(id).map_or(0.0, remaining);
+        let global = self.global.as_ref().map_or(0.0, remaining);
+        skill.max(global)
+    }
+}
+
+/// A skill being cast, going off at `target` once the timer finishes.
+#[derive(Clone, Debug)]
+pub struct SkillCast {
+    pub skill: SkillId,
+    pub target: Vec2,
+    pub timer: Timer,
+}
+
+impl SkillCast {
+    /// Between 0 when the cast starts and 1 when it goes off.
+    pub fn progress(&self) -> f32 {
+        self.timer.fraction()
+    }
+}
+
+/// Every skill in the game, loaded from `data/*.skills.ron`.
+#[derive(Asset, TypePath, Debug, Deserialize)]
+pub struct SkillBook {
+    pub skills: HashMap<SkillId, SkillDefinition>,
+}
+
+/// Looks skill definitions up by id.
+#[derive(SystemParam)]
+pub struct SkillDefinitions<'w> {
+    data_assets: Res<'w, DataAssets>,
+    skill_books: Res<'w, Assets<SkillBook>>,
+}
+
+impl SkillDefinitions<'_> {
+    pub fn get(&self, id: &SkillId) -> Option<&SkillDefinition> {
+        self.skill_books
+            .get(&self.data_assets.skills)
+            .and_then(|book| book.skills.get(id))
+    }
+
+    /// Every skill, ordered by tier and name.
+    pub fn all(&self) -> Vec<(&SkillId, &SkillDefinition)> {
+        let mut skills: Vec<_> = self
+            .skill_books
+            .get(&self.data_assets.skills)
+            .map(|book| book.skills.iter().collect())
+            .unwrap_or_default();
+        skills.sort_by(|(_, a), (_, b)| a.tier.cmp(&b.tier).then_with(|| a.name.cmp(&b.name)));
+        skills
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct LearnedSkill {
+    pub level: u16,
+    /// Skill points spent on the skill, given back on respec.
+    pub points: u16,
+}
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum SkillPointError {
+    #[error("no skill points available")]
+    NoPoints,
+    #[error("skill is already at its max level")]
+    MaxLevel,
+    #[error("requires {} at level {}", .0.skill.0, .0.level)]
+    MissingRequirement(SkillRequirement),
+}
+
+/// The skills a character has learned.
+#[derive(Component, Debug, Clone)]
+pub struct Skills {
+    pub skills: HashMap<SkillId, LearnedSkill>,
+    pub available_points: u16, // available to spend on upgrading
+}
+
+impl Default for Skills {
+    fn default() -> Self {
+        let mut skills = HashMap::new();
+        for id in ["attack", "ice_bolt"] {
+            skills.insert(
+                SkillId::from(id),
+                LearnedSkill {
+                    level: 1,
+                    points: 0,
+                },
+            );
+        }
+
+        Self {
+            skills,
+            available_points: 0,
+        }
+    }
+}
+
+impl Skills {
+    pub fn level(&self, id: &SkillId) -> Option<u16> {
+        self.skills.get(id).map(|skill| skill.level)
+    }
+
+    /// Whether a point can be spent on the skill defined by `definition`.
+    pub fn can_spend(
+        &self,
+        id: &SkillId,
+        definition: &SkillDefinition,
+    ) -> Result<(), SkillPointError> {
+        if self.available_points == 0 {
+            return Err(SkillPointError::NoPoints);
+        }
+        if self.level(id).unwrap_or(0) >= definition.max_level {
+            return Err(SkillPointError::MaxLevel);
+        }
+        if let Some(requirement) = definition
+            .requires
+            .iter()
+            .find(|requirement| self.level(&requirement.skill).unwrap_or(0) < requirement.level)
+        {
+            return Err(SkillPointError::MissingRequirement(requirement.clone()));
+        }
+        Ok(())
+    }
+
+    /// Spends a point to learn the skill or raise its level.
+    pub fn spend(
+        &mut self,
+        id: &SkillId,
+        definition: &SkillDefinition,
+    ) -> Result<(), SkillPointError> {
+        self.can_spend(id, definition)?;
+        self.available_points -= 1;
+        let skill = self.skills.entry(id.clone()).or_insert(LearnedSkill {
+            level: 0,
+            points: 0,
+        });
+        skill.level += 1;
+        skill.points += 1;
+        Ok(())
+    }
+
+    /// Takes back every spent point, forgetting skills that weren't known
+    /// from the start.
+    pub fn respec(&mut self) {
+        for skill in self.skills.values_mut() {
+            self.available_points += skill.points;
+            skill.level -= skill.points;
+            skill.points = 0;
+        }
+        self.skills.retain(|_, skill| skill.level > 0);
+    }
+}
+
+/// Asks to spend one of the player's skill points on `skill`.
+#[derive(Event, Clone, Debug)]
+pub struct SpendSkillPoint {
+    pub entity: Entity,
+    pub skill: SkillId,
+}
+
+/// Asks to refund every skill point the player spent.
+#[derive(Event, Clone, Copy, Debug)]
+pub struct RespecSkills {
+    pub entity: Entity,
+}
+
+fn spend_skill_points(
+    mut player_q: Query<&mut Player>,
+    definitions: SkillDefinitions,
+    mut spend_events: EventReader<SpendSkillPoint>,
+) {
+    for event in spend_events.read() {
+        let (Ok(mut player), Some(definition)) = (
+            player_q.get_mut(event.entity),
+            definitions.get(&event.skill),
+        ) else {
+            continue;
+        };
+        if let Err(err) = player.skills.spend(&event.skill, definition) {
+            warn!("can't spend a point on {}: {err}", event.skill.0);
+        }
+    }
+}
+
+fn respec_skills(mut player_q: Query<&mut Player>, mut respec_events: EventReader<RespecSkills>) {
+    for event in respec_events.read() {
+        if let Ok(mut player) = player_q.get_mut(event.entity) {
+            player.skills.respec();
+        }
+    }
+}
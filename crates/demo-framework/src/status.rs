@@ -0,0 +1,432 @@
+use std::time::Duration;
+
+use bevy::{
+    app::{App, Plugin, Update},
+    ecs::{
+        component::Component,
+        entity::Entity,
+        event::{EventReader, EventWriter},
+        query::{Has, Without},
+        schedule::{common_conditions::in_state, IntoSystemConfigs},
+        system::{Commands, Query, Res},
+    },
+    render::color::Color,
+    sprite::Sprite,
+    time::{Time, Timer, TimerMode},
+};
+use bevy_trickfilm::animation::AnimationPlayer2D;
+
+use crate::{
+    animation::AnimationState,
+    damage::{DamageEvent, DamageFlags, DamageSet, Health, Invulnerable},
+    skill::Element,
+    GameState,
+};
+
+const CHILL_DURATION: Duration = Duration::from_secs(2);
+/// Movement and animation speed lost while chilled.
+const CHILL_SLOW: f32 = 0.3;
+const FREEZE_DURATION: Duration = Duration::from_millis(1000);
+/// Cold hits dealing at least this fraction of the target's max health freeze it.
+const FREEZE_THRESHOLD: f32 = 0.25;
+const BURN_DURATION: Duration = Duration::from_secs(3);
+/// Fraction of a fire hit dealt again every second while burning.
+const BURN_DAMAGE: f32 = 0.2;
+const MAX_BURN_STACKS: u32 = 5;
+const SHOCK_DURATION: Duration = Duration::from_secs(2);
+/// Extra damage taken while shocked.
+const SHOCK_AMPLIFY: f32 = 0.2;
+
+pub struct StatusPlugin;
+
+impl Plugin for StatusPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (
+                apply_status_effects.after(DamageSet),
+                (burn_damage.before(DamageSet), tick_status_effects).chain(),
+                (status_tint, status_animation_speed).after(tick_status_effects),
+            )
+                .run_if(in_state(GameState::Playing)),
+        );
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StatusKind {
+    /// Slows movement and animations. Reapplying refreshes the duration.
+    Chill,
+    /// Stops the target entirely. Reapplying never shortens it.
+    Freeze,
+    /// Deals fire damage over time. Stacks up to [`MAX_BURN_STACKS`], each
+    /// new stack refreshing the duration.
+    Burn,
+    /// Amplifies all damage taken. Reapplying refreshes the duration.
+    Shock,
+}
+
+#[derive(Clone, Debug)]
+pub struct StatusEffect {
+    pub kind: StatusKind,
+    pub timer: Timer,
+    /// Damage per second for burns, unused by other effects.
+    pub magnitude: f32,
+    pub stacks: u32,
+    pub source: Option<Entity>,
+}
+
+/// Timed effects on an entity. Timers follow virtual time, so effects hold
+/// while the game is paused.
+#[derive(Component, Clone, Debug, Default)]
+pub struct StatusEffects {
+    pub effects: Vec<StatusEffect>,
+}
+
+impl StatusEffects {
+    pub fn get(&self, kind: StatusKind) -> Option<&StatusEffect> {
+        self.effects.iter().find(|effect| effect.kind == kind)
+    }
+
+    pub fn has(&self, kind: StatusKind) -> bool {
+        self.get(kind).is_some()
+    }
+
+    pub fn apply(
+        &mut self,
+        kind: StatusKind,
+        duration: Duration,
+        magnitude: f32,
+        source: Option<Entity>,
+    ) {
+        let Some(effect) = self.effects.iter_mut().find(|effect| effect.kind == kind) else {
+            self.effects.push(StatusEffect {
+                kind,
+                timer: Timer::new(duration, TimerMode::Once),
+                magnitude,
+                stacks: 1,
+                source,
+            });
+            return;
+        };
+
+        effect.source = source.or(effect.source);
+        match kind {
+            StatusKind::Freeze => {
+                if effect.timer.remaining() < duration {
+                    effect.timer = Timer::new(duration, TimerMode::Once);
+                }
+            }
+            StatusKind::Burn => {
+                // every stack burns for as much as the strongest one
+                effect.stacks = (effect.stacks + 1).min(MAX_BURN_STACKS);
+                effect.magnitude = effect.magnitude.max(magnitude);
+                effect.timer = Timer::new(duration, TimerMode::Once);
+            }
+            StatusKind::Chill | StatusKind::Shock => {
+                effect.timer = Timer::new(duration, TimerMode::Once);
+            }
+        }
+    }
+
+    /// Applies what an elemental hit of `amount` does to a target with
+    /// `max_health`. Cold chills, and freezes on big enough hits.
+    pub fn apply_hit(
+        &mut self,
+        element: Element,
+        amount: f32,
+        max_health: f32,
+        source: Option<Entity>,
+    ) {
+        match element {
+            Element::Physical => {}
+            Element::Cold => {
+                self.apply(StatusKind::Chill, CHILL_DURATION, 0.0, source);
+                if amount >= max_health * FREEZE_THRESHOLD {
+                    self.apply(StatusKind::Freeze, FREEZE_DURATION, 0.0, source);
+                }
+            }
+            Element::Fire => {
+                self.apply(
+                    StatusKind::Burn,
+                    BURN_DURATION,
+                    amount * BURN_DAMAGE,
+                    source,
+                );
+            }
+            Element::Lightning => {
+                self.apply(StatusKind::Shock, SHOCK_DURATION, 0.0, source);
+            }
+        }
+    }
+
+    /// Advances every effect, removing the ones that ran out.
+    pub fn tick(&mut self, delta: Duration) {
+        for effect in self.effects.iter_mut() {
+            effect.timer.tick(delta);
+        }
+        self.effects.retain(|effect| !effect.timer.finished());
+    }
+
+    pub fn is_frozen(&self) -> bool {
+        self.has(StatusKind::Freeze)
+    }
+
+    /// Multiplier for movement and animation speed.
+    pub fn speed_multiplier(&self) -> f32 {
+        if self.is_frozen() {
+            0.0
+        } else if self.has(StatusKind::Chill) {
+            1.0 - CHILL_SLOW
+        } else {
+            1.0
+        }
+    }
+
+    /// Multiplier for damage taken.
+    pub fn damage_taken_multiplier(&self) -> f32 {
+        if self.has(StatusKind::Shock) {
+            1.0 + SHOCK_AMPLIFY
+        } else {
+            1.0
+        }
+    }
+
+    /// Fire damage per second from all burn stacks.
+    pub fn burn_per_second(&self) -> f32 {
+        self.get(StatusKind::Burn)
+            .map_or(0.0, |burn| burn.magnitude * burn.stacks as f32)
+    }
+
+    /// Fire damage dealt by the burns over the next `delta`, up to when they
+    /// run out.
+    pub fn burn_damage(&self, delta: Duration) -> f32 {
+        self.get(StatusKind::Burn).map_or(0.0, |burn| {
+            self.burn_per_second() * delta.min(burn.timer.remaining()).as_secs_f32()
+        })
+    }
+
+    /// Sprite tint showing the most important effect.
+    pub fn tint(&self) -> Color {
+        if self.is_frozen() {
+            Color::rgb(0.55, 0.8, 1.0)
+        } else if self.has(StatusKind::Burn) {
+            Color::rgb(1.0, 0.6, 0.45)
+        } else if self.has(StatusKind::Shock) {
+            Color::rgb(1.0, 1.0, 0.55)
+        } else if self.has(StatusKind::Chill) {
+            Color::rgb(0.75, 0.9, 1.0)
+        } else {
+            Color::WHITE
+        }
+    }
+}
+
+/// Elemental hits chill, freeze, burn or shock what they damage, unless the
+/// target is [`Invulnerable`].
+fn apply_status_effects(
+    mut commands: Commands,
+    mut target_q: Query<(&Health, Option<&mut StatusEffects>, Has<Invulnerable>)>,
+    mut damage_events: EventReader<DamageEvent>,
+) {
+    for event in damage_events.read() {
+        if event.flags.contains(DamageFlags::NO_STATUS) {
+            continue;
+        }
+        let Ok((health, effects, invulnerable)) = target_q.get_mut(event.target) else {
+            continue;
+        };
+        if health.is_dead() || invulnerable {
+            continue;
+        }
+
+        let mut new_effects = StatusEffects::default();
+        let effects = match effects {
+            Some(effects) => effects.into_inner(),
+            None => &mut new_effects,
+        };
+        effects.apply_hit(event.element, event.amount, health.max, event.source);
+
+        if !new_effects.effects.is_empty() {
+            commands.entity(event.target).insert(new_effects);
+        }
+    }
+}
+
+fn tick_status_effects(mut effects_q: Query<&mut StatusEffects>, time: Res<Time>) {
+    for mut effects in effects_q.iter_mut() {
+        effects.tick(time.delta());
+    }
+}
+
+fn burn_damage(
+    effects_q: Query<(Entity, &StatusEffects)>,
+    time: Res<Time>,
+    mut damage_events: EventWriter<DamageEvent>,
+) {
+    for (entity, effects) in &effects_q {
+        let Some(burn) = effects.get(StatusKind::Burn) else {
+            continue;
+        };
+        damage_events.send(DamageEvent {
+            source: burn.source,
+            target: entity,
+            element: Element::Fire,
+            amount: effects.burn_damage(time.delta()),
+            crit_chance: 0.0,
+            flags: DamageFlags::NO_CRIT | DamageFlags::NO_STATUS,
+        });
+    }
+}
+
+fn status_tint(mut effects_q: Query<(&StatusEffects, &mut Sprite)>) {
+    for (effects, mut sprite) in effects_q.iter_mut() {
+        let tint = effects.tint();
+        if sprite.color != tint {
+            sprite.color = tint;
+        }
+    }
+}
+
+/// Entities with an [`AnimationState`] get their speed from it instead.
+fn status_animation_speed(
+    mut effects_q: Query<(&StatusEffects, &mut AnimationPlayer2D), Without<AnimationState>>,
+) {
+    for (effects, mut animator) in effects_q.iter_mut() {
+        animator.set_speed(effects.speed_multiplier());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::{event::Events, system::RunSystemOnce, world::World};
+
+    use super::*;
+
+    fn cold_hit(target: Entity) -> DamageEvent {
+        DamageEvent {
+            source: None,
+            target,
+            element: Element::Cold,
+            amount: 5.0,
+            crit_chance: 0.0,
+            flags: DamageFlags::NONE,
+        }
+    }
+
+    #[test]
+    fn invulnerable_targets_get_no_status_effects() {
+        let mut world = World::new();
+        world.init_resource::<Events<DamageEvent>>();
+        let invulnerable = world
+            .spawn((
+                Health::new(10.0),
+                Invulnerable(Timer::from_seconds(1.0, TimerMode::Once)),
+            ))
+            .id();
+        let vulnerable = world.spawn(Health::new(10.0)).id();
+        world.send_event(cold_hit(invulnerable));
+        world.send_event(cold_hit(vulnerable));
+
+        world.run_system_once(apply_status_effects);
+
+        assert!(world.get::<StatusEffects>(invulnerable).is_none());
+        assert!(world
+            .get::<StatusEffects>(vulnerable)
+            .is_some_and(StatusEffects::is_frozen));
+    }
+
+    fn remaining(effects: &StatusEffects, kind: StatusKind) -> Duration {
+        effects.get(kind).unwrap().timer.remaining()
+    }
+
+    #[test]
+    fn chill_and_shock_refresh_their_duration() {
+        for kind in [StatusKind::Chill, StatusKind::Shock] {
+            let mut effects = StatusEffects::default();
+            effects.apply(kind, Duration::from_secs(2), 0.0, None);
+            effects.tick(Duration::from_millis(1500));
+            effects.apply(kind, Duration::from_secs(2), 0.0, None);
+            assert_eq!(remaining(&effects, kind), Duration::from_secs(2));
+            assert_eq!(effects.get(kind).unwrap().stacks, 1);
+        }
+    }
+
+    #[test]
+    fn freeze_is_never_shortened() {
+        let mut effects = StatusEffects::default();
+        effects.apply(StatusKind::Freeze, Duration::from_secs(1), 0.0, None);
+        effects.apply(StatusKind::Freeze, Duration::from_millis(500), 0.0, None);
+        assert_eq!(
+            remaining(&effects, StatusKind::Freeze),
+            Duration::from_secs(1)
+        );
+
+        effects.tick(Duration::from_millis(800));
+        effects.apply(StatusKind::Freeze, Duration::from_secs(1), 0.0, None);
+        assert_eq!(
+            remaining(&effects, StatusKind::Freeze),
+            Duration::from_secs(1)
+        );
+    }
+
+    #[test]
+    fn burns_stack_up_to_the_max() {
+        let mut effects = StatusEffects::default();
+        for magnitude in 1..=MAX_BURN_STACKS + 2 {
+            effects.tick(Duration::from_millis(500));
+            effects.apply(StatusKind::Burn, BURN_DURATION, magnitude as f32, None);
+        }
+        let burn = effects.get(StatusKind::Burn).unwrap();
+        assert_eq!(burn.stacks, MAX_BURN_STACKS);
+        // the strongest stack sets the damage, the last one the duration
+        assert_eq!(burn.magnitude, (MAX_BURN_STACKS + 2) as f32);
+        assert_eq!(burn.timer.remaining(), BURN_DURATION);
+        assert_eq!(
+            effects.burn_per_second(),
+            ((MAX_BURN_STACKS + 2) * MAX_BURN_STACKS) as f32
+        );
+    }
+
+    #[test]
+    fn big_cold_hits_freeze_chilled_targets() {
+        let mut effects = StatusEffects::default();
+        effects.apply_hit(Element::Cold, 10.0, 100.0, None);
+        assert!(effects.has(StatusKind::Chill));
+        assert!(!effects.is_frozen());
+        assert_eq!(effects.speed_multiplier(), 1.0 - CHILL_SLOW);
+
+        effects.apply_hit(Element::Cold, 100.0 * FREEZE_THRESHOLD, 100.0, None);
+        assert!(effects.is_frozen());
+        assert_eq!(effects.speed_multiplier(), 0.0);
+
+        // chill outlasts the freeze
+        effects.tick(FREEZE_DURATION);
+        assert!(!effects.is_frozen());
+        assert!(effects.has(StatusKind::Chill));
+    }
+
+    #[test]
+    fn burns_deal_their_whole_damage_over_their_duration() {
+        let mut effects = StatusEffects::default();
+        effects.apply_hit(Element::Fire, 10.0, 100.0, None);
+        let frame = Duration::from_secs_f32(1.0 / 60.0);
+        let mut total = 0.0;
+        while effects.has(StatusKind::Burn) {
+            total += effects.burn_damage(frame);
+            effects.tick(frame);
+        }
+        let expected = 10.0 * BURN_DAMAGE * BURN_DURATION.as_secs_f32();
+        assert!((total - expected).abs() < 1e-3, "{total} != {expected}");
+    }
+
+    #[test]
+    fn shock_amplifies_damage_taken() {
+        let mut effects = StatusEffects::default();
+        assert_eq!(effects.damage_taken_multiplier(), 1.0);
+        effects.apply_hit(Element::Lightning, 1.0, 100.0, None);
+        assert_eq!(effects.damage_taken_multiplier(), 1.0 + SHOCK_AMPLIFY);
+        effects.tick(SHOCK_DURATION);
+        assert_eq!(effects.damage_taken_multiplier(), 1.0);
+    }
+}
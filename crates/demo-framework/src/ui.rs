@@ -1,8 +1,14 @@
 use bevy::app::{App, Plugin};
 
+mod belt;
+mod character_creation;
 mod diagnostics;
+mod game_over;
+mod hotbar;
 mod hud;
+mod inventory;
 mod menu;
+mod skill_tree;
 
 pub struct UiPlugin;
 
@@ -10,8 +16,14 @@ impl Plugin for UiPlugin {
     fn build(&self, app: &mut App) {
         app.add_plugins((
             menu::MenuPlugin,
+            character_creation::CharacterCreationPlugin,
             diagnostics::DiagnosticsPlugin,
             hud::HudPlugin,
+            hotbar::HotbarPlugin,
+            belt::BeltPlugin,
+            inventory::InventoryUiPlugin,
+            skill_tree::SkillTreePlugin,
+            game_over::GameOverPlugin,
         ));
     }
 }
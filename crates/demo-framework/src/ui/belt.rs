@@ -0,0 +1,192 @@
+use bevy::{
+    app::{App, Plugin, Update},
+    core::Name,
+    ecs::{
+        component::Component,
+        query::With,
+        schedule::{
+            common_conditions::{in_state, run_once},
+            IntoSystemConfigs, OnEnter,
+        },
+        system::{Commands, Query, Res},
+    },
+    hierarchy::BuildChildren,
+    render::{color::Color, view::RenderLayers},
+    text::{Text, TextStyle},
+    ui::{
+        node_bundles::{NodeBundle, TextBundle},
+        AlignItems, BackgroundColor, JustifyContent, PositionType, Style, UiRect, Val,
+    },
+};
+
+use crate::{
+    item::{
+        consumable::{cooldown_group, ConsumableCooldowns, PotionBelt},
+        Inventory, ItemDefinitions,
+    },
+    loading::TextureAssets,
+    player::{
+        input::{key_label, BELT_KEYS},
+        Player,
+    },
+    GameState,
+};
+
+const SLOT_SIZE: f32 = 40.0;
+const EMPTY_SLOT_COLOR: Color = Color::rgba(0.275, 0.204, 0.220, 0.9);
+/// Tint of bound items the player has run out of.
+const MISSING_ITEM_ALPHA: f32 = 0.3;
+const COOLDOWN_COLOR: Color = Color::rgba(0.0, 0.0, 0.0, 0.6);
+const TEXT_COLOR: Color = Color::rgb(0.902, 0.855, 0.773);
+
+pub struct BeltPlugin;
+
+impl Plugin for BeltPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(GameState::Playing), spawn_belt.run_if(run_once()))
+            .add_systems(
+                Update,
+                (update_belt_slots, update_belt_cooldowns).run_if(in_state(GameState::Playing)),
+            );
+    }
+}
+
+#[derive(Component)]
+struct BeltSlot(usize);
+
+/// How many of the bound item are in the inventory.
+#[derive(Component)]
+struct BeltCount(usize);
+
+/// Covers the slot, shrinking as the item's cooldown runs out.
+#[derive(Component)]
+struct BeltCooldown(usize);
+
+fn spawn_belt(mut commands: Commands, texture_assets: Res<TextureAssets>) {
+    let text_style = TextStyle {
+        font: texture_assets.dungeon_font.clone(),
+        font_size: 16.0,
+        color: TEXT_COLOR,
+    };
+
+    commands
+        .spawn((
+            Name::new("potion_belt"),
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    right: Val::Px(20.0),
+                    bottom: Val::Px(10.0),
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            RenderLayers::all(),
+        ))
+        .with_children(|parent| {
+            for (index, key) in BELT_KEYS.into_iter().enumerate() {
+                let label = key_label(key);
+                parent
+                    .spawn((
+                        Name::new(format!("belt_slot_{label}")),
+                        BeltSlot(index),
+                        NodeBundle {
+                            style: Style {
+                                width: Val::Px(SLOT_SIZE),
+                                height: Val::Px(SLOT_SIZE),
+                                margin: UiRect::horizontal(Val::Px(2.0)),
+                                justify_content: JustifyContent::FlexEnd,
+                                align_items: AlignItems::FlexEnd,
+                                ..Default::default()
+                            },
+                            background_color: EMPTY_SLOT_COLOR.into(),
+                            ..Default::default()
+                        },
+                    ))
+                    .with_children(|parent| {
+                        parent.spawn((
+                            BeltCooldown(index),
+                            NodeBundle {
+                                style: Style {
+                                    position_type: PositionType::Absolute,
+                                    bottom: Val::Px(0.0),
+                                    width: Val::Percent(100.0),
+                                    height: Val::Percent(0.0),
+                                    ..Default::default()
+                                },
+                                background_color: COOLDOWN_COLOR.into(),
+                                ..Default::default()
+                            },
+                        ));
+                        parent.spawn((
+                            BeltCount(index),
+                            TextBundle::from_section("", text_style.clone()),
+                        ));
+                        parent.spawn(
+                            TextBundle::from_section(label, text_style.clone()).with_style(Style {
+                                position_type: PositionType::Absolute,
+                                top: Val::Px(2.0),
+                                left: Val::Px(4.0),
+                                ..Default::default()
+                            }),
+                        );
+                    });
+            }
+        });
+}
+
+fn update_belt_slots(
+    player_q: Query<(&PotionBelt, &Inventory), With<Player>>,
+    item_definitions: ItemDefinitions,
+    mut slot_q: Query<(&BeltSlot, &mut BackgroundColor)>,
+    mut count_q: Query<(&BeltCount, &mut Text)>,
+) {
+    let Ok((belt, inventory)) = player_q.get_single() else {
+        return;
+    };
+
+    for (slot, mut color) in slot_q.iter_mut() {
+        color.0 = belt.slots[slot.0]
+            .as_ref()
+            .and_then(|item| {
+                let color = item_definitions.get(item)?.color();
+                Some(if inventory.count(item) == 0 {
+                    color.with_a(MISSING_ITEM_ALPHA)
+                } else {
+                    color
+                })
+            })
+            .unwrap_or(EMPTY_SLOT_COLOR);
+    }
+
+    for (count, mut text) in count_q.iter_mut() {
+        let value = belt.slots[count.0]
+            .as_ref()
+            .map(|item| inventory.count(item).to_string())
+            .unwrap_or_default();
+        if text.sections[0].value != value {
+            text.sections[0].value = value;
+        }
+    }
+}
+
+fn update_belt_cooldowns(
+    player_q: Query<(&PotionBelt, &ConsumableCooldowns), With<Player>>,
+    item_definitions: ItemDefinitions,
+    mut cooldown_q: Query<(&BeltCooldown, &mut Style)>,
+) {
+    let Ok((belt, cooldowns)) = player_q.get_single() else {
+        return;
+    };
+
+    for (cooldown, mut style) in cooldown_q.iter_mut() {
+        let remaining = belt.slots[cooldown.0]
+            .as_ref()
+            .and_then(|item| {
+                let consumable = item_definitions.get(item)?.consumable.as_ref()?;
+                Some(cooldowns.remaining_fraction(cooldown_group(item, consumable)))
+            })
+            .unwrap_or(0.0);
+        style.height = Val::Percent(remaining * 100.0);
+    }
+}
@@ -0,0 +1,293 @@
+use bevy::{
+    app::{App, Plugin, Update},
+    ecs::{
+        change_detection::DetectChanges,
+        component::Component,
+        event::EventReader,
+        query::{Changed, With, Without},
+        schedule::{common_conditions::in_state, IntoSystemConfigs, NextState, OnEnter, OnExit},
+        system::{Commands, Query, Res, ResMut},
+    },
+    hierarchy::{BuildChildren, ChildBuilder},
+    input::{keyboard::KeyCode, ButtonInput},
+    sprite::TextureAtlas,
+    text::{Text, TextStyle},
+    ui::{
+        node_bundles::{AtlasImageBundle, ButtonBundle, NodeBundle, TextBundle},
+        widget::Button,
+        AlignItems, FlexDirection, Interaction, JustifyContent, Style, UiImage, UiRect, Val,
+        ZIndex,
+    },
+    window::ReceivedCharacter,
+};
+
+use crate::{
+    loading::TextureAssets,
+    player::character::{CharacterSprites, SelectedCharacter, PREVIEW_FRAME},
+    GameState,
+};
+
+use super::menu::{despawn_screen, MenuState, BTN_COLOR, TEXT_COLOR, TITLE_TEXT_COLOR};
+
+const MAX_NAME_LENGTH: usize = 16;
+const PREVIEW_SCALE: f32 = 3.0;
+
+pub struct CharacterCreationPlugin;
+
+impl Plugin for CharacterCreationPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(MenuState::Character), spawn_character_screen)
+            .add_systems(
+                OnExit(MenuState::Character),
+                despawn_screen::<CharacterScreen>,
+            )
+            .add_systems(
+                Update,
+                (character_action, type_name, update_character_preview)
+                    .chain()
+                    .run_if(in_state(MenuState::Character)),
+            );
+    }
+}
+
+#[derive(Component)]
+struct CharacterScreen;
+
+#[derive(Component)]
+struct CharacterPreview;
+
+/// Names the previewed appearance.
+#[derive(Component)]
+struct CharacterLabel;
+
+#[derive(Component)]
+struct NameText;
+
+type ButtonInteraction = (Changed<Interaction>, With<Button>);
+
+#[derive(Component)]
+enum CharacterAction {
+    Previous,
+    Next,
+    Start,
+    Back,
+}
+
+fn name_label(name: &str) -> String {
+    format!("Name: {name}_")
+}
+
+fn spawn_character_screen(
+    mut commands: Commands,
+    texture_assets: Res<TextureAssets>,
+    characters: Res<CharacterSprites>,
+    selected: Res<SelectedCharacter>,
+) {
+    let text_style = TextStyle {
+        font_size: 30.0,
+        font: texture_assets.dungeon_font.clone(),
+        color: TEXT_COLOR,
+    };
+    let btn_style = Style {
+        width: Val::Px(150.0),
+        height: Val::Px(50.0),
+        margin: UiRect::all(Val::Px(20.0)),
+        justify_content: JustifyContent::Center,
+        align_items: AlignItems::Center,
+        ..Default::default()
+    };
+    let arrow_style = Style {
+        width: Val::Px(50.0),
+        ..btn_style.clone()
+    };
+    let character = characters.get(&selected.character).or(characters.0.first());
+
+    let spawn_button =
+        |parent: &mut ChildBuilder<'_>, style: &Style, label: &str, action: CharacterAction| {
+            parent
+                .spawn((
+                    ButtonBundle {
+                        style: style.clone(),
+                        background_color: BTN_COLOR.into(),
+                        ..Default::default()
+                    },
+                    action,
+                ))
+                .with_children(|parent| {
+                    parent.spawn(TextBundle::from_section(label, text_style.clone()));
+                });
+        };
+
+    commands
+        .spawn((
+            CharacterScreen,
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    ..Default::default()
+                },
+                z_index: ZIndex::Global(1),
+                ..Default::default()
+            },
+        ))
+        .with_children(|parent| {
+            parent.spawn(
+                TextBundle::from_section(
+                    "New character",
+                    TextStyle {
+                        font_size: 60.0,
+                        color: TITLE_TEXT_COLOR,
+                        ..text_style.clone()
+                    },
+                )
+                .with_style(Style {
+                    margin: UiRect::all(Val::Px(30.0)),
+                    ..Default::default()
+                }),
+            );
+
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        align_items: AlignItems::Center,
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                })
+                .with_children(|parent| {
+                    spawn_button(parent, &arrow_style, "<", CharacterAction::Previous);
+                    let mut preview = parent.spawn((
+                        CharacterPreview,
+                        AtlasImageBundle {
+                            style: Style {
+                                width: Val::Px(32.0 * PREVIEW_SCALE),
+                                height: Val::Px(48.0 * PREVIEW_SCALE),
+                                ..Default::default()
+                            },
+                            ..Default::default()
+                        },
+                    ));
+                    if let Some(character) = character {
+                        preview.insert((
+                            UiImage::new(character.texture.clone()),
+                            TextureAtlas {
+                                layout: character.layout.clone(),
+                                index: PREVIEW_FRAME,
+                            },
+                        ));
+                    }
+                    spawn_button(parent, &arrow_style, ">", CharacterAction::Next);
+                });
+
+            parent.spawn((
+                CharacterLabel,
+                TextBundle::from_section(
+                    character
+                        .map(|character| character.name.clone())
+                        .unwrap_or_default(),
+                    text_style.clone(),
+                ),
+            ));
+            parent.spawn((
+                NameText,
+                TextBundle::from_section(name_label(&selected.name), text_style.clone())
+                    .with_style(Style {
+                        margin: UiRect::top(Val::Px(20.0)),
+                        ..Default::default()
+                    }),
+            ));
+
+            parent.spawn(NodeBundle::default()).with_children(|parent| {
+                spawn_button(parent, &btn_style, "Start", CharacterAction::Start);
+                spawn_button(parent, &btn_style, "Back", CharacterAction::Back);
+            });
+        });
+}
+
+fn character_action(
+    interaction_q: Query<(&Interaction, &CharacterAction), ButtonInteraction>,
+    characters: Res<CharacterSprites>,
+    mut selected: ResMut<SelectedCharacter>,
+    mut menu_state: ResMut<NextState<MenuState>>,
+    mut game_state: ResMut<NextState<GameState>>,
+) {
+    for (interaction, action) in &interaction_q {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+        let count = characters.0.len();
+        let index = characters
+            .0
+            .iter()
+            .position(|character| character.id == selected.character)
+            .unwrap_or(0);
+        let step = match action {
+            CharacterAction::Previous => count.saturating_sub(1),
+            CharacterAction::Next => 1,
+            CharacterAction::Start => {
+                // nameless characters go by their appearance's name
+                if selected.name.trim().is_empty() {
+                    selected.name = characters
+                        .get(&selected.character)
+                        .map(|character| character.name.clone())
+                        .unwrap_or_default();
+                }
+                menu_state.set(MenuState::Disabled);
+                game_state.set(GameState::Playing);
+                continue;
+            }
+            CharacterAction::Back => {
+                menu_state.set(MenuState::Main);
+                continue;
+            }
+        };
+        if let Some(character) = characters.0.get((index + step) % count.max(1)) {
+            selected.character = character.id.clone();
+        }
+    }
+}
+
+fn type_name(
+    mut character_events: EventReader<ReceivedCharacter>,
+    keys: Res<ButtonInput<KeyCode>>,
+    mut selected: ResMut<SelectedCharacter>,
+) {
+    for event in character_events.read() {
+        for c in event.char.chars() {
+            if !c.is_control() && selected.name.chars().count() < MAX_NAME_LENGTH {
+                selected.name.push(c);
+            }
+        }
+    }
+    if keys.just_pressed(KeyCode::Backspace) {
+        selected.name.pop();
+    }
+}
+
+fn update_character_preview(
+    characters: Res<CharacterSprites>,
+    selected: Res<SelectedCharacter>,
+    mut preview_q: Query<(&mut UiImage, &mut TextureAtlas), With<CharacterPreview>>,
+    mut label_q: Query<&mut Text, (With<CharacterLabel>, Without<NameText>)>,
+    mut name_q: Query<&mut Text, (With<NameText>, Without<CharacterLabel>)>,
+) {
+    if !selected.is_changed() {
+        return;
+    }
+    if let Some(character) = characters.get(&selected.character) {
+        for (mut image, mut atlas) in preview_q.iter_mut() {
+            image.texture = character.texture.clone();
+            atlas.layout = character.layout.clone();
+        }
+        for mut text in label_q.iter_mut() {
+            text.sections[0].value = character.name.clone();
+        }
+    }
+    for mut text in name_q.iter_mut() {
+        text.sections[0].value = name_label(&selected.name);
+    }
+}
@@ -4,7 +4,10 @@ use bevy::{
     ecs::{
         component::Component,
         query::{With, Without},
-        schedule::{common_conditions::in_state, IntoSystemConfigs, OnEnter},
+        schedule::{
+            common_conditions::{in_state, run_once},
+            IntoSystemConfigs, OnEnter,
+        },
         system::{Commands, Query, Res},
     },
     hierarchy::BuildChildren,
@@ -26,12 +29,14 @@ pub struct DiagnosticsPlugin;
 
 impl Plugin for DiagnosticsPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(OnEnter(GameState::Playing), setup_diagnostics)
-            .add_systems(
-                Update,
-                (diagnostics_text_update, diagnostics_show_hide)
-                    .run_if(in_state(GameState::Playing)),
-            );
+        app.add_systems(
+            OnEnter(GameState::Playing),
+            setup_diagnostics.run_if(run_once()),
+        )
+        .add_systems(
+            Update,
+            (diagnostics_text_update, diagnostics_show_hide).run_if(in_state(GameState::Playing)),
+        );
     }
 }
 
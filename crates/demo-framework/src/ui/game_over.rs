@@ -0,0 +1,182 @@
+use bevy::{
+    app::{App, Plugin, Update},
+    core::Name,
+    ecs::{
+        component::Component,
+        entity::Entity,
+        query::{Changed, With},
+        schedule::{common_conditions::in_state, IntoSystemConfigs, NextState, OnEnter, OnExit},
+        system::{Commands, Query, Res, ResMut},
+    },
+    hierarchy::{BuildChildren, DespawnRecursiveExt},
+    render::{color::Color, view::RenderLayers},
+    text::TextStyle,
+    ui::{
+        node_bundles::{ButtonBundle, NodeBundle, TextBundle},
+        widget::Button,
+        AlignItems, BackgroundColor, FlexDirection, Interaction, JustifyContent, Style, UiRect,
+        Val, ZIndex,
+    },
+};
+
+use crate::{
+    loading::TextureAssets,
+    player::death::{Hardcore, LastDeath},
+    GameState,
+};
+
+const OVERLAY_COLOR: Color = Color::rgba(0.1, 0.0, 0.0, 0.6);
+const TITLE_TEXT_COLOR: Color = Color::rgb(0.8, 0.2, 0.2);
+const TEXT_COLOR: Color = Color::rgb(0.902, 0.855, 0.773);
+const BTN_COLOR: Color = Color::rgb(0.275, 0.204, 0.220);
+const HOVERED_BTN_COLOR: Color = Color::rgb(0.337, 0.259, 0.220);
+const PRESSED_BTN_COLOR: Color = Color::rgb(119. / 255., 98. / 255., 96. / 255.);
+
+pub struct GameOverPlugin;
+
+impl Plugin for GameOverPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(GameState::GameOver), spawn_game_over)
+            .add_systems(OnExit(GameState::GameOver), despawn_game_over)
+            .add_systems(
+                Update,
+                (game_over_action, update_btn_colors).run_if(in_state(GameState::GameOver)),
+            );
+    }
+}
+
+#[derive(Component)]
+struct GameOverScreen;
+
+type ButtonInteraction = (Changed<Interaction>, With<Button>, With<GameOverAction>);
+
+#[derive(Component)]
+enum GameOverAction {
+    Respawn,
+    Menu,
+}
+
+fn spawn_game_over(
+    mut commands: Commands,
+    texture_assets: Res<TextureAssets>,
+    hardcore: Res<Hardcore>,
+    last_death: Res<LastDeath>,
+) {
+    let text_style = TextStyle {
+        font: texture_assets.dungeon_font.clone(),
+        font_size: 24.0,
+        color: TEXT_COLOR,
+    };
+    let btn_style = Style {
+        width: Val::Px(220.0),
+        height: Val::Px(50.0),
+        margin: UiRect::all(Val::Px(10.0)),
+        justify_content: JustifyContent::Center,
+        align_items: AlignItems::Center,
+        ..Default::default()
+    };
+
+    let penalty = if hardcore.0 {
+        "Your character is lost. Create a new one from the menu.".to_string()
+    } else {
+        format!(
+            "You lost {} experience and {} gold.",
+            last_death.exp_lost, last_death.gold_lost
+        )
+    };
+    let mut actions = vec![("Main menu", GameOverAction::Menu)];
+    if !hardcore.0 {
+        actions.insert(0, ("Respawn", GameOverAction::Respawn));
+    }
+
+    commands
+        .spawn((
+            Name::new("game_over_screen"),
+            GameOverScreen,
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    ..Default::default()
+                },
+                background_color: OVERLAY_COLOR.into(),
+                z_index: ZIndex::Global(10),
+                ..Default::default()
+            },
+            RenderLayers::all(),
+        ))
+        .with_children(|parent| {
+            parent.spawn(
+                TextBundle::from_section(
+                    "You died",
+                    TextStyle {
+                        font_size: 75.0,
+                        color: TITLE_TEXT_COLOR,
+                        ..text_style.clone()
+                    },
+                )
+                .with_style(Style {
+                    margin: UiRect::all(Val::Px(20.0)),
+                    ..Default::default()
+                }),
+            );
+            parent.spawn(
+                TextBundle::from_section(penalty, text_style.clone()).with_style(Style {
+                    margin: UiRect::bottom(Val::Px(30.0)),
+                    ..Default::default()
+                }),
+            );
+
+            for (label, action) in actions {
+                parent
+                    .spawn((
+                        ButtonBundle {
+                            style: btn_style.clone(),
+                            background_color: BTN_COLOR.into(),
+                            ..Default::default()
+                        },
+                        action,
+                    ))
+                    .with_children(|parent| {
+                        parent.spawn(TextBundle::from_section(label, text_style.clone()));
+                    });
+            }
+        });
+}
+
+fn despawn_game_over(mut commands: Commands, screen_q: Query<Entity, With<GameOverScreen>>) {
+    for entity in &screen_q {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+/// Both leave the game over state, which respawns the player outside of
+/// hardcore.
+fn game_over_action(
+    interaction_q: Query<(&Interaction, &GameOverAction), ButtonInteraction>,
+    mut game_state: ResMut<NextState<GameState>>,
+) {
+    for (interaction, action) in &interaction_q {
+        if *interaction == Interaction::Pressed {
+            game_state.set(match action {
+                GameOverAction::Respawn => GameState::Playing,
+                GameOverAction::Menu => GameState::Menu,
+            });
+        }
+    }
+}
+
+fn update_btn_colors(
+    mut interaction_q: Query<(&Interaction, &mut BackgroundColor), ButtonInteraction>,
+) {
+    for (interaction, mut color) in &mut interaction_q {
+        *color = match *interaction {
+            Interaction::Pressed => PRESSED_BTN_COLOR.into(),
+            Interaction::Hovered => HOVERED_BTN_COLOR.into(),
+            Interaction::None => BTN_COLOR.into(),
+        }
+    }
+}
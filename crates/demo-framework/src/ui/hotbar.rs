@@ -0,0 +1,402 @@
+use bevy::{
+    app::{App, Plugin, Update},
+    asset::Handle,
+    core::Name,
+    ecs::{
+        component::Component,
+        query::{With, Without},
+        schedule::{
+            common_conditions::{in_state, run_once},
+            IntoSystemConfigs, OnEnter,
+        },
+        system::{Commands, Query, Res, ResMut, Resource},
+    },
+    hierarchy::BuildChildren,
+    input::{mouse::MouseButton, ButtonInput},
+    render::{color::Color, texture::Image, view::RenderLayers, view::Visibility},
+    sprite::{TextureAtlas, TextureAtlasLayout},
+    text::{Text, TextStyle},
+    transform::components::GlobalTransform,
+    ui::{
+        node_bundles::{ImageBundle, NodeBundle, TextBundle},
+        AlignItems, BackgroundColor, Display, Interaction, JustifyContent, Node, PositionType,
+        Style, UiImage, UiRect, Val,
+    },
+    window::{PrimaryWindow, Window},
+};
+
+use crate::{
+    damage::Health,
+    loading::TextureAssets,
+    player::{Player, HOTBAR_SLOTS},
+    skill::{Cost, CostType, SkillDefinition, SkillDefinitions, SkillId, SkillVfx},
+    GameState,
+};
+
+const SLOT_SIZE: f32 = 56.0;
+const COOLDOWN_COLOR: Color = Color::rgba(0.0, 0.0, 0.0, 0.6);
+/// Tint of skills the player can't afford.
+const UNAFFORDABLE_TINT: Color = Color::rgb(0.45, 0.5, 1.0);
+/// Tint of skills bound but not learned, e.g. after a respec.
+const UNLEARNED_TINT: Color = Color::rgb(0.35, 0.35, 0.35);
+const TEXT_COLOR: Color = Color::rgb(0.902, 0.855, 0.773);
+
+pub struct HotbarPlugin;
+
+impl Plugin for HotbarPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<DraggedSkill>()
+            .add_systems(OnEnter(GameState::Playing), spawn_hotbar.run_if(run_once()))
+            .add_systems(
+                Update,
+                (
+                    expand_hotbar,
+                    update_slot_icons,
+                    update_slot_cooldowns,
+                    drag_skill,
+                )
+                    .run_if(in_state(GameState::Playing)),
+            );
+    }
+}
+
+/// The skill being dragged onto the hotbar, if any.
+#[derive(Resource, Default)]
+pub(super) struct DraggedSkill(pub Option<SkillId>);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum HotbarBinding {
+    LeftMouse,
+    RightMouse,
+    Key(usize),
+}
+
+impl HotbarBinding {
+    fn skill(self, player: &Player) -> Option<&SkillId> {
+        match self {
+            Self::LeftMouse => Some(&player.selected_left_skill),
+            Self::RightMouse => Some(&player.selected_right_skill),
+            Self::Key(slot) => player.hotbar[slot].as_ref(),
+        }
+    }
+
+    fn assign(self, player: &mut Player, skill: SkillId) {
+        match self {
+            Self::LeftMouse => player.selected_left_skill = skill,
+            Self::RightMouse => player.selected_right_skill = skill,
+            Self::Key(slot) => player.hotbar[slot] = Some(skill),
+        }
+    }
+
+    fn label(self) -> String {
+        match self {
+            Self::LeftMouse => "LMB".to_string(),
+            Self::RightMouse => "RMB".to_string(),
+            Self::Key(slot) => (slot + 1).to_string(),
+        }
+    }
+}
+
+#[derive(Component)]
+struct HotbarSlot(HotbarBinding);
+
+#[derive(Component)]
+struct SlotIcon(HotbarBinding);
+
+/// The skill's name, shown for skills without an icon.
+#[derive(Component)]
+struct SlotName(HotbarBinding);
+
+/// Covers the slot, shrinking as the skill's cooldown or cast runs out.
+#[derive(Component)]
+struct SlotCooldown(HotbarBinding);
+
+/// Follows the cursor while a skill is dragged.
+#[derive(Component)]
+struct DragGhost;
+
+/// The skill's visual effect doubles as its icon.
+fn skill_icon(
+    texture_assets: &TextureAssets,
+    definition: &SkillDefinition,
+) -> Option<(Handle<Image>, Handle<TextureAtlasLayout>)> {
+    match definition.vfx? {
+        SkillVfx::IceMissile => Some((
+            texture_assets.ice_spell_one.clone(),
+            texture_assets.ice_spell_one_layout.clone(),
+        )),
+        SkillVfx::IceShard => Some((
+            texture_assets.ice_spell_two.clone(),
+            texture_assets.ice_spell_two_layout.clone(),
+        )),
+    }
+}
+
+fn can_afford(player: &Player, health: &Health, cost: Cost) -> bool {
+    match cost.cost_type {
+        CostType::None => true,
+        CostType::Life => health.current > cost.value,
+        CostType::Mana => player.stats.mana.current >= cost.value,
+        CostType::Stamina => player.stats.stamina.current >= cost.value,
+    }
+}
+
+fn spawn_hotbar(mut commands: Commands, texture_assets: Res<TextureAssets>) {
+    let text_style = TextStyle {
+        font: texture_assets.dungeon_font.clone(),
+        font_size: 16.0,
+        color: TEXT_COLOR,
+    };
+    let bindings = [HotbarBinding::LeftMouse, HotbarBinding::RightMouse]
+        .into_iter()
+        .chain((0..HOTBAR_SLOTS).map(HotbarBinding::Key));
+
+    commands
+        .spawn((
+            Name::new("hotbar"),
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::FlexEnd,
+                    padding: UiRect::bottom(Val::Px(10.0)),
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            RenderLayers::all(),
+        ))
+        .with_children(|parent| {
+            for binding in bindings {
+                parent
+                    .spawn((
+                        Name::new(format!("hotbar_slot_{}", binding.label())),
+                        HotbarSlot(binding),
+                        Interaction::default(),
+                        ImageBundle {
+                            style: Style {
+                                width: Val::Px(SLOT_SIZE),
+                                height: Val::Px(SLOT_SIZE),
+                                margin: UiRect::horizontal(Val::Px(2.0)),
+                                justify_content: JustifyContent::Center,
+                                align_items: AlignItems::Center,
+                                ..Default::default()
+                            },
+                            image: UiImage::new(texture_assets.border.clone()),
+                            ..Default::default()
+                        },
+                        TextureAtlas::from(texture_assets.border_layout.clone()),
+                        RenderLayers::all(),
+                    ))
+                    .with_children(|parent| {
+                        parent.spawn((
+                            SlotIcon(binding),
+                            ImageBundle {
+                                style: Style {
+                                    width: Val::Percent(70.0),
+                                    height: Val::Percent(70.0),
+                                    ..Default::default()
+                                },
+                                visibility: Visibility::Hidden,
+                                ..Default::default()
+                            },
+                            TextureAtlas::default(),
+                        ));
+                        parent.spawn((
+                            SlotName(binding),
+                            TextBundle::from_section("", text_style.clone()).with_style(Style {
+                                position_type: PositionType::Absolute,
+                                ..Default::default()
+                            }),
+                        ));
+                        parent.spawn((
+                            SlotCooldown(binding),
+                            NodeBundle {
+                                style: Style {
+                                    position_type: PositionType::Absolute,
+                                    bottom: Val::Px(0.0),
+                                    width: Val::Percent(100.0),
+                                    height: Val::Percent(0.0),
+                                    ..Default::default()
+                                },
+                                background_color: COOLDOWN_COLOR.into(),
+                                ..Default::default()
+                            },
+                        ));
+                        parent.spawn(
+                            TextBundle::from_section(binding.label(), text_style.clone())
+                                .with_style(Style {
+                                    position_type: PositionType::Absolute,
+                                    top: Val::Px(4.0),
+                                    left: Val::Px(6.0),
+                                    ..Default::default()
+                                }),
+                        );
+                    });
+            }
+        });
+
+    commands.spawn((
+        Name::new("drag_ghost"),
+        DragGhost,
+        TextBundle::from_section("", text_style).with_style(Style {
+            display: Display::None,
+            position_type: PositionType::Absolute,
+            ..Default::default()
+        }),
+        RenderLayers::all(),
+    ));
+}
+
+/// Shows the filled number slots and one empty slot after them.
+fn expand_hotbar(player_q: Query<&Player>, mut slot_q: Query<(&HotbarSlot, &mut Style)>) {
+    let Ok(player) = player_q.get_single() else {
+        return;
+    };
+    let shown = player
+        .hotbar
+        .iter()
+        .rposition(Option::is_some)
+        .map_or(1, |last| last + 2);
+
+    for (slot, mut style) in slot_q.iter_mut() {
+        let HotbarBinding::Key(index) = slot.0 else {
+            continue;
+        };
+        let display = if index < shown {
+            Display::Flex
+        } else {
+            Display::None
+        };
+        if style.display != display {
+            style.display = display;
+        }
+    }
+}
+
+fn update_slot_icons(
+    player_q: Query<(&Player, &Health)>,
+    texture_assets: Res<TextureAssets>,
+    skill_definitions: SkillDefinitions,
+    mut icon_q: Query<(
+        &SlotIcon,
+        &mut UiImage,
+        &mut TextureAtlas,
+        &mut BackgroundColor,
+        &mut Visibility,
+    )>,
+    mut name_q: Query<(&SlotName, &mut Text)>,
+) {
+    let Ok((player, health)) = player_q.get_single() else {
+        return;
+    };
+
+    for (icon, mut image, mut atlas, mut color, mut visibility) in icon_q.iter_mut() {
+        let Some((id, definition)) = icon
+            .0
+            .skill(player)
+            .and_then(|id| Some((id, skill_definitions.get(id)?)))
+        else {
+            *visibility = Visibility::Hidden;
+            continue;
+        };
+        let Some((texture, layout)) = skill_icon(&texture_assets, definition) else {
+            *visibility = Visibility::Hidden;
+            continue;
+        };
+
+        *visibility = Visibility::Inherited;
+        if image.texture != texture {
+            image.texture = texture;
+            *atlas = TextureAtlas { layout, index: 0 };
+        }
+        let tint = match player.skills.level(id) {
+            None => UNLEARNED_TINT,
+            Some(level) if !can_afford(player, health, definition.cost(level)) => UNAFFORDABLE_TINT,
+            Some(_) => Color::WHITE,
+        };
+        color.0 = tint;
+    }
+
+    for (name, mut text) in name_q.iter_mut() {
+        let value = name
+            .0
+            .skill(player)
+            .and_then(|id| skill_definitions.get(id))
+            .filter(|definition| skill_icon(&texture_assets, definition).is_none())
+            .map(|definition| definition.name.clone())
+            .unwrap_or_default();
+        if text.sections[0].value != value {
+            text.sections[0].value = value;
+        }
+    }
+}
+
+fn update_slot_cooldowns(
+    player_q: Query<&Player>,
+    mut cooldown_q: Query<(&SlotCooldown, &mut Style)>,
+) {
+    let Ok(player) = player_q.get_single() else {
+        return;
+    };
+
+    for (cooldown, mut style) in cooldown_q.iter_mut() {
+        let remaining = cooldown.0.skill(player).map_or(0.0, |id| {
+            match player.casting.as_ref().filter(|cast| cast.skill == *id) {
+                Some(cast) => 1.0 - cast.progress(),
+                None => player.cooldowns.remaining_fraction(id),
+            }
+        });
+        style.height = Val::Percent(remaining * 100.0);
+    }
+}
+
+type DragGhostFilter = (With<DragGhost>, Without<HotbarSlot>);
+
+/// Moves the dragged skill with the cursor and binds it to the slot it is
+/// dropped on.
+fn drag_skill(
+    mut player_q: Query<&mut Player>,
+    window_q: Query<&Window, With<PrimaryWindow>>,
+    slot_q: Query<(&HotbarSlot, &Node, &GlobalTransform, &Style)>,
+    mut ghost_q: Query<(&mut Style, &mut Text), DragGhostFilter>,
+    mouse: Res<ButtonInput<MouseButton>>,
+    skill_definitions: SkillDefinitions,
+    mut dragged: ResMut<DraggedSkill>,
+) {
+    let (Ok(window), Ok((mut ghost_style, mut ghost_text))) =
+        (window_q.get_single(), ghost_q.get_single_mut())
+    else {
+        return;
+    };
+    let Some(skill) = dragged.0.clone() else {
+        if ghost_style.display != Display::None {
+            ghost_style.display = Display::None;
+        }
+        return;
+    };
+    let Some(cursor) = window.cursor_position() else {
+        return;
+    };
+
+    ghost_style.display = Display::Flex;
+    ghost_style.left = Val::Px(cursor.x + 12.0);
+    ghost_style.top = Val::Px(cursor.y + 12.0);
+    if let Some(definition) = skill_definitions.get(&skill) {
+        if ghost_text.sections[0].value != definition.name {
+            ghost_text.sections[0].value = definition.name.clone();
+        }
+    }
+
+    if !mouse.just_released(MouseButton::Left) {
+        return;
+    }
+    dragged.0 = None;
+    let target = slot_q.iter().find(|(_, node, transform, style)| {
+        style.display != Display::None && node.logical_rect(transform).contains(cursor)
+    });
+    if let (Some((slot, ..)), Ok(mut player)) = (target, player_q.get_single_mut()) {
+        slot.0.assign(&mut player, skill);
+    }
+}
@@ -1,86 +1,100 @@
+use std::time::Duration;
+
 use bevy::{
-    app::{App, Plugin},
+    app::{App, Plugin, Update},
     core::Name,
     ecs::{
-        schedule::OnEnter,
-        system::{Commands, Res},
-    },
-    hierarchy::{BuildChildren, Parent},
-    render::view::RenderLayers,
-    sprite::TextureAtlas,
-    ui::{
-        node_bundles::{ImageBundle, NodeBundle},
-        AlignItems, JustifyContent, Style, UiImage, Val,
+        component::Component,
+        event::EventReader,
+        schedule::{
+            common_conditions::{in_state, run_once},
+            IntoSystemConfigs, OnEnter,
+        },
+        system::{Commands, Query, Res},
     },
+    render::{color::Color, view::RenderLayers},
+    text::{Text, TextStyle},
+    time::{Time, Timer, TimerMode},
+    ui::{node_bundles::TextBundle, JustifyContent, PositionType, Style, Val},
+};
+
+use crate::{
+    loading::TextureAssets, player::attack::NotEnoughResource, skill::CostType, GameState,
 };
 
-use crate::{loading::TextureAssets, GameState};
+const WARNING_DURATION: Duration = Duration::from_millis(1500);
 
 pub struct HudPlugin;
 
 impl Plugin for HudPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(OnEnter(GameState::Playing), spawn_skill_selection);
+        app.add_systems(
+            OnEnter(GameState::Playing),
+            spawn_resource_warning.run_if(run_once()),
+        )
+        .add_systems(
+            Update,
+            show_resource_warning.run_if(in_state(GameState::Playing)),
+        );
     }
 }
 
-fn spawn_skill_selection(mut commands: Commands, texture_assets: Res<TextureAssets>) {
-    let root = commands
-        .spawn(NodeBundle {
-            style: Style {
-                width: Val::Percent(100.0),
-                height: Val::Percent(100.0),
-                justify_content: JustifyContent::Center,
-                align_items: AlignItems::FlexEnd,
-                ..Default::default()
-            },
-            ..Default::default()
-        })
-        .insert(Name::new("root_skill_container_node"))
-        .insert(RenderLayers::all())
-        .id();
+/// Text telling the player a skill couldn't be cast, fading out over time.
+#[derive(Component)]
+struct ResourceWarning {
+    timer: Timer,
+}
+
+fn spawn_resource_warning(mut commands: Commands, texture_assets: Res<TextureAssets>) {
+    let mut timer = Timer::new(WARNING_DURATION, TimerMode::Once);
+    timer.tick(WARNING_DURATION);
 
-    let skill_container = commands
-        .spawn(NodeBundle {
-            style: Style {
-                width: Val::Px(300.0),
-                height: Val::Px(100.0),
-                justify_content: JustifyContent::SpaceAround,
-                align_items: AlignItems::Center,
-                ..Default::default()
+    commands.spawn((
+        Name::new("resource_warning"),
+        ResourceWarning { timer },
+        TextBundle::from_section(
+            "",
+            TextStyle {
+                font: texture_assets.dungeon_font.clone(),
+                font_size: 24.0,
+                color: Color::NONE,
             },
+        )
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            bottom: Val::Px(110.0),
+            width: Val::Percent(100.0),
+            justify_content: JustifyContent::Center,
             ..Default::default()
-        })
-        .insert(Name::new("skill_container"))
-        .insert(RenderLayers::all())
-        .id();
-
-    commands.entity(root).add_child(skill_container);
+        }),
+        RenderLayers::all(),
+    ));
+}
 
-    let mut skill_selectors = vec![];
-    for i in 0..2 {
-        skill_selectors.push(
-            commands
-                .spawn((
-                    Name::new(format!("skill_selector_{}", i)),
-                    ImageBundle {
-                        style: Style {
-                            width: Val::Px(80.0),
-                            height: Val::Px(80.0),
-                            ..Default::default()
-                        },
+fn show_resource_warning(
+    mut warning_q: Query<(&mut ResourceWarning, &mut Text)>,
+    mut not_enough_resource_events: EventReader<NotEnoughResource>,
+    time: Res<Time>,
+) {
+    let Ok((mut warning, mut text)) = warning_q.get_single_mut() else {
+        return;
+    };
 
-                        image: UiImage::new(texture_assets.border.clone()),
-                        ..Default::default()
-                    },
-                    TextureAtlas::from(texture_assets.border_layout.clone()),
-                ))
-                .insert(RenderLayers::all())
-                .id(),
-        );
+    if let Some(event) = not_enough_resource_events.read().last() {
+        let resource = match event.cost_type {
+            CostType::Life => "life",
+            CostType::Mana => "mana",
+            CostType::Stamina => "stamina",
+            CostType::None => return,
+        };
+        text.sections[0].value = format!("Not enough {resource}");
+        warning.timer.reset();
     }
 
-    commands
-        .entity(skill_container)
-        .push_children(&skill_selectors);
+    if warning.timer.finished() {
+        return;
+    }
+    warning.timer.tick(time.delta());
+    let alpha = 1.0 - warning.timer.fraction();
+    text.sections[0].style.color = Color::rgba(0.9, 0.85, 0.8, alpha);
 }
@@ -0,0 +1,602 @@
+use bevy::{
+    app::{App, Plugin, PreUpdate, Update},
+    core::Name,
+    ecs::{
+        component::Component,
+        entity::Entity,
+        event::EventWriter,
+        query::{Changed, With, Without},
+        schedule::{
+            common_conditions::{in_state, run_once},
+            IntoSystemConfigs, OnEnter,
+        },
+        system::{Commands, Query, Res, ResMut},
+    },
+    hierarchy::{BuildChildren, ChildBuilder},
+    input::{keyboard::KeyCode, mouse::MouseButton, ButtonInput},
+    render::{color::Color, view::RenderLayers},
+    text::{Text, TextStyle},
+    ui::{
+        node_bundles::{ButtonBundle, NodeBundle, TextBundle},
+        AlignItems, BackgroundColor, Display, FlexDirection, Interaction, JustifyContent,
+        PositionType, RepeatedGridTrack, Style, UiRect, UiSystem, Val,
+    },
+};
+
+use crate::{
+    item::{
+        consumable::{PotionBelt, UseItem},
+        equipment::{EquipItem, Equipment, EquipmentSlot, UnequipItem},
+        DropItem, Gold, Inventory, ItemDefinition, ItemDefinitions, ItemStack,
+    },
+    loading::TextureAssets,
+    player::{
+        input::{PlayerInput, PlayerInputSet},
+        stats::{AttributeKind, ModifierKind, RespecAttributes, SpendAttributePoint},
+        Player,
+    },
+    GameState,
+};
+
+const CELL_SIZE: f32 = 36.0;
+const PANEL_COLOR: Color = Color::rgba(0.275, 0.204, 0.220, 0.9);
+const EMPTY_CELL_COLOR: Color = Color::rgb(0.337, 0.259, 0.220);
+const TEXT_COLOR: Color = Color::rgb(0.902, 0.855, 0.773);
+const BTN_COLOR: Color = Color::rgb(0.337, 0.259, 0.220);
+const HOVERED_BTN_COLOR: Color = Color::rgb(0.467, 0.384, 0.376);
+const DISABLED_BTN_COLOR: Color = Color::rgba(0.337, 0.259, 0.220, 0.3);
+
+pub struct InventoryUiPlugin;
+
+impl Plugin for InventoryUiPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            OnEnter(GameState::Playing),
+            spawn_inventory.run_if(run_once()),
+        )
+        .add_systems(
+            PreUpdate,
+            bind_belt_slot
+                .after(PlayerInputSet)
+                .after(UiSystem::Focus)
+                .run_if(in_state(GameState::Playing)),
+        )
+        .add_systems(
+            Update,
+            (
+                toggle_inventory,
+                click_items,
+                drop_hovered_item,
+                update_inventory,
+                update_equipment,
+                update_gold,
+                update_character_name,
+                attribute_buttons,
+                update_attributes,
+            )
+                .chain()
+                .run_if(in_state(GameState::Playing)),
+        );
+    }
+}
+
+/// The inventory window, toggled with `I`.
+#[derive(Component)]
+struct InventoryPanel;
+
+/// A slot of the player's inventory, by index.
+#[derive(Component)]
+struct InventoryCell(usize);
+
+#[derive(Component)]
+struct InventoryCellText(usize);
+
+/// Names the item worn in a slot.
+#[derive(Component)]
+struct EquipmentCell(EquipmentSlot);
+
+/// Shows the player's name above their equipment.
+#[derive(Component)]
+struct CharacterNameText;
+
+#[derive(Component)]
+struct AttributePointsText;
+
+/// Shows an attribute's value next to the button raising it.
+#[derive(Component)]
+struct AttributeText(AttributeKind);
+
+#[derive(Component)]
+enum AttributeButton {
+    Spend(AttributeKind),
+    Respec,
+}
+
+/// Shows how much gold the player carries.
+#[derive(Component)]
+struct GoldText;
+
+/// Names the hovered item.
+#[derive(Component)]
+struct InventoryTooltip;
+
+fn spawn_inventory(mut commands: Commands, texture_assets: Res<TextureAssets>) {
+    let text_style = TextStyle {
+        font: texture_assets.dungeon_font.clone(),
+        font_size: 16.0,
+        color: TEXT_COLOR,
+    };
+    let inventory = Inventory::default();
+    let spawn_button = |parent: &mut ChildBuilder<'_>, label: &str, action: AttributeButton| {
+        parent
+            .spawn((
+                ButtonBundle {
+                    style: Style {
+                        padding: UiRect::axes(Val::Px(6.0), Val::Px(1.0)),
+                        margin: UiRect::left(Val::Px(8.0)),
+                        justify_content: JustifyContent::Center,
+                        ..Default::default()
+                    },
+                    background_color: BTN_COLOR.into(),
+                    ..Default::default()
+                },
+                action,
+            ))
+            .with_children(|parent| {
+                parent.spawn(TextBundle::from_section(label, text_style.clone()));
+            });
+    };
+
+    commands
+        .spawn((
+            Name::new("inventory"),
+            InventoryPanel,
+            // keeps clicks on the panel from attacking
+            Interaction::default(),
+            NodeBundle {
+                style: Style {
+                    display: Display::None,
+                    position_type: PositionType::Absolute,
+                    left: Val::Px(20.0),
+                    top: Val::Px(20.0),
+                    padding: UiRect::all(Val::Px(12.0)),
+                    flex_direction: FlexDirection::Column,
+                    ..Default::default()
+                },
+                background_color: PANEL_COLOR.into(),
+                ..Default::default()
+            },
+            RenderLayers::all(),
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                CharacterNameText,
+                TextBundle::from_section("", text_style.clone()).with_style(Style {
+                    margin: UiRect::bottom(Val::Px(8.0)),
+                    ..Default::default()
+                }),
+            ));
+
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::Column,
+                        margin: UiRect::bottom(Val::Px(8.0)),
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                })
+                .with_children(|parent| {
+                    parent
+                        .spawn(NodeBundle {
+                            style: Style {
+                                justify_content: JustifyContent::SpaceBetween,
+                                align_items: AlignItems::Center,
+                                ..Default::default()
+                            },
+                            ..Default::default()
+                        })
+                        .with_children(|parent| {
+                            parent.spawn((
+                                AttributePointsText,
+                                TextBundle::from_section("", text_style.clone()),
+                            ));
+                            spawn_button(parent, "Respec", AttributeButton::Respec);
+                        });
+                    for attribute in AttributeKind::ALL {
+                        parent
+                            .spawn(NodeBundle {
+                                style: Style {
+                                    justify_content: JustifyContent::SpaceBetween,
+                                    align_items: AlignItems::Center,
+                                    margin: UiRect::vertical(Val::Px(1.0)),
+                                    ..Default::default()
+                                },
+                                ..Default::default()
+                            })
+                            .with_children(|parent| {
+                                parent.spawn((
+                                    AttributeText(attribute),
+                                    TextBundle::from_section("", text_style.clone()),
+                                ));
+                                spawn_button(parent, "+", AttributeButton::Spend(attribute));
+                            });
+                    }
+                });
+
+            for slot in EquipmentSlot::ALL {
+                parent.spawn((
+                    EquipmentCell(slot),
+                    Interaction::default(),
+                    TextBundle::from_section("", text_style.clone()),
+                ));
+            }
+
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        display: Display::Grid,
+                        margin: UiRect::top(Val::Px(8.0)),
+                        grid_template_columns: RepeatedGridTrack::px(
+                            inventory.width as u16,
+                            CELL_SIZE,
+                        ),
+                        column_gap: Val::Px(2.0),
+                        row_gap: Val::Px(2.0),
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                })
+                .with_children(|parent| {
+                    for index in 0..inventory.slots.len() {
+                        parent
+                            .spawn((
+                                InventoryCell(index),
+                                Interaction::default(),
+                                NodeBundle {
+                                    style: Style {
+                                        width: Val::Px(CELL_SIZE),
+                                        height: Val::Px(CELL_SIZE),
+                                        justify_content: JustifyContent::FlexEnd,
+                                        align_items: AlignItems::FlexEnd,
+                                        ..Default::default()
+                                    },
+                                    background_color: EMPTY_CELL_COLOR.into(),
+                                    ..Default::default()
+                                },
+                            ))
+                            .with_children(|parent| {
+                                parent.spawn((
+                                    InventoryCellText(index),
+                                    TextBundle::from_section("", text_style.clone()),
+                                ));
+                            });
+                    }
+                });
+
+            parent.spawn((
+                GoldText,
+                TextBundle::from_section("", text_style.clone()).with_style(Style {
+                    margin: UiRect::top(Val::Px(8.0)),
+                    ..Default::default()
+                }),
+            ));
+
+            parent.spawn((
+                InventoryTooltip,
+                TextBundle::from_section("", text_style.clone()).with_style(Style {
+                    margin: UiRect::top(Val::Px(8.0)),
+                    ..Default::default()
+                }),
+            ));
+        });
+}
+
+fn toggle_inventory(
+    mut panel_q: Query<&mut Style, With<InventoryPanel>>,
+    keys: Res<ButtonInput<KeyCode>>,
+) {
+    if !keys.just_pressed(KeyCode::KeyI) {
+        return;
+    }
+    if let Ok(mut style) = panel_q.get_single_mut() {
+        style.display = match style.display {
+            Display::None => Display::Flex,
+            _ => Display::None,
+        };
+    }
+}
+
+/// Clicking an item uses or equips it, clicking a worn item takes it off.
+fn click_items(
+    cell_q: Query<(&InventoryCell, &Interaction), Changed<Interaction>>,
+    equipment_cell_q: Query<(&EquipmentCell, &Interaction), Changed<Interaction>>,
+    player_q: Query<(Entity, &Inventory), With<Player>>,
+    item_definitions: ItemDefinitions,
+    mut equip_events: EventWriter<EquipItem>,
+    mut unequip_events: EventWriter<UnequipItem>,
+    mut use_events: EventWriter<UseItem>,
+) {
+    let Ok((player, inventory)) = player_q.get_single() else {
+        return;
+    };
+    for (cell, interaction) in &cell_q {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+        let is_consumable = inventory
+            .slots
+            .get(cell.0)
+            .and_then(Option::as_ref)
+            .and_then(|stack| item_definitions.get(&stack.item))
+            .is_some_and(|definition| definition.consumable.is_some());
+        if is_consumable {
+            use_events.send(UseItem {
+                entity: player,
+                slot: cell.0,
+            });
+        } else {
+            equip_events.send(EquipItem {
+                entity: player,
+                slot: cell.0,
+            });
+        }
+    }
+    for (cell, interaction) in &equipment_cell_q {
+        if *interaction == Interaction::Pressed {
+            unequip_events.send(UnequipItem {
+                entity: player,
+                slot: cell.0,
+            });
+        }
+    }
+}
+
+/// Pressing a belt key over a consumable binds it to that belt slot instead of
+/// using the slot.
+fn bind_belt_slot(
+    cell_q: Query<(&InventoryCell, &Interaction)>,
+    mut player_q: Query<(&Inventory, &mut PotionBelt), With<Player>>,
+    item_definitions: ItemDefinitions,
+    mut player_input: ResMut<PlayerInput>,
+) {
+    let (Some(belt_slot), Ok((inventory, mut belt))) =
+        (player_input.belt_slot, player_q.get_single_mut())
+    else {
+        return;
+    };
+    let Some(stack) = cell_q
+        .iter()
+        .find(|(_, interaction)| **interaction == Interaction::Hovered)
+        .and_then(|(cell, _)| inventory.slots.get(cell.0)?.as_ref())
+    else {
+        return;
+    };
+    let is_consumable = item_definitions
+        .get(&stack.item)
+        .is_some_and(|definition| definition.consumable.is_some());
+    if is_consumable {
+        belt.slots[belt_slot] = Some(stack.item.clone());
+        player_input.belt_slot = None;
+    }
+}
+
+/// Right clicking an item drops it.
+fn drop_hovered_item(
+    cell_q: Query<(&InventoryCell, &Interaction)>,
+    player_q: Query<Entity, With<Player>>,
+    mouse: Res<ButtonInput<MouseButton>>,
+    mut drop_events: EventWriter<DropItem>,
+) {
+    if !mouse.just_pressed(MouseButton::Right) {
+        return;
+    }
+    let Ok(player) = player_q.get_single() else {
+        return;
+    };
+    if let Some((cell, _)) = cell_q
+        .iter()
+        .find(|(_, interaction)| **interaction == Interaction::Hovered)
+    {
+        drop_events.send(DropItem {
+            entity: player,
+            slot: cell.0,
+        });
+    }
+}
+
+fn update_inventory(
+    panel_q: Query<&Style, With<InventoryPanel>>,
+    inventory_q: Query<&Inventory, With<Player>>,
+    item_definitions: ItemDefinitions,
+    mut cell_q: Query<(&InventoryCell, &Interaction, &mut BackgroundColor)>,
+    mut text_q: Query<(&InventoryCellText, &mut Text), Without<InventoryTooltip>>,
+    mut tooltip_q: Query<&mut Text, With<InventoryTooltip>>,
+) {
+    let (Ok(panel), Ok(inventory)) = (panel_q.get_single(), inventory_q.get_single()) else {
+        return;
+    };
+    if panel.display == Display::None {
+        return;
+    }
+
+    let mut tooltip = (String::new(), TEXT_COLOR);
+    for (cell, interaction, mut color) in cell_q.iter_mut() {
+        let stack = inventory.slots.get(cell.0).and_then(Option::as_ref);
+        let definition = stack.and_then(|stack| item_definitions.get(&stack.item));
+        color.0 = definition.map_or(EMPTY_CELL_COLOR, |definition| definition.color());
+        if let (Some(stack), Some(definition), Interaction::Hovered) =
+            (stack, definition, interaction)
+        {
+            tooltip = (describe_item(stack, definition), stack.label_color());
+        }
+    }
+
+    for (cell, mut text) in text_q.iter_mut() {
+        let count = inventory
+            .slots
+            .get(cell.0)
+            .and_then(Option::as_ref)
+            .filter(|stack| stack.count > 1)
+            .map(|stack| stack.count.to_string())
+            .unwrap_or_default();
+        if text.sections[0].value != count {
+            text.sections[0].value = count;
+        }
+    }
+
+    if let Ok(mut text) = tooltip_q.get_single_mut() {
+        let (value, color) = tooltip;
+        if text.sections[0].value != value {
+            text.sections[0].value = value;
+            text.sections[0].style.color = color;
+        }
+    }
+}
+
+/// The item's name followed by its modifiers, one per line.
+fn describe_item(stack: &ItemStack, definition: &ItemDefinition) -> String {
+    let mut description = stack.name(definition).to_string();
+    if let Some(generated) = &stack.generated {
+        description += &format!(
+            "\n{:?} (item level {})",
+            generated.rarity, generated.item_level
+        );
+    }
+    for modifier in stack.modifiers(definition) {
+        let value = match modifier.kind {
+            ModifierKind::Flat => format!("{:+}", modifier.value),
+            ModifierKind::Additive | ModifierKind::Multiplicative => {
+                format!("{:+.0}%", modifier.value * 100.0)
+            }
+        };
+        description += &format!("\n{value} {:?}", modifier.stat);
+    }
+    description
+}
+
+fn update_equipment(
+    panel_q: Query<&Style, With<InventoryPanel>>,
+    equipment_q: Query<&Equipment, With<Player>>,
+    item_definitions: ItemDefinitions,
+    mut cell_q: Query<(&EquipmentCell, &mut Text)>,
+) {
+    let (Ok(panel), Ok(equipment)) = (panel_q.get_single(), equipment_q.get_single()) else {
+        return;
+    };
+    if panel.display == Display::None {
+        return;
+    }
+
+    for (cell, mut text) in cell_q.iter_mut() {
+        let item = equipment
+            .get(cell.0)
+            .and_then(|stack| Some(stack.name(item_definitions.get(&stack.item)?)))
+            .unwrap_or("-");
+        let value = format!("{}: {}", cell.0.name(), item);
+        if text.sections[0].value != value {
+            text.sections[0].value = value;
+        }
+    }
+}
+
+fn update_gold(
+    panel_q: Query<&Style, With<InventoryPanel>>,
+    gold_q: Query<&Gold, With<Player>>,
+    mut text_q: Query<&mut Text, With<GoldText>>,
+) {
+    let (Ok(panel), Ok(gold), Ok(mut text)) = (
+        panel_q.get_single(),
+        gold_q.get_single(),
+        text_q.get_single_mut(),
+    ) else {
+        return;
+    };
+    if panel.display == Display::None {
+        return;
+    }
+    let value = format!("Gold: {}", gold.0);
+    if text.sections[0].value != value {
+        text.sections[0].value = value;
+    }
+}
+
+fn update_character_name(
+    player_q: Query<&Player, Changed<Player>>,
+    mut text_q: Query<&mut Text, With<CharacterNameText>>,
+) {
+    let (Ok(player), Ok(mut text)) = (player_q.get_single(), text_q.get_single_mut()) else {
+        return;
+    };
+    if text.sections[0].value != player.name {
+        text.sections[0].value = player.name.clone();
+    }
+}
+
+fn attribute_buttons(
+    interaction_q: Query<(&Interaction, &AttributeButton), Changed<Interaction>>,
+    player_q: Query<Entity, With<Player>>,
+    mut spend_events: EventWriter<SpendAttributePoint>,
+    mut respec_events: EventWriter<RespecAttributes>,
+) {
+    let Ok(player) = player_q.get_single() else {
+        return;
+    };
+    for (interaction, button) in &interaction_q {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+        match button {
+            AttributeButton::Spend(attribute) => {
+                spend_events.send(SpendAttributePoint {
+                    entity: player,
+                    attribute: *attribute,
+                });
+            }
+            AttributeButton::Respec => {
+                respec_events.send(RespecAttributes { entity: player });
+            }
+        }
+    }
+}
+
+fn update_attributes(
+    panel_q: Query<&Style, With<InventoryPanel>>,
+    player_q: Query<&Player>,
+    mut points_q: Query<&mut Text, With<AttributePointsText>>,
+    mut attribute_q: Query<(&AttributeText, &mut Text), Without<AttributePointsText>>,
+    mut button_q: Query<(&AttributeButton, &Interaction, &mut BackgroundColor)>,
+) {
+    let (Ok(panel), Ok(player)) = (panel_q.get_single(), player_q.get_single()) else {
+        return;
+    };
+    if panel.display == Display::None {
+        return;
+    }
+    let attributes = &player.attributes;
+
+    if let Ok(mut text) = points_q.get_single_mut() {
+        let value = format!("Attribute points: {}", attributes.available_points);
+        if text.sections[0].value != value {
+            text.sections[0].value = value;
+        }
+    }
+    for (attribute, mut text) in attribute_q.iter_mut() {
+        let value = format!("{}: {}", attribute.0.name(), attributes.get(attribute.0));
+        if text.sections[0].value != value {
+            text.sections[0].value = value;
+        }
+    }
+
+    for (button, interaction, mut color) in button_q.iter_mut() {
+        let enabled = match button {
+            AttributeButton::Spend(_) => attributes.available_points > 0,
+            AttributeButton::Respec => attributes.spent.values().any(|points| *points > 0),
+        };
+        *color = match (enabled, interaction) {
+            (false, _) => DISABLED_BTN_COLOR.into(),
+            (true, Interaction::None) => BTN_COLOR.into(),
+            (true, _) => HOVERED_BTN_COLOR.into(),
+        };
+    }
+}
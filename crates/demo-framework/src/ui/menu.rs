@@ -7,16 +7,16 @@ use bevy::{
         component::Component,
         entity::Entity,
         event::EventWriter,
-        query::{Changed, With},
+        query::{Changed, With, Without},
         schedule::{
             common_conditions::in_state, IntoSystemConfigs, NextState, OnEnter, OnExit, States,
         },
-        system::{Commands, Query, Res, ResMut},
+        system::{Commands, Query, Res, ResMut, SystemParam},
     },
     hierarchy::{BuildChildren, ChildBuilder, DespawnRecursiveExt},
     prelude::NodeBundle,
     render::{camera::Camera, color::Color, texture::Image},
-    text::TextStyle,
+    text::{Text, TextStyle},
     ui::{
         node_bundles::{ButtonBundle, ImageBundle, TextBundle},
         widget::Button,
@@ -25,7 +25,11 @@ use bevy::{
     },
 };
 
-use crate::{loading::TextureAssets, GameState};
+use crate::{
+    loading::TextureAssets,
+    player::{death::Hardcore, navigation::ControlScheme, Player},
+    GameState,
+};
 
 const PALETTE: [Color; 4] = [
     Color::rgb(0.902, 0.855, 0.773),                 // off-white
@@ -33,15 +37,17 @@ const PALETTE: [Color; 4] = [
     Color::rgb(0.337, 0.259, 0.220),                 // brown
     Color::rgb(119. / 255., 98. / 255., 96. / 255.), // light brown
 ];
-const TEXT_COLOR: Color = PALETTE[0];
-const TITLE_TEXT_COLOR: Color = PALETTE[1];
-const BTN_COLOR: Color = PALETTE[1];
+pub(super) const TEXT_COLOR: Color = PALETTE[0];
+pub(super) const TITLE_TEXT_COLOR: Color = PALETTE[1];
+pub(super) const BTN_COLOR: Color = PALETTE[1];
 const HOVERED_BTN_COLOR: Color = PALETTE[2];
 const PRESSED_BTN_COLOR: Color = PALETTE[3];
 
 #[derive(Clone, Copy, Default, Eq, PartialEq, Debug, Hash, States)]
-enum MenuState {
+pub(super) enum MenuState {
     Main,
+    /// Picking the appearance and name of a new character.
+    Character,
     Settings,
     #[default]
     Disabled,
@@ -73,7 +79,33 @@ fn setup_menu(mut menu_state: ResMut<NextState<MenuState>>) {
 #[derive(Component)]
 struct MainMenuScreen;
 
-fn setup_main_menu(mut commands: Commands, texture_assets: Res<TextureAssets>) {
+#[derive(Component)]
+struct HardcoreText;
+
+fn hardcore_label(hardcore: &Hardcore) -> &'static str {
+    if hardcore.0 {
+        "Hardcore: On"
+    } else {
+        "Hardcore: Off"
+    }
+}
+
+#[derive(Component)]
+struct ControlSchemeText;
+
+fn control_scheme_label(scheme: &ControlScheme) -> &'static str {
+    match scheme {
+        ControlScheme::Keyboard => "Controls: Keyboard",
+        ControlScheme::ClickToMove => "Controls: Mouse",
+    }
+}
+
+fn setup_main_menu(
+    mut commands: Commands,
+    texture_assets: Res<TextureAssets>,
+    hardcore: Res<Hardcore>,
+    control_scheme: Res<ControlScheme>,
+) {
     let btn_style = Style {
         width: Val::Px(150.0),
         height: Val::Px(50.0),
@@ -147,6 +179,50 @@ fn setup_main_menu(mut commands: Commands, texture_assets: Res<TextureAssets>) {
                             parent.spawn(TextBundle::from_section("Play", btn_text_style.clone()));
                         });
 
+                    parent
+                        .spawn((
+                            ButtonBundle {
+                                style: Style {
+                                    width: Val::Px(250.0),
+                                    ..btn_style.clone()
+                                },
+                                background_color: BTN_COLOR.into(),
+                                ..Default::default()
+                            },
+                            MenuButtonAction::Hardcore,
+                        ))
+                        .with_children(|parent| {
+                            parent.spawn((
+                                HardcoreText,
+                                TextBundle::from_section(
+                                    hardcore_label(&hardcore),
+                                    btn_text_style.clone(),
+                                ),
+                            ));
+                        });
+
+                    parent
+                        .spawn((
+                            ButtonBundle {
+                                style: Style {
+                                    width: Val::Px(250.0),
+                                    ..btn_style.clone()
+                                },
+                                background_color: BTN_COLOR.into(),
+                                ..Default::default()
+                            },
+                            MenuButtonAction::ControlScheme,
+                        ))
+                        .with_children(|parent| {
+                            parent.spawn((
+                                ControlSchemeText,
+                                TextBundle::from_section(
+                                    control_scheme_label(&control_scheme),
+                                    btn_text_style.clone(),
+                                ),
+                            ));
+                        });
+
                     parent
                         .spawn((
                             ButtonBundle {
@@ -246,15 +322,32 @@ fn setup_background(mut commands: Commands, texture_assets: Res<TextureAssets>)
 #[derive(Component)]
 enum MenuButtonAction {
     Play,
+    /// Toggles whether death deletes the character.
+    Hardcore,
+    /// Switches between keyboard and click-to-move controls.
+    ControlScheme,
     Settings,
     Quit,
 }
 
+/// Settings toggled from the main menu, with their button labels.
+#[derive(SystemParam)]
+struct MenuToggles<'w, 's> {
+    hardcore: ResMut<'w, Hardcore>,
+    control_scheme: ResMut<'w, ControlScheme>,
+    hardcore_text_q:
+        Query<'w, 's, &'static mut Text, (With<HardcoreText>, Without<ControlSchemeText>)>,
+    control_scheme_text_q:
+        Query<'w, 's, &'static mut Text, (With<ControlSchemeText>, Without<HardcoreText>)>,
+}
+
 fn menu_action(
     interaction_q: Query<(&Interaction, &MenuButtonAction), (Changed<Interaction>, With<Button>)>,
     mut app_exit_event: EventWriter<AppExit>,
     mut menu_state: ResMut<NextState<MenuState>>,
     mut game_state: ResMut<NextState<GameState>>,
+    mut toggles: MenuToggles,
+    player_q: Query<(), With<Player>>,
 ) {
     for (interaction, menu_btn_action) in &interaction_q {
         if *interaction == Interaction::Pressed {
@@ -262,10 +355,30 @@ fn menu_action(
                 MenuButtonAction::Quit => {
                     app_exit_event.send(AppExit);
                 }
+                // continues the session if there is a character
+                MenuButtonAction::Play if player_q.is_empty() => {
+                    menu_state.set(MenuState::Character);
+                }
                 MenuButtonAction::Play => {
                     menu_state.set(MenuState::Disabled);
                     game_state.set(GameState::Playing);
                 }
+                MenuButtonAction::Hardcore => {
+                    toggles.hardcore.0 = !toggles.hardcore.0;
+                    for mut text in toggles.hardcore_text_q.iter_mut() {
+                        text.sections[0].value = hardcore_label(&toggles.hardcore).to_string();
+                    }
+                }
+                MenuButtonAction::ControlScheme => {
+                    *toggles.control_scheme = match *toggles.control_scheme {
+                        ControlScheme::Keyboard => ControlScheme::ClickToMove,
+                        ControlScheme::ClickToMove => ControlScheme::Keyboard,
+                    };
+                    for mut text in toggles.control_scheme_text_q.iter_mut() {
+                        text.sections[0].value =
+                            control_scheme_label(&toggles.control_scheme).to_string();
+                    }
+                }
                 MenuButtonAction::Settings => menu_state.set(MenuState::Settings),
             }
         }
@@ -287,7 +400,10 @@ fn update_btn_colors(
     }
 }
 
-fn despawn_screen<T: Component>(mut commands: Commands, to_despawn: Query<Entity, With<T>>) {
+pub(super) fn despawn_screen<T: Component>(
+    mut commands: Commands,
+    to_despawn: Query<Entity, With<T>>,
+) {
     for entity in &to_despawn {
         commands.entity(entity).despawn_recursive();
     }
@@ -0,0 +1,284 @@
+use bevy::{
+    app::{App, Plugin, Update},
+    core::Name,
+    ecs::{
+        component::Component,
+        entity::Entity,
+        event::EventWriter,
+        query::{Changed, With, Without},
+        schedule::{
+            common_conditions::{in_state, run_once},
+            IntoSystemConfigs, OnEnter,
+        },
+        system::{Commands, Query, Res, ResMut},
+    },
+    hierarchy::{BuildChildren, ChildBuilder},
+    input::{keyboard::KeyCode, ButtonInput},
+    render::{color::Color, view::RenderLayers},
+    text::{Text, TextStyle},
+    ui::{
+        node_bundles::{ButtonBundle, NodeBundle, TextBundle},
+        AlignItems, BackgroundColor, Display, FlexDirection, Interaction, JustifyContent,
+        PositionType, Style, UiRect, Val,
+    },
+};
+
+use super::hotbar::DraggedSkill;
+use crate::{
+    loading::TextureAssets,
+    player::Player,
+    skill::{RespecSkills, SkillDefinitions, SkillId, SpendSkillPoint},
+    GameState,
+};
+
+const PANEL_COLOR: Color = Color::rgba(0.275, 0.204, 0.220, 0.9);
+const TEXT_COLOR: Color = Color::rgb(0.902, 0.855, 0.773);
+const LOCKED_TEXT_COLOR: Color = Color::rgb(0.55, 0.5, 0.48);
+const BTN_COLOR: Color = Color::rgb(0.337, 0.259, 0.220);
+const HOVERED_BTN_COLOR: Color = Color::rgb(0.467, 0.384, 0.376);
+const DISABLED_BTN_COLOR: Color = Color::rgba(0.337, 0.259, 0.220, 0.3);
+
+pub struct SkillTreePlugin;
+
+impl Plugin for SkillTreePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            OnEnter(GameState::Playing),
+            spawn_skill_tree.run_if(run_once()),
+        )
+        .add_systems(
+            Update,
+            (
+                toggle_skill_tree,
+                skill_tree_buttons,
+                drag_skill_nodes,
+                update_skill_tree,
+            )
+                .chain()
+                .run_if(in_state(GameState::Playing)),
+        );
+    }
+}
+
+/// The skill tree window, toggled with `K`.
+#[derive(Component)]
+struct SkillTreePanel;
+
+#[derive(Component)]
+struct SkillPointsText;
+
+/// Shows the name and level of a skill, greyed out until it can be learned.
+/// Learned skills can be dragged onto the hotbar.
+#[derive(Component)]
+struct SkillNodeText(SkillId);
+
+#[derive(Component)]
+enum SkillTreeButton {
+    Spend(SkillId),
+    Respec,
+}
+
+fn spawn_skill_tree(
+    mut commands: Commands,
+    texture_assets: Res<TextureAssets>,
+    skill_definitions: SkillDefinitions,
+) {
+    let text_style = TextStyle {
+        font: texture_assets.dungeon_font.clone(),
+        font_size: 22.0,
+        color: TEXT_COLOR,
+    };
+    let spawn_button = |parent: &mut ChildBuilder<'_>, label: &str, action: SkillTreeButton| {
+        parent
+            .spawn((
+                ButtonBundle {
+                    style: Style {
+                        padding: UiRect::axes(Val::Px(8.0), Val::Px(2.0)),
+                        margin: UiRect::left(Val::Px(8.0)),
+                        justify_content: JustifyContent::Center,
+                        ..Default::default()
+                    },
+                    background_color: BTN_COLOR.into(),
+                    ..Default::default()
+                },
+                action,
+            ))
+            .with_children(|parent| {
+                parent.spawn(TextBundle::from_section(label, text_style.clone()));
+            });
+    };
+
+    commands
+        .spawn((
+            Name::new("skill_tree"),
+            SkillTreePanel,
+            // keeps clicks on the panel from attacking
+            Interaction::default(),
+            NodeBundle {
+                style: Style {
+                    display: Display::None,
+                    position_type: PositionType::Absolute,
+                    right: Val::Px(20.0),
+                    top: Val::Px(20.0),
+                    min_width: Val::Px(280.0),
+                    padding: UiRect::all(Val::Px(12.0)),
+                    flex_direction: FlexDirection::Column,
+                    ..Default::default()
+                },
+                background_color: PANEL_COLOR.into(),
+                ..Default::default()
+            },
+            RenderLayers::all(),
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                SkillPointsText,
+                TextBundle::from_section("", text_style.clone()).with_style(Style {
+                    margin: UiRect::bottom(Val::Px(8.0)),
+                    ..Default::default()
+                }),
+            ));
+
+            for (id, definition) in skill_definitions.all() {
+                parent
+                    .spawn(NodeBundle {
+                        style: Style {
+                            justify_content: JustifyContent::SpaceBetween,
+                            align_items: AlignItems::Center,
+                            margin: UiRect::vertical(Val::Px(2.0)),
+                            // indent deeper tiers
+                            padding: UiRect::left(Val::Px(16.0 * definition.tier as f32)),
+                            ..Default::default()
+                        },
+                        ..Default::default()
+                    })
+                    .with_children(|parent| {
+                        parent.spawn((
+                            SkillNodeText(id.clone()),
+                            Interaction::default(),
+                            TextBundle::from_section("", text_style.clone()),
+                        ));
+                        spawn_button(parent, "+", SkillTreeButton::Spend(id.clone()));
+                    });
+            }
+
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        justify_content: JustifyContent::FlexEnd,
+                        margin: UiRect::top(Val::Px(8.0)),
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                })
+                .with_children(|parent| {
+                    spawn_button(parent, "Respec", SkillTreeButton::Respec);
+                });
+        });
+}
+
+fn toggle_skill_tree(
+    mut panel_q: Query<&mut Style, With<SkillTreePanel>>,
+    keys: Res<ButtonInput<KeyCode>>,
+) {
+    if !keys.just_pressed(KeyCode::KeyK) {
+        return;
+    }
+    if let Ok(mut style) = panel_q.get_single_mut() {
+        style.display = match style.display {
+            Display::None => Display::Flex,
+            _ => Display::None,
+        };
+    }
+}
+
+fn skill_tree_buttons(
+    interaction_q: Query<(&Interaction, &SkillTreeButton), Changed<Interaction>>,
+    player_q: Query<Entity, With<Player>>,
+    mut spend_events: EventWriter<SpendSkillPoint>,
+    mut respec_events: EventWriter<RespecSkills>,
+) {
+    let Ok(player) = player_q.get_single() else {
+        return;
+    };
+    for (interaction, button) in &interaction_q {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+        match button {
+            SkillTreeButton::Spend(skill) => {
+                spend_events.send(SpendSkillPoint {
+                    entity: player,
+                    skill: skill.clone(),
+                });
+            }
+            SkillTreeButton::Respec => {
+                respec_events.send(RespecSkills { entity: player });
+            }
+        }
+    }
+}
+
+fn drag_skill_nodes(
+    node_q: Query<(&Interaction, &SkillNodeText), Changed<Interaction>>,
+    player_q: Query<&Player>,
+    mut dragged: ResMut<DraggedSkill>,
+) {
+    let Ok(player) = player_q.get_single() else {
+        return;
+    };
+    for (interaction, node) in &node_q {
+        if *interaction == Interaction::Pressed && player.skills.level(&node.0).is_some() {
+            dragged.0 = Some(node.0.clone());
+        }
+    }
+}
+
+fn update_skill_tree(
+    panel_q: Query<&Style, With<SkillTreePanel>>,
+    player_q: Query<&Player>,
+    skill_definitions: SkillDefinitions,
+    mut points_q: Query<&mut Text, With<SkillPointsText>>,
+    mut node_q: Query<(&SkillNodeText, &mut Text), Without<SkillPointsText>>,
+    mut button_q: Query<(&SkillTreeButton, &Interaction, &mut BackgroundColor)>,
+) {
+    let (Ok(panel), Ok(player)) = (panel_q.get_single(), player_q.get_single()) else {
+        return;
+    };
+    if panel.display == Display::None {
+        return;
+    }
+    let skills = &player.skills;
+
+    if let Ok(mut text) = points_q.get_single_mut() {
+        text.sections[0].value = format!("Skill points: {}", skills.available_points);
+    }
+
+    for (node, mut text) in node_q.iter_mut() {
+        let Some(definition) = skill_definitions.get(&node.0) else {
+            continue;
+        };
+        let level = skills.level(&node.0).unwrap_or(0);
+        let section = &mut text.sections[0];
+        section.value = format!("{} {}/{}", definition.name, level, definition.max_level);
+        section.style.color = if level > 0 {
+            TEXT_COLOR
+        } else {
+            LOCKED_TEXT_COLOR
+        };
+    }
+
+    for (button, interaction, mut color) in button_q.iter_mut() {
+        let enabled = match button {
+            SkillTreeButton::Spend(id) => skill_definitions
+                .get(id)
+                .is_some_and(|definition| skills.can_spend(id, definition).is_ok()),
+            SkillTreeButton::Respec => skills.skills.values().any(|skill| skill.points > 0),
+        };
+        *color = match (enabled, interaction) {
+            (false, _) => DISABLED_BTN_COLOR.into(),
+            (true, Interaction::None) => BTN_COLOR.into(),
+            (true, _) => HOVERED_BTN_COLOR.into(),
+        };
+    }
+}
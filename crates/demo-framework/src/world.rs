@@ -1,5 +1,8 @@
-mod chunk;
-mod helpers;
+pub(crate) mod chunk;
+mod deltas;
+pub(crate) mod helpers;
+mod map;
+mod nav;
 mod tile;
 
 use bevy::{
@@ -11,11 +14,17 @@ use bevy_rapier2d::plugin::RapierConfiguration;
 
 use crate::GameState;
 
+pub use deltas::{ChunkDelta, ChunkDeltas};
+pub use map::{
+    HandcraftedMap, MapProperties, MapRegions, MapSpawnPoint, MapTrigger, MapTriggerEvent, TiledMap,
+};
+pub use nav::{NavGrid, NavObstacle};
+
 pub struct WorldPlugin;
 
 impl Plugin for WorldPlugin {
     fn build(&self, app: &mut App) {
-        app.add_plugins(chunk::ChunkPlugin)
+        app.add_plugins((chunk::ChunkPlugin, map::MapPlugin))
             .add_systems(OnExit(GameState::Loading), configure_physics);
     }
 }
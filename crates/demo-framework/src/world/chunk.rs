@@ -2,24 +2,30 @@ use crate::camera::MainCamera;
 use crate::loading::TextureAssets;
 use crate::GameState;
 use bevy::app::{App, Plugin, PostUpdate, Update};
+use bevy::asset::Assets;
 use bevy::core::Name;
 use bevy::ecs::reflect::ReflectComponent;
 use bevy::hierarchy::{BuildChildren, DespawnRecursiveExt};
-use bevy::math::{IVec2, Vec3, Vec3Swizzles};
+use bevy::math::{IVec2, Vec2, Vec3, Vec3Swizzles};
 use bevy::prelude::{
     in_state, Camera, Commands, Component, Entity, Event, EventReader, EventWriter,
-    IntoSystemConfigs, Query, Res, SpatialBundle, Transform, With,
+    IntoSystemConfigs, Query, Res, SpatialBundle, Transform, With, Without,
 };
 use bevy::reflect::Reflect;
 use bevy::render::view::RenderLayers;
+use bevy::sprite::{SpriteSheetBundle, TextureAtlas};
 use bevy::utils::HashSet;
 use bevy_ecs_tilemap::map::{TilemapId, TilemapRenderSettings, TilemapTexture};
 use bevy_ecs_tilemap::prelude::{TileBundle, TilePos, TilemapType};
 use bevy_ecs_tilemap::tiles::TileStorage;
+use bevy_ecs_tilemap::tiles::TileTextureIndex;
 use bevy_ecs_tilemap::TilemapBundle;
-use bevy_magic_light_2d::gi::render_layer::CAMERA_LAYER_FLOOR;
+use bevy_magic_light_2d::gi::render_layer::{CAMERA_LAYER_FLOOR, CAMERA_LAYER_OBJECTS};
 
+use crate::loading::MapAssets;
+use crate::world::deltas::ChunkDeltas;
 use crate::world::helpers::{camera_pos_to_chunk_pos, CHUNK_SIZE, TILE_SIZE};
+use crate::world::map::{spawn_handcrafted_maps, HandcraftedMap, MapRegions, MapSpawned};
 use crate::world::tile::{
     determine_predominant_tile_type, get_tile_from_perlin_noise, tile_type_to_texture_index,
 };
@@ -32,7 +38,7 @@ impl Plugin for ChunkPlugin {
             .add_systems(
                 Update,
                 (
-                    spawn_chunks_around_camera,
+                    spawn_chunks_around_camera.after(spawn_handcrafted_maps),
                     handle_spawn_chunk_event.after(spawn_chunks_around_camera),
                 )
                     .run_if(in_state(GameState::Playing)),
@@ -45,12 +51,19 @@ impl Plugin for ChunkPlugin {
 }
 
 const CHUNKS_AROUND_CAMERA: IVec2 = IVec2 { x: 4, y: 4 };
+const WORLD_SEED: u32 = 238432;
 
 pub fn spawn_chunks_around_camera(
     camera_q: Query<&Transform, With<MainCamera>>,
     chunk_q: Query<&Chunk>,
+    map_regions: Res<MapRegions>,
+    pending_map_q: Query<(), (With<HandcraftedMap>, Without<MapSpawned>)>,
     mut spawn_chunk_event: EventWriter<SpawnChunkEvent>,
 ) {
+    // chunks could end up under handcrafted maps whose region isn't known yet
+    if !pending_map_q.is_empty() {
+        return;
+    }
     if let Ok(transform) = camera_q.get_single() {
         let camera_chunk_pos = camera_pos_to_chunk_pos(&transform.translation.xy());
         let existing_chunks: HashSet<IVec2> = chunk_q.iter().map(|chunk| chunk.pos).collect();
@@ -61,7 +74,7 @@ pub fn spawn_chunks_around_camera(
                 ..=(camera_chunk_pos.y + CHUNKS_AROUND_CAMERA.y)
             {
                 let pos = IVec2 { x, y };
-                if !existing_chunks.contains(&pos) {
+                if !existing_chunks.contains(&pos) && !map_regions.contains(pos) {
                     spawn_chunk_event.send(SpawnChunkEvent { pos });
                 }
             }
@@ -98,7 +111,10 @@ pub fn handle_spawn_chunk_event(
     mut cache_events: EventReader<SpawnChunkEvent>,
     chunk_q: Query<&Chunk>,
     texture_assets: Res<TextureAssets>,
+    map_assets: Res<MapAssets>,
+    chunk_deltas: Res<Assets<ChunkDeltas>>,
 ) {
+    let deltas = chunk_deltas.get(&map_assets.world_deltas);
     for event in cache_events.read() {
         let chunk_pos = event.pos;
         let existing_chunks: HashSet<IVec2> = chunk_q.iter().map(|chunk| chunk.pos).collect();
@@ -111,14 +127,7 @@ pub fn handle_spawn_chunk_event(
         for x in 0..CHUNK_SIZE.x {
             for y in 0..CHUNK_SIZE.y {
                 let tile_pos = TilePos { x, y };
-                let blocks = get_tile_from_perlin_noise(chunk_pos, tile_pos, 238432);
-
-                // let predominant_tile_type = determine_predominant_tile_type(&blocks);
-                let texture_index = tile_type_to_texture_index(blocks[0]);
-                // println!(
-                //     "Predominant tile type: {:?} {:?}",
-                //     predominant_tile_type, texture_index
-                // );
+                let texture_index = ground_texture_index(chunk_pos, tile_pos, deltas);
 
                 let tile_entity = commands
                     .spawn(TileBundle {
@@ -131,6 +140,28 @@ pub fn handle_spawn_chunk_event(
                     .id();
                 commands.entity(tilemap_entity).add_child(tile_entity);
                 tile_storage.set(&tile_pos, tile_entity);
+
+                if let Some(prop_index) = deltas.and_then(|d| d.prop(chunk_pos, tile_pos)) {
+                    let prop_entity = commands
+                        .spawn((
+                            ChunkProp,
+                            SpriteSheetBundle {
+                                transform: Transform::from_translation(
+                                    (Vec2::new(x as f32, y as f32) * Vec2::from(TILE_SIZE))
+                                        .extend(1.0),
+                                ),
+                                texture: texture_assets.grass_land_decorative.clone(),
+                                atlas: TextureAtlas {
+                                    layout: texture_assets.grass_land_decorative_layout.clone(),
+                                    index: prop_index as usize,
+                                },
+                                ..Default::default()
+                            },
+                        ))
+                        .insert(RenderLayers::from_layers(CAMERA_LAYER_OBJECTS))
+                        .id();
+                    commands.entity(tilemap_entity).add_child(prop_entity);
+                }
             }
         }
 
@@ -168,6 +199,25 @@ pub struct Chunk {
     pub pos: IVec2,
 }
 
+/// A decorative sprite placed on a chunk tile by hand.
+#[derive(Component, Debug)]
+pub struct ChunkProp;
+
+/// The ground texture of a tile, taking hand edits over the generated one.
+pub(crate) fn ground_texture_index(
+    chunk_pos: IVec2,
+    tile_pos: TilePos,
+    deltas: Option<&ChunkDeltas>,
+) -> TileTextureIndex {
+    if let Some(index) = deltas.and_then(|d| d.tile(chunk_pos, tile_pos)) {
+        return TileTextureIndex(index);
+    }
+
+    let blocks = get_tile_from_perlin_noise(chunk_pos, tile_pos, WORLD_SEED);
+    // let predominant_tile_type = determine_predominant_tile_type(&blocks);
+    tile_type_to_texture_index(blocks[0])
+}
+
 #[derive(Event)]
 pub struct SpawnChunkEvent {
     pub pos: IVec2,
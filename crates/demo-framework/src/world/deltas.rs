@@ -0,0 +1,83 @@
+use std::collections::BTreeMap;
+
+use bevy::{asset::Asset, math::IVec2, reflect::TypePath};
+use bevy_ecs_tilemap::tiles::TilePos;
+use serde::{Deserialize, Serialize};
+
+/// Hand edits layered on top of the procedural world, keyed by chunk.
+///
+/// Edits are stored in ordered maps so saving the same world twice produces
+/// the same file.
+#[derive(Asset, TypePath, Serialize, Deserialize, Debug, Default, Clone)]
+pub struct ChunkDeltas {
+    #[serde(default)]
+    pub chunks: BTreeMap<(i32, i32), ChunkDelta>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+pub struct ChunkDelta {
+    /// Texture index in the ground atlas per tile position.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub tiles: BTreeMap<(u32, u32), u32>,
+    /// Texture index in the decorative atlas per tile position.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub props: BTreeMap<(u32, u32), u32>,
+}
+
+impl ChunkDelta {
+    fn is_empty(&self) -> bool {
+        self.tiles.is_empty() && self.props.is_empty()
+    }
+}
+
+impl ChunkDeltas {
+    pub fn chunk(&self, chunk_pos: IVec2) -> Option<&ChunkDelta> {
+        self.chunks.get(&(chunk_pos.x, chunk_pos.y))
+    }
+
+    pub fn tile(&self, chunk_pos: IVec2, tile_pos: TilePos) -> Option<u32> {
+        self.chunk(chunk_pos)
+            .and_then(|chunk| chunk.tiles.get(&(tile_pos.x, tile_pos.y)).copied())
+    }
+
+    pub fn prop(&self, chunk_pos: IVec2, tile_pos: TilePos) -> Option<u32> {
+        self.chunk(chunk_pos)
+            .and_then(|chunk| chunk.props.get(&(tile_pos.x, tile_pos.y)).copied())
+    }
+
+    /// Sets or clears the tile override, returning the previous one.
+    pub fn set_tile(
+        &mut self,
+        chunk_pos: IVec2,
+        tile_pos: TilePos,
+        index: Option<u32>,
+    ) -> Option<u32> {
+        self.update(chunk_pos, |chunk| match index {
+            Some(index) => chunk.tiles.insert((tile_pos.x, tile_pos.y), index),
+            None => chunk.tiles.remove(&(tile_pos.x, tile_pos.y)),
+        })
+    }
+
+    /// Sets or clears the prop, returning the previous one.
+    pub fn set_prop(
+        &mut self,
+        chunk_pos: IVec2,
+        tile_pos: TilePos,
+        index: Option<u32>,
+    ) -> Option<u32> {
+        self.update(chunk_pos, |chunk| match index {
+            Some(index) => chunk.props.insert((tile_pos.x, tile_pos.y), index),
+            None => chunk.props.remove(&(tile_pos.x, tile_pos.y)),
+        })
+    }
+
+    fn update<T>(&mut self, chunk_pos: IVec2, f: impl FnOnce(&mut ChunkDelta) -> T) -> T {
+        let key = (chunk_pos.x, chunk_pos.y);
+        let chunk = self.chunks.entry(key).or_default();
+        let result = f(chunk);
+        if chunk.is_empty() {
+            self.chunks.remove(&key);
+        }
+        result
+    }
+}
@@ -1,5 +1,5 @@
 use bevy::math::{IVec2, UVec2, Vec2};
-use bevy_ecs_tilemap::map::TilemapTileSize;
+use bevy_ecs_tilemap::{map::TilemapTileSize, tiles::TilePos};
 use noise::{NoiseFn, Perlin};
 
 pub(crate) const TILE_SIZE: TilemapTileSize = TilemapTileSize { x: 32.0, y: 32.0 };
@@ -16,6 +16,38 @@ pub(crate) fn camera_pos_to_chunk_pos(camera_pos: &Vec2) -> IVec2 {
     )
 }
 
+/// Returns the chunk and the tile inside of it that cover a world position.
+pub(crate) fn world_pos_to_tile_pos(world_pos: &Vec2) -> (IVec2, TilePos) {
+    cell_to_tile_pos(world_pos_to_cell(world_pos))
+}
+
+/// The world-wide tile coordinates of the tile covering a world position.
+pub(crate) fn world_pos_to_cell(world_pos: &Vec2) -> IVec2 {
+    let tile_size = Vec2::from(TILE_SIZE);
+    // tiles are centered on their grid position
+    ((*world_pos + tile_size / 2.) / tile_size)
+        .floor()
+        .as_ivec2()
+}
+
+/// The center of the tile at world-wide tile coordinates.
+pub(crate) fn cell_to_world_pos(cell: IVec2) -> Vec2 {
+    cell.as_vec2() * Vec2::from(TILE_SIZE)
+}
+
+/// Splits world-wide tile coordinates into a chunk and a tile inside of it.
+pub(crate) fn cell_to_tile_pos(cell: IVec2) -> (IVec2, TilePos) {
+    let chunk_size = CHUNK_SIZE.as_ivec2();
+    let local = cell.rem_euclid(chunk_size);
+    (
+        cell.div_euclid(chunk_size),
+        TilePos {
+            x: local.x as u32,
+            y: local.y as u32,
+        },
+    )
+}
+
 pub(crate) fn get_perlin_noise_for_pos(pos: (f64, f64), seed: u32) -> f64 {
     let n1 = Perlin::new(1 + seed);
     let n2 = Perlin::new(2 + seed);
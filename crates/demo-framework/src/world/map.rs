@@ -0,0 +1,676 @@
+use std::collections::HashMap;
+
+use bevy::{
+    app::{App, Plugin, Update},
+    asset::{
+        io::Reader, Asset, AssetApp, AssetLoader, Assets, AsyncReadExt, Handle, LoadContext,
+        ParseAssetPathError,
+    },
+    core::Name,
+    ecs::{
+        component::Component,
+        entity::Entity,
+        event::{Event, EventReader, EventWriter},
+        query::Without,
+        schedule::{
+            common_conditions::{in_state, run_once},
+            IntoSystemConfigs, OnEnter,
+        },
+        system::{Commands, Query, Res, ResMut, Resource},
+    },
+    hierarchy::BuildChildren,
+    math::{IRect, IVec2, UVec2, Vec2, Vec3},
+    prelude::SpatialBundle,
+    reflect::TypePath,
+    render::{
+        color::Color,
+        texture::Image,
+        view::{RenderLayers, VisibilityBundle},
+    },
+    transform::{components::Transform, TransformBundle},
+    utils::BoxedFuture,
+};
+use bevy_ecs_tilemap::{
+    map::{TilemapId, TilemapRenderSettings, TilemapSize, TilemapTexture, TilemapTileSize},
+    prelude::{TileBundle, TilePos, TilemapType},
+    tiles::{TileFlip, TileStorage, TileTextureIndex},
+    TilemapBundle,
+};
+use bevy_magic_light_2d::gi::{
+    render_layer::{CAMERA_LAYER_FLOOR, CAMERA_LAYER_OBJECTS},
+    types::OmniLightSource2D,
+};
+use bevy_rapier2d::{
+    geometry::{ActiveEvents, Collider, Sensor},
+    pipeline::CollisionEvent,
+};
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::{
+    item::loot::{chest_sprite, Loot, LootContainer},
+    loading::MapAssets,
+    player::Player,
+    GameState,
+};
+
+use super::{
+    helpers::{CHUNK_SIZE, TILE_SIZE},
+    nav::NavObstacle,
+};
+
+/// Where the starter town is embedded in the procedural world, in chunk coordinates.
+const TOWN_CHUNK_OFFSET: IVec2 = IVec2::new(-3, -3);
+
+// Tiled stores flip flags in the three highest bits of a global tile id.
+const FLIPPED_HORIZONTALLY_FLAG: u32 = 0x8000_0000;
+const FLIPPED_VERTICALLY_FLAG: u32 = 0x4000_0000;
+const FLIPPED_DIAGONALLY_FLAG: u32 = 0x2000_0000;
+const GID_MASK: u32 =
+    !(FLIPPED_HORIZONTALLY_FLAG | FLIPPED_VERTICALLY_FLAG | FLIPPED_DIAGONALLY_FLAG);
+
+pub struct MapPlugin;
+
+impl Plugin for MapPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_asset::<TiledMap>()
+            .register_asset_loader(TiledMapLoader)
+            .init_resource::<MapRegions>()
+            .add_event::<MapTriggerEvent>()
+            .add_systems(
+                OnEnter(GameState::Playing),
+                place_handcrafted_maps.run_if(run_once()),
+            )
+            .add_systems(
+                Update,
+                (spawn_handcrafted_maps, emit_map_trigger_events)
+                    .run_if(in_state(GameState::Playing)),
+            );
+    }
+}
+
+/// A hand-made map exported from Tiled as JSON (`.tmj`).
+///
+/// Only embedded tilesets are supported, external `.tsj` tilesets have to be
+/// embedded from the Tiled map properties before exporting.
+#[derive(Asset, TypePath, Debug)]
+pub struct TiledMap {
+    pub size: UVec2,
+    pub tile_size: Vec2,
+    pub tilesets: Vec<TiledTileset>,
+    pub layers: Vec<TiledLayer>,
+}
+
+#[derive(Debug)]
+pub struct TiledTileset {
+    pub first_gid: u32,
+    pub tile_count: u32,
+    pub texture: Handle<Image>,
+}
+
+#[derive(Debug)]
+pub enum TiledLayer {
+    Tiles {
+        name: String,
+        data: Vec<u32>,
+    },
+    Objects {
+        name: String,
+        objects: Vec<TiledObject>,
+    },
+}
+
+#[derive(Debug, Clone)]
+pub struct TiledObject {
+    pub name: String,
+    pub kind: String,
+    /// Top-left corner in Tiled's y-down pixel space.
+    pub position: Vec2,
+    pub size: Vec2,
+    pub properties: MapProperties,
+}
+
+/// Custom properties set on a Tiled object.
+#[derive(Component, Debug, Clone, Default)]
+pub struct MapProperties(HashMap<String, serde_json::Value>);
+
+impl MapProperties {
+    pub fn get_f32(&self, name: &str) -> Option<f32> {
+        self.0.get(name).and_then(|v| v.as_f64()).map(|v| v as f32)
+    }
+
+    pub fn get_bool(&self, name: &str) -> Option<bool> {
+        self.0.get(name).and_then(|v| v.as_bool())
+    }
+
+    pub fn get_str(&self, name: &str) -> Option<&str> {
+        self.0.get(name).and_then(|v| v.as_str())
+    }
+
+    /// Tiled colors are written as `#AARRGGBB` or `#RRGGBB`.
+    pub fn get_color(&self, name: &str) -> Option<Color> {
+        let hex = self.get_str(name)?.trim_start_matches('#');
+        match hex.len() {
+            6 => Color::hex(hex).ok(),
+            8 => Color::hex(format!("{}{}", &hex[2..], &hex[..2])).ok(),
+            _ => None,
+        }
+    }
+}
+
+impl TiledMap {
+    fn tileset_for(&self, gid: u32) -> Option<(usize, u32)> {
+        self.tilesets
+            .iter()
+            .enumerate()
+            .rev()
+            .find(|(_, tileset)| gid >= tileset.first_gid)
+            .filter(|(_, tileset)| gid < tileset.first_gid + tileset.tile_count)
+            .map(|(i, tileset)| (i, gid - tileset.first_gid))
+    }
+
+    pub fn size_in_pixels(&self) -> Vec2 {
+        self.size.as_vec2() * self.tile_size
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "lowercase")]
+struct RawMap {
+    width: u32,
+    height: u32,
+    tilewidth: f32,
+    tileheight: f32,
+    #[serde(default)]
+    tilesets: Vec<RawTileset>,
+    #[serde(default)]
+    layers: Vec<RawLayer>,
+}
+
+#[derive(Deserialize)]
+struct RawTileset {
+    firstgid: u32,
+    #[serde(default)]
+    source: Option<String>,
+    #[serde(default)]
+    image: Option<String>,
+    #[serde(default)]
+    tilecount: u32,
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+enum RawLayer {
+    TileLayer {
+        name: String,
+        #[serde(default)]
+        data: Vec<u32>,
+    },
+    ObjectGroup {
+        name: String,
+        #[serde(default)]
+        objects: Vec<RawObject>,
+    },
+    #[serde(other)]
+    Unsupported,
+}
+
+#[derive(Deserialize)]
+struct RawObject {
+    #[serde(default)]
+    name: String,
+    // Tiled 1.9 writes `class`, older and newer versions write `type`
+    #[serde(default, alias = "class", rename = "type")]
+    kind: String,
+    x: f32,
+    y: f32,
+    #[serde(default)]
+    width: f32,
+    #[serde(default)]
+    height: f32,
+    #[serde(default)]
+    properties: Vec<RawProperty>,
+}
+
+#[derive(Deserialize)]
+struct RawProperty {
+    name: String,
+    value: serde_json::Value,
+}
+
+#[derive(Debug, Error)]
+pub enum TiledMapLoaderError {
+    #[error("could not read map: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("could not parse map: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("invalid tileset image path: {0}")]
+    ImagePath(#[from] ParseAssetPathError),
+    #[error("external tileset `{0}` is not supported, embed it in the map")]
+    ExternalTileset(String),
+    #[error("tileset with first gid {0} has no image")]
+    MissingImage(u32),
+    #[error("tile layer `{name}` has {found} tiles, expected {expected}")]
+    LayerSize {
+        name: String,
+        expected: usize,
+        found: usize,
+    },
+}
+
+#[derive(Default)]
+struct TiledMapLoader;
+
+impl AssetLoader for TiledMapLoader {
+    type Asset = TiledMap;
+    type Settings = ();
+    type Error = TiledMapLoaderError;
+
+    fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader,
+        _settings: &'a (),
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<Self::Asset, Self::Error>> {
+        Box::pin(async move {
+            let mut bytes = Vec::new();
+            reader.read_to_end(&mut bytes).await?;
+            let raw: RawMap = serde_json::from_slice(&bytes)?;
+
+            let mut tilesets = Vec::with_capacity(raw.tilesets.len());
+            for tileset in raw.tilesets {
+                if let Some(source) = tileset.source {
+                    return Err(TiledMapLoaderError::ExternalTileset(source));
+                }
+                let image = tileset
+                    .image
+                    .ok_or(TiledMapLoaderError::MissingImage(tileset.firstgid))?;
+                let image_path = load_context.asset_path().resolve_embed(&image)?;
+                tilesets.push(TiledTileset {
+                    first_gid: tileset.firstgid,
+                    tile_count: tileset.tilecount,
+                    texture: load_context.load(image_path),
+                });
+            }
+
+            let tile_count = raw.width as usize * raw.height as usize;
+            let mut layers = Vec::with_capacity(raw.layers.len());
+            for layer in raw.layers {
+                layers.extend(convert_layer(layer, tile_count)?);
+            }
+
+            Ok(TiledMap {
+                size: UVec2::new(raw.width, raw.height),
+                tile_size: Vec2::new(raw.tilewidth, raw.tileheight),
+                tilesets,
+                layers,
+            })
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["tmj"]
+    }
+}
+
+/// Tile layers need one entry per tile of the map, or tiles would be placed
+/// outside of it.
+fn convert_layer(
+    layer: RawLayer,
+    tile_count: usize,
+) -> Result<Option<TiledLayer>, TiledMapLoaderError> {
+    let layer = match layer {
+        RawLayer::TileLayer { name, data } => {
+            if data.len() != tile_count {
+                return Err(TiledMapLoaderError::LayerSize {
+                    name,
+                    expected: tile_count,
+                    found: data.len(),
+                });
+            }
+            TiledLayer::Tiles { name, data }
+        }
+        RawLayer::ObjectGroup { name, objects } => TiledLayer::Objects {
+            name,
+            objects: objects
+                .into_iter()
+                .map(|object| TiledObject {
+                    name: object.name,
+                    kind: object.kind,
+                    position: Vec2::new(object.x, object.y),
+                    size: Vec2::new(object.width, object.height),
+                    properties: MapProperties(
+                        object
+                            .properties
+                            .into_iter()
+                            .map(|p| (p.name, p.value))
+                            .collect(),
+                    ),
+                })
+                .collect(),
+        },
+        RawLayer::Unsupported => return Ok(None),
+    };
+    Ok(Some(layer))
+}
+
+/// Places a [`TiledMap`] in the procedural world. The offset is in chunk
+/// coordinates so the map lines up with the surrounding chunk grid.
+#[derive(Component, Debug)]
+pub struct HandcraftedMap {
+    pub map: Handle<TiledMap>,
+    pub chunk_offset: IVec2,
+}
+
+/// Marks a [`HandcraftedMap`] whose layers and objects have been spawned.
+#[derive(Component)]
+pub(crate) struct MapSpawned;
+
+/// Chunk areas occupied by handcrafted maps, procedural chunks are not
+/// generated inside them.
+#[derive(Resource, Default, Debug)]
+pub struct MapRegions(Vec<IRect>);
+
+impl MapRegions {
+    pub fn contains(&self, chunk_pos: IVec2) -> bool {
+        self.0.iter().any(|region| region.contains(chunk_pos))
+    }
+}
+
+/// A named spawn location placed in a map, e.g. for the player or NPCs.
+#[derive(Component, Debug)]
+pub struct MapSpawnPoint {
+    pub name: String,
+}
+
+/// A sensor area placed in a map that reports when the player enters or leaves it.
+#[derive(Component, Debug)]
+pub struct MapTrigger {
+    pub name: String,
+}
+
+#[derive(Event, Debug)]
+pub struct MapTriggerEvent {
+    pub trigger: Entity,
+    pub name: String,
+    pub entered: bool,
+}
+
+fn chunk_size_in_pixels() -> Vec2 {
+    CHUNK_SIZE.as_vec2() * Vec2::from(TILE_SIZE)
+}
+
+fn place_handcrafted_maps(mut commands: Commands, map_assets: Res<MapAssets>) {
+    commands.spawn((
+        Name::new("Map town"),
+        HandcraftedMap {
+            map: map_assets.town.clone(),
+            chunk_offset: TOWN_CHUNK_OFFSET,
+        },
+        SpatialBundle::default(),
+    ));
+}
+
+pub(crate) fn spawn_handcrafted_maps(
+    mut commands: Commands,
+    map_q: Query<(Entity, &HandcraftedMap), Without<MapSpawned>>,
+    maps: Res<Assets<TiledMap>>,
+    mut regions: ResMut<MapRegions>,
+) {
+    for (entity, handcrafted) in map_q.iter() {
+        let Some(map) = maps.get(&handcrafted.map) else {
+            continue;
+        };
+
+        let chunk_pixels = chunk_size_in_pixels();
+        let chunk_count = (map.size_in_pixels() / chunk_pixels).ceil().as_ivec2();
+        regions.0.push(IRect::from_corners(
+            handcrafted.chunk_offset,
+            handcrafted.chunk_offset + chunk_count - IVec2::ONE,
+        ));
+
+        // Procedural tiles are centered on their grid position, so the map's
+        // bottom-left corner sits half a procedural tile below the chunk origin.
+        let corner =
+            handcrafted.chunk_offset.as_vec2() * chunk_pixels - Vec2::from(TILE_SIZE) / 2.0;
+        let origin = corner + map.tile_size / 2.0;
+
+        commands
+            .entity(entity)
+            .insert((Transform::from_translation(corner.extend(0.0)), MapSpawned));
+
+        for (layer_index, layer) in map.layers.iter().enumerate() {
+            let z = layer_index as f32 * 0.01;
+            match layer {
+                TiledLayer::Tiles { name, data } => {
+                    spawn_tile_layer(&mut commands, entity, map, name, data, origin.extend(z));
+                }
+                TiledLayer::Objects { objects, .. } => {
+                    for object in objects {
+                        spawn_map_object(&mut commands, entity, map, object, corner);
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn spawn_tile_layer(
+    commands: &mut Commands,
+    map_entity: Entity,
+    map: &TiledMap,
+    name: &str,
+    data: &[u32],
+    translation: Vec3,
+) {
+    let size = TilemapSize::from(map.size);
+    let tile_size = TilemapTileSize::from(map.tile_size);
+
+    // bevy_ecs_tilemap renders a single texture per tilemap, so every tileset
+    // used by the layer gets its own tilemap entity.
+    let mut tilemaps: HashMap<usize, (Entity, TileStorage)> = HashMap::new();
+    for (i, raw_gid) in data.iter().enumerate() {
+        let gid = raw_gid & GID_MASK;
+        let Some((tileset_index, local_id)) = map.tileset_for(gid) else {
+            continue;
+        };
+
+        let (tilemap_entity, storage) = tilemaps
+            .entry(tileset_index)
+            .or_insert_with(|| (commands.spawn_empty().id(), TileStorage::empty(size)));
+
+        // Tiled rows go top to bottom, tilemap rows go bottom to top
+        let tile_pos = TilePos {
+            x: i as u32 % map.size.x,
+            y: map.size.y - 1 - i as u32 / map.size.x,
+        };
+        let tile_entity = commands
+            .spawn(TileBundle {
+                position: tile_pos,
+                tilemap_id: TilemapId(*tilemap_entity),
+                texture_index: TileTextureIndex(local_id),
+                flip: TileFlip {
+                    x: raw_gid & FLIPPED_HORIZONTALLY_FLAG != 0,
+                    y: raw_gid & FLIPPED_VERTICALLY_FLAG != 0,
+                    d: raw_gid & FLIPPED_DIAGONALLY_FLAG != 0,
+                },
+                ..Default::default()
+            })
+            .insert(RenderLayers::from_layers(CAMERA_LAYER_FLOOR))
+            .id();
+        commands.entity(*tilemap_entity).add_child(tile_entity);
+        storage.set(&tile_pos, tile_entity);
+    }
+
+    for (tileset_index, (tilemap_entity, storage)) in tilemaps {
+        commands
+            .entity(tilemap_entity)
+            .insert(TilemapBundle {
+                grid_size: tile_size.into(),
+                map_type: TilemapType::Square,
+                size,
+                storage,
+                texture: TilemapTexture::Single(map.tilesets[tileset_index].texture.clone()),
+                tile_size,
+                transform: Transform::from_translation(translation),
+                render_settings: TilemapRenderSettings {
+                    render_chunk_size: CHUNK_SIZE * 2,
+                    ..Default::default()
+                },
+                ..Default::default()
+            })
+            .insert(RenderLayers::from_layers(CAMERA_LAYER_FLOOR))
+            .insert(Name::new(format!("Map layer {name}")));
+        commands.entity(map_entity).add_child(tilemap_entity);
+    }
+}
+
+fn spawn_map_object(
+    commands: &mut Commands,
+    map_entity: Entity,
+    map: &TiledMap,
+    object: &TiledObject,
+    corner: Vec2,
+) {
+    // Convert the top-left, y-down Tiled position to the object's center in
+    // map space, relative to the map entity placed at the map's corner.
+    let center = Vec2::new(
+        object.position.x + object.size.x / 2.0,
+        map.size_in_pixels().y - object.position.y - object.size.y / 2.0,
+    );
+    let transform =
+        TransformBundle::from_transform(Transform::from_translation(center.extend(1.0)));
+
+    let mut entity = commands.spawn((
+        Name::new(format!("Map object {}", object.name)),
+        transform,
+        object.properties.clone(),
+        RenderLayers::from_layers(CAMERA_LAYER_OBJECTS),
+    ));
+
+    match object.kind.as_str() {
+        "spawn" => {
+            entity.insert(MapSpawnPoint {
+                name: object.name.clone(),
+            });
+        }
+        "trigger" => {
+            entity.insert((
+                MapTrigger {
+                    name: object.name.clone(),
+                },
+                Collider::cuboid(object.size.x / 2.0, object.size.y / 2.0),
+                Sensor,
+                ActiveEvents::COLLISION_EVENTS,
+            ));
+        }
+        "light" => {
+            let properties = &object.properties;
+            entity.insert(OmniLightSource2D {
+                intensity: properties.get_f32("intensity").unwrap_or(0.1),
+                color: properties.get_color("color").unwrap_or(Color::WHITE),
+                falloff: Vec3::new(
+                    properties.get_f32("falloff_x").unwrap_or(10.0),
+                    properties.get_f32("falloff_y").unwrap_or(10.0),
+                    properties.get_f32("falloff_z").unwrap_or(0.05),
+                ),
+                jitter_intensity: properties.get_f32("jitter_intensity").unwrap_or(0.0),
+                jitter_translation: properties.get_f32("jitter_translation").unwrap_or(0.0),
+            });
+        }
+        "chest" | "structure" => {
+            let properties = &object.properties;
+            entity.insert((
+                LootContainer::default(),
+                Loot {
+                    table: properties.get_str("loot").unwrap_or("chest").to_string(),
+                    item_level: properties.get_f32("item_level").unwrap_or(1.0) as u16,
+                },
+                NavObstacle {
+                    half_size: object.size / 2.0,
+                },
+            ));
+            if object.kind == "chest" {
+                entity
+                    .insert(VisibilityBundle::default())
+                    .with_children(|parent| {
+                        parent.spawn(chest_sprite());
+                    });
+            }
+        }
+        kind => {
+            bevy::log::warn!(
+                "Unknown map object type `{}` for object `{}` at {:?}",
+                kind,
+                object.name,
+                corner + center
+            );
+        }
+    }
+
+    let object_entity = entity.id();
+    commands.entity(map_entity).add_child(object_entity);
+}
+
+fn emit_map_trigger_events(
+    mut collision_events: EventReader<CollisionEvent>,
+    trigger_q: Query<&MapTrigger>,
+    player_q: Query<&Player>,
+    mut trigger_events: EventWriter<MapTriggerEvent>,
+) {
+    let Ok(player) = player_q.get_single() else {
+        return;
+    };
+
+    for event in collision_events.read() {
+        let (a, b, entered) = match *event {
+            CollisionEvent::Started(a, b, _) => (a, b, true),
+            CollisionEvent::Stopped(a, b, _) => (a, b, false),
+        };
+        let trigger = if b == player.collider_entity {
+            a
+        } else if a == player.collider_entity {
+            b
+        } else {
+            continue;
+        };
+        if let Ok(map_trigger) = trigger_q.get(trigger) {
+            trigger_events.send(MapTriggerEvent {
+                trigger,
+                name: map_trigger.name.clone(),
+                entered,
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tile_layer(data: &[u32]) -> RawLayer {
+        serde_json::from_value(serde_json::json!({
+            "type": "tilelayer",
+            "name": "ground",
+            "data": data,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn tile_layers_need_one_entry_per_tile() {
+        assert!(matches!(
+            convert_layer(tile_layer(&[1, 2, 3, 4]), 4),
+            Ok(Some(TiledLayer::Tiles { .. }))
+        ));
+        assert!(matches!(
+            convert_layer(tile_layer(&[1, 2, 3, 4, 5]), 4),
+            Err(TiledMapLoaderError::LayerSize {
+                expected: 4,
+                found: 5,
+                ..
+            })
+        ));
+        assert!(convert_layer(tile_layer(&[1, 2, 3]), 4).is_err());
+    }
+}
@@ -0,0 +1,247 @@
+use std::{cmp::Reverse, collections::BinaryHeap};
+
+use bevy::{
+    asset::Assets,
+    ecs::{
+        component::Component,
+        system::{Query, Res, SystemParam},
+    },
+    math::{IVec2, Vec2},
+    transform::components::GlobalTransform,
+    utils::{HashMap, HashSet},
+};
+
+use crate::loading::MapAssets;
+
+use super::{
+    chunk::ground_texture_index,
+    deltas::ChunkDeltas,
+    helpers::{cell_to_tile_pos, cell_to_world_pos, world_pos_to_cell},
+    map::MapRegions,
+    tile::{tile_type_to_texture_index, TileType},
+};
+
+/// Cells expanded before settling for the closest one found.
+const MAX_VISITED_CELLS: usize = 2048;
+/// How far from a blocked goal a free cell is looked for, in cells.
+const GOAL_SEARCH_RADIUS: i32 = 3;
+const STRAIGHT_COST: u32 = 10;
+const DIAGONAL_COST: u32 = 14;
+const NEIGHBOURS: [(IVec2, u32); 8] = [
+    (IVec2::new(1, 0), STRAIGHT_COST),
+    (IVec2::new(-1, 0), STRAIGHT_COST),
+    (IVec2::new(0, 1), STRAIGHT_COST),
+    (IVec2::new(0, -1), STRAIGHT_COST),
+    (IVec2::new(1, 1), DIAGONAL_COST),
+    (IVec2::new(1, -1), DIAGONAL_COST),
+    (IVec2::new(-1, 1), DIAGONAL_COST),
+    (IVec2::new(-1, -1), DIAGONAL_COST),
+];
+
+/// A map object paths go around, covering `half_size` around its center.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct NavObstacle {
+    pub half_size: Vec2,
+}
+
+/// The walkable tiles of the world. Generated water, chunk props and
+/// [`NavObstacle`]s block.
+#[derive(SystemParam)]
+pub struct NavGrid<'w, 's> {
+    map_assets: Res<'w, MapAssets>,
+    chunk_deltas: Res<'w, Assets<ChunkDeltas>>,
+    map_regions: Res<'w, MapRegions>,
+    obstacle_q: Query<'w, 's, (&'static NavObstacle, &'static GlobalTransform)>,
+}
+
+impl NavGrid<'_, '_> {
+    /// Waypoints from `from` to `to` through tile centers. Ends exactly at `to`
+    /// when it can be reached, otherwise as close to it as possible.
+    pub fn path(&self, from: Vec2, to: Vec2) -> Vec<Vec2> {
+        let obstacles: HashSet<IVec2> = self
+            .obstacle_q
+            .iter()
+            .flat_map(|(obstacle, transform)| {
+                let center = transform.translation().truncate();
+                let min = world_pos_to_cell(&(center - obstacle.half_size));
+                let max = world_pos_to_cell(&(center + obstacle.half_size));
+                (min.x..=max.x).flat_map(move |x| (min.y..=max.y).map(move |y| IVec2::new(x, y)))
+            })
+            .collect();
+        let deltas = self.chunk_deltas.get(&self.map_assets.world_deltas);
+        // the ground is sampled from noise, so each cell is only checked once
+        let mut blocked = HashMap::new();
+
+        let start = world_pos_to_cell(&from);
+        let goal = world_pos_to_cell(&to);
+        let cells = find_path(start, goal, |cell| {
+            *blocked
+                .entry(cell)
+                .or_insert_with(|| obstacles.contains(&cell) || self.is_blocked_tile(cell, deltas))
+        });
+
+        let reached = cells.last().copied().unwrap_or(start) == goal;
+        let mut waypoints: Vec<Vec2> = cells.into_iter().map(cell_to_world_pos).collect();
+        if reached {
+            waypoints.pop();
+            waypoints.push(to);
+        }
+        waypoints
+    }
+
+    fn is_blocked_tile(&self, cell: IVec2, deltas: Option<&ChunkDeltas>) -> bool {
+        let (chunk_pos, tile_pos) = cell_to_tile_pos(cell);
+        // handcrafted maps only block with their objects
+        if self.map_regions.contains(chunk_pos) {
+            return false;
+        }
+        deltas
+            .and_then(|deltas| deltas.prop(chunk_pos, tile_pos))
+            .is_some()
+            || ground_texture_index(chunk_pos, tile_pos, deltas)
+                == tile_type_to_texture_index(TileType::Water)
+    }
+}
+
+/// Octile distance, matching the cost of moving in eight directions.
+fn heuristic(from: IVec2, to: IVec2) -> u32 {
+    let delta = (to - from).abs();
+    let diagonal = delta.x.min(delta.y) as u32;
+    let straight = delta.x.max(delta.y) as u32 - diagonal;
+    diagonal * DIAGONAL_COST + straight * STRAIGHT_COST
+}
+
+/// The free cell closest to `start` around a blocked `goal`.
+fn free_cell_near(
+    goal: IVec2,
+    start: IVec2,
+    is_blocked: &mut impl FnMut(IVec2) -> bool,
+) -> Option<IVec2> {
+    (1..=GOAL_SEARCH_RADIUS).find_map(|radius| {
+        (-radius..=radius)
+            .flat_map(|x| (-radius..=radius).map(move |y| IVec2::new(x, y)))
+            .filter(|offset| offset.x.abs() == radius || offset.y.abs() == radius)
+            .map(|offset| goal + offset)
+            .filter(|cell| !is_blocked(*cell))
+            .min_by_key(|cell| heuristic(*cell, start))
+    })
+}
+
+/// A* over the tile grid in eight directions, never cutting a blocked corner.
+/// Returns the cells to walk through after `start`, up to `goal` or, when it
+/// can't be reached, to the closest reachable cell.
+fn find_path(start: IVec2, goal: IVec2, mut is_blocked: impl FnMut(IVec2) -> bool) -> Vec<IVec2> {
+    let goal = if is_blocked(goal) {
+        free_cell_near(goal, start, &mut is_blocked).unwrap_or(goal)
+    } else {
+        goal
+    };
+
+    let mut open = BinaryHeap::new();
+    let mut costs = HashMap::from([(start, 0)]);
+    let mut came_from = HashMap::new();
+    let mut closest = (heuristic(start, goal), start);
+    open.push(Reverse((heuristic(start, goal), 0, start.x, start.y)));
+
+    let mut visited = 0;
+    while let Some(Reverse((_, cost, x, y))) = open.pop() {
+        let cell = IVec2::new(x, y);
+        if cell == goal || visited == MAX_VISITED_CELLS {
+            break;
+        }
+        // skips entries superseded by a cheaper way in
+        if costs.get(&cell).is_some_and(|best| *best < cost) {
+            continue;
+        }
+        visited += 1;
+
+        for (offset, step) in NEIGHBOURS {
+            let next = cell + offset;
+            let cuts_corner = offset.x != 0
+                && offset.y != 0
+                && (is_blocked(cell + IVec2::new(offset.x, 0))
+                    || is_blocked(cell + IVec2::new(0, offset.y)));
+            let next_cost = cost + step;
+            if cuts_corner
+                || costs.get(&next).is_some_and(|best| *best <= next_cost)
+                || is_blocked(next)
+            {
+                continue;
+            }
+            costs.insert(next, next_cost);
+            came_from.insert(next, cell);
+
+            let remaining = heuristic(next, goal);
+            if remaining < closest.0 {
+                closest = (remaining, next);
+            }
+            open.push(Reverse((next_cost + remaining, next_cost, next.x, next.y)));
+        }
+    }
+
+    let mut path = vec![];
+    let mut cell = closest.1;
+    while let Some(previous) = came_from.get(&cell) {
+        path.push(cell);
+        cell = *previous;
+    }
+    path.reverse();
+    path
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A vertical wall at x = 2 from y = -2 to y = 2.
+    fn wall(cell: IVec2) -> bool {
+        cell.x == 2 && (-2..=2).contains(&cell.y)
+    }
+
+    #[test]
+    fn walks_straight_when_nothing_blocks() {
+        let path = find_path(IVec2::ZERO, IVec2::new(4, 0), |_| false);
+        assert_eq!(
+            path,
+            vec![
+                IVec2::new(1, 0),
+                IVec2::new(2, 0),
+                IVec2::new(3, 0),
+                IVec2::new(4, 0)
+            ]
+        );
+    }
+
+    #[test]
+    fn goes_around_walls_without_cutting_corners() {
+        let path = find_path(IVec2::ZERO, IVec2::new(4, 0), wall);
+        assert_eq!(path.last(), Some(&IVec2::new(4, 0)));
+        assert!(!path.iter().any(|cell| wall(*cell)));
+
+        let mut previous = IVec2::ZERO;
+        for cell in path {
+            let step = cell - previous;
+            assert!(step.x.abs() <= 1 && step.y.abs() <= 1);
+            assert!(!wall(previous + IVec2::new(step.x, 0)));
+            assert!(!wall(previous + IVec2::new(0, step.y)));
+            previous = cell;
+        }
+    }
+
+    #[test]
+    fn stops_next_to_a_blocked_goal() {
+        let path = find_path(IVec2::ZERO, IVec2::new(2, 0), wall);
+        assert_eq!(path, vec![IVec2::new(1, 0)]);
+    }
+
+    #[test]
+    fn gets_as_close_as_possible_to_enclosed_goals() {
+        let goal = IVec2::new(10, 0);
+        let ring = |cell: IVec2| {
+            let offset = (cell - goal).abs();
+            offset.x.max(offset.y) == 5
+        };
+        let path = find_path(IVec2::ZERO, goal, ring);
+        assert_eq!(path.last(), Some(&IVec2::new(4, 0)));
+    }
+}
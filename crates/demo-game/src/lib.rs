@@ -8,8 +8,9 @@ use bevy_rapier2d::plugin::{NoUserData, RapierPhysicsPlugin};
 use bevy_trickfilm::Animation2DPlugin;
 
 use demo_framework::{
-    camera::CameraPlugin, loading::LoadingPlugin, player::PlayerPlugin, ui::UiPlugin,
-    world::WorldPlugin, GameState,
+    animation::CharacterAnimationPlugin, camera::CameraPlugin, damage::DamagePlugin,
+    item::ItemPlugin, loading::LoadingPlugin, player::PlayerPlugin, projectile::ProjectilePlugin,
+    skill::SkillPlugin, status::StatusPlugin, ui::UiPlugin, world::WorldPlugin, GameState,
 };
 use discord::{ActivityState, DiscordClient};
 
@@ -28,14 +29,19 @@ impl Plugin for GamePlugin {
             .add_plugins((
                 TilemapPlugin,
                 RapierPhysicsPlugin::<NoUserData>::pixels_per_meter(100.0),
-                Animation2DPlugin,
+                (Animation2DPlugin, CharacterAnimationPlugin),
                 BevyMagicLight2DPlugin,
                 LoadingPlugin,
                 discord::DiscordPlugin::new(app_id, true),
                 UiPlugin,
                 CameraPlugin,
+                DamagePlugin,
                 WorldPlugin,
                 PlayerPlugin,
+                ItemPlugin,
+                ProjectilePlugin,
+                SkillPlugin,
+                StatusPlugin,
             ))
             .add_systems(OnEnter(GameState::Playing), update_presence);
 